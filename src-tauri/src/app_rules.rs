@@ -0,0 +1,462 @@
+use crate::config::Config;
+use crate::types::{ActiveApplication, AppMatcher, AppRule, MatchField, MatchKind};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::{Arc, Mutex};
+
+/// Which app rule applies to the focused application, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleResolution {
+    pub rule: Option<AppRule>,
+    pub reason: String,
+    /// Every enabled rule that matched, highest priority first
+    pub matched_rule_ids: Vec<String>,
+}
+
+/// Why `rule` matches `app`, or `None` if it doesn't. Used both by the
+/// resolver and by `test_app_rule`, so the settings preview can't disagree
+/// with what dictation does.
+pub fn rule_matches(rule: &AppRule, app: &ActiveApplication) -> Option<String> {
+    let patterns: Vec<Result<Pattern, String>> = rule.matchers.iter().map(Pattern::compile).collect();
+    rule_matches_compiled(rule, &patterns, app)
+}
+
+/// `rule_matches` with the rule's matcher patterns already compiled, in order
+fn rule_matches_compiled(
+    rule: &AppRule,
+    patterns: &[Result<Pattern, String>],
+    app: &ActiveApplication,
+) -> Option<String> {
+    if !app.name.to_lowercase().contains(&rule.app_name.to_lowercase()) {
+        return None;
+    }
+
+    let mut reasons = vec![format!("app name contains \"{}\"", rule.app_name)];
+    if let Some(exe) = &rule.executable {
+        if !app.executable.to_lowercase().contains(&exe.to_lowercase()) {
+            return None;
+        }
+        reasons.push(format!("executable contains \"{}\"", exe));
+    }
+
+    for (matcher, pattern) in rule.matchers.iter().zip(patterns) {
+        match pattern {
+            Ok(pattern) if pattern.matches(matcher, app) => reasons.push(describe(matcher)),
+            _ => return None,
+        }
+    }
+
+    Some(reasons.join(" and "))
+}
+
+/// A matcher's pattern, compiled once so resolving rules on every focus
+/// change and recording doesn't rebuild regexes and globs
+enum Pattern {
+    /// Lowercased
+    Contains(String),
+    Regex(Regex),
+    Glob(GlobMatcher),
+}
+
+impl Pattern {
+    fn compile(matcher: &AppMatcher) -> Result<Self, String> {
+        Ok(match matcher.kind {
+            MatchKind::Contains => Pattern::Contains(matcher.pattern.to_lowercase()),
+            MatchKind::Regex => Pattern::Regex(
+                RegexBuilder::new(&matcher.pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| e.to_string())?,
+            ),
+            MatchKind::Glob => Pattern::Glob(
+                GlobBuilder::new(&matcher.pattern)
+                    .case_insensitive(true)
+                    .literal_separator(false)
+                    .build()
+                    .map_err(|e| e.to_string())?
+                    .compile_matcher(),
+            ),
+        })
+    }
+
+    /// Whether `app` satisfies `matcher`, whose pattern this is, negation included
+    fn matches(&self, matcher: &AppMatcher, app: &ActiveApplication) -> bool {
+        let value = match matcher.field {
+            MatchField::AppName => app.name.as_str(),
+            MatchField::Executable => app.executable_path.as_deref().unwrap_or(&app.executable),
+            MatchField::WindowTitle => app.title.as_str(),
+        };
+
+        let found = match self {
+            Pattern::Contains(pattern) => value.to_lowercase().contains(pattern),
+            Pattern::Regex(regex) => regex.is_match(value),
+            Pattern::Glob(glob) => glob.is_match(value),
+        };
+        found != matcher.negate
+    }
+}
+
+/// Whether `app` satisfies the matcher, negation included. Fails if the
+/// pattern doesn't compile.
+pub fn matcher_matches(matcher: &AppMatcher, app: &ActiveApplication) -> Result<bool, String> {
+    Ok(Pattern::compile(matcher)?.matches(matcher, app))
+}
+
+/// Compile the matcher's pattern, for validating rules before they're saved
+pub fn check_pattern(matcher: &AppMatcher) -> Result<(), String> {
+    Pattern::compile(matcher).map(|_| ())
+}
+
+/// Compiled matcher patterns of each rule, in rule order
+type RulePatterns = Vec<Vec<Result<Pattern, String>>>;
+
+type RulePatternCache = Option<(Vec<AppRule>, Arc<RulePatterns>)>;
+
+/// The configured rules' patterns compiled, reusing the last compilation
+/// until the rules in the config change
+fn compiled_patterns(rules: &[AppRule]) -> Arc<RulePatterns> {
+    static CACHE: Mutex<RulePatternCache> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached, compiled)) = cache.as_ref() {
+        if cached == rules {
+            return compiled.clone();
+        }
+    }
+    let compiled = Arc::new(
+        rules
+            .iter()
+            .map(|rule| rule.matchers.iter().map(Pattern::compile).collect())
+            .collect::<RulePatterns>(),
+    );
+    *cache = Some((rules.to_vec(), compiled.clone()));
+    compiled
+}
+
+fn describe(matcher: &AppMatcher) -> String {
+    let field = match matcher.field {
+        MatchField::AppName => "app name",
+        MatchField::Executable => "executable",
+        MatchField::WindowTitle => "window title",
+    };
+    let kind = match (matcher.kind, matcher.negate) {
+        (MatchKind::Contains, false) => "contains",
+        (MatchKind::Contains, true) => "does not contain",
+        (MatchKind::Regex, false) => "matches regex",
+        (MatchKind::Regex, true) => "does not match regex",
+        (MatchKind::Glob, false) => "matches glob",
+        (MatchKind::Glob, true) => "does not match glob",
+    };
+    format!("{} {} \"{}\"", field, kind, matcher.pattern)
+}
+
+/// Pick the enabled rule with the highest priority that matches `app`.
+/// Rules earlier in the list win ties.
+pub fn resolve(config: &Config, app: Option<&ActiveApplication>) -> RuleResolution {
+    let unresolved = |reason: &str| RuleResolution {
+        rule: None,
+        reason: reason.to_string(),
+        matched_rule_ids: vec![],
+    };
+
+    if !config.enable_app_rules {
+        return unresolved("app rules are disabled");
+    }
+    let Some(app) = app else {
+        return unresolved("no focused application detected");
+    };
+
+    let patterns = compiled_patterns(&config.app_rules);
+    let mut matched: Vec<(&AppRule, String)> = config
+        .app_rules
+        .iter()
+        .zip(patterns.iter())
+        .filter(|(rule, _)| rule.enabled)
+        .filter_map(|(rule, patterns)| rule_matches_compiled(rule, patterns, app).map(|reason| (rule, reason)))
+        .collect();
+    // Stable, so equal priorities keep their list order
+    matched.sort_by_key(|(rule, _)| std::cmp::Reverse(rule.priority));
+
+    let matched_rule_ids = matched.iter().map(|(rule, _)| rule.id.clone()).collect();
+    match matched.into_iter().next() {
+        Some((rule, reason)) => RuleResolution {
+            rule: Some(rule.clone()),
+            reason: format!("{}: {} (priority {})", app.name, reason, rule.priority),
+            matched_rule_ids,
+        },
+        None => unresolved(&format!("no enabled rule matches {}", app.name)),
+    }
+}
+
+/// Refuse a rule that sets a shortcut, when it is created or edited. The
+/// recording shortcut is registered once for every app, so it can't change
+/// with the one in focus.
+pub fn check_rule(rule: &AppRule) -> Result<(), String> {
+    let shortcut_fields: Vec<&str> = [
+        ("shortcut", &rule.shortcut),
+        ("holdKey", &rule.hold_key),
+        ("keyCombination", &rule.key_combination),
+    ]
+    .into_iter()
+    .filter(|(_, value)| value.as_deref().is_some_and(|v| !v.is_empty()))
+    .map(|(field, _)| field)
+    .collect();
+
+    if shortcut_fields.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "App rules can't change the recording shortcut; clear {} on rule \"{}\"",
+            shortcut_fields.join(", "),
+            rule.app_name
+        ))
+    }
+}
+
+/// The settings a rule overrides, as a partial config. The shortcut fields
+/// aren't among them, see `check_rule`; older configs may still carry them.
+pub fn overrides(rule: &AppRule) -> Value {
+    let mut overrides = Map::new();
+    let mut set = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            overrides.insert(key.to_string(), value);
+        }
+    };
+
+    set("sttProviderId", rule.stt_provider_id.clone().map(Value::from));
+    set(
+        "transcriptPostProcessingEnabled",
+        rule.transcript_post_processing_enabled.map(Value::from),
+    );
+    set(
+        "transcriptPostProcessingProviderId",
+        rule.transcript_post_processing_provider_id.clone().map(Value::from),
+    );
+    set(
+        "transcriptPostProcessingPrompt",
+        rule.transcript_post_processing_prompt.clone().map(Value::from),
+    );
+    set("autoInsert", rule.auto_insert.map(Value::from));
+    set("contextFormatting", rule.context_formatting.clone().filter(|v| v.is_object()));
+
+    Value::Object(overrides)
+}
+
+/// `config` with the rule's overrides merged in, if there is a rule
+pub fn effective_config(config: Config, rule: Option<&AppRule>) -> Config {
+    match rule {
+        Some(rule) => config.merged_with(&overrides(rule)),
+        None => config,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn app(name: &str, executable_path: &str, title: &str) -> ActiveApplication {
+        ActiveApplication {
+            name: name.to_string(),
+            executable: executable_path.rsplit('/').next().unwrap_or_default().to_string(),
+            executable_path: Some(executable_path.to_string()),
+            title: title.to_string(),
+            last_updated: 0,
+        }
+    }
+
+    fn rule(id: &str, app_name: &str, priority: i32, matchers: Vec<AppMatcher>) -> AppRule {
+        AppRule {
+            id: id.to_string(),
+            app_name: app_name.to_string(),
+            executable: None,
+            enabled: true,
+            shortcut: None,
+            hold_key: None,
+            key_combination: None,
+            stt_provider_id: None,
+            transcript_post_processing_enabled: None,
+            transcript_post_processing_provider_id: None,
+            transcript_post_processing_prompt: None,
+            auto_insert: None,
+            priority,
+            context_formatting: None,
+            matchers,
+            extra: Map::new(),
+        }
+    }
+
+    fn matcher(field: MatchField, kind: MatchKind, pattern: &str, negate: bool) -> AppMatcher {
+        AppMatcher {
+            field,
+            kind,
+            pattern: pattern.to_string(),
+            negate,
+        }
+    }
+
+    #[test]
+    fn name_and_executable_match_case_insensitively() {
+        let firefox = app("Firefox", "/usr/lib/firefox/firefox", "Inbox - Mail");
+        assert!(rule_matches(&rule("r", "fire", 0, vec![]), &firefox).is_some());
+
+        let mut with_exe = rule("r", "", 0, vec![]);
+        with_exe.executable = Some("FIREFOX".to_string());
+        assert!(rule_matches(&with_exe, &firefox).is_some());
+        with_exe.executable = Some("chrome".to_string());
+        assert!(rule_matches(&with_exe, &firefox).is_none());
+    }
+
+    #[test]
+    fn matchers_by_field_and_kind() {
+        let editor = app("Code", "/usr/share/code/code", "main.rs - whispo - Visual Studio Code");
+        let cases = [
+            (matcher(MatchField::WindowTitle, MatchKind::Contains, "WHISPO", false), true),
+            (matcher(MatchField::WindowTitle, MatchKind::Regex, r"^\S+\.rs - whispo ", false), true),
+            (matcher(MatchField::WindowTitle, MatchKind::Regex, r"^\S+\.ts ", false), false),
+            (matcher(MatchField::Executable, MatchKind::Glob, "/usr/share/*/code", false), true),
+            (matcher(MatchField::Executable, MatchKind::Glob, "*/bin/*", false), false),
+            (matcher(MatchField::AppName, MatchKind::Glob, "co*", false), true),
+            (matcher(MatchField::AppName, MatchKind::Regex, "^code$", false), true),
+        ];
+
+        for (matcher, expected) in cases {
+            assert_eq!(matcher_matches(&matcher, &editor), Ok(expected), "{:?}", matcher);
+        }
+    }
+
+    #[test]
+    fn negation_inverts_the_match() {
+        let browser = app("Chrome", "/opt/google/chrome/chrome", "YouTube - Google Chrome");
+        let not_youtube = matcher(MatchField::WindowTitle, MatchKind::Contains, "youtube", true);
+        assert_eq!(matcher_matches(&not_youtube, &browser), Ok(false));
+
+        let docs = app("Chrome", "/opt/google/chrome/chrome", "Design doc - Google Docs");
+        assert_eq!(matcher_matches(&not_youtube, &docs), Ok(true));
+        assert_eq!(
+            rule_matches(&rule("r", "chrome", 0, vec![not_youtube]), &docs).as_deref(),
+            Some("app name contains \"chrome\" and window title does not contain \"youtube\"")
+        );
+    }
+
+    #[test]
+    fn every_matcher_must_hold() {
+        let editor = app("Code", "/usr/share/code/code", "notes.md - blog");
+        let matchers = vec![
+            matcher(MatchField::WindowTitle, MatchKind::Glob, "*.md - *", false),
+            matcher(MatchField::WindowTitle, MatchKind::Contains, "whispo", false),
+        ];
+        assert!(rule_matches(&rule("r", "code", 0, matchers), &editor).is_none());
+    }
+
+    #[test]
+    fn invalid_patterns_never_match() {
+        let editor = app("Code", "/usr/share/code/code", "main.rs");
+        let broken = matcher(MatchField::WindowTitle, MatchKind::Regex, "(unclosed", true);
+        assert!(matcher_matches(&broken, &editor).is_err());
+        assert!(check_pattern(&broken).is_err());
+        assert!(rule_matches(&rule("r", "", 0, vec![broken]), &editor).is_none());
+    }
+
+    #[test]
+    fn resolver_uses_the_shared_matchers_and_priority() {
+        let config = Config {
+            enable_app_rules: true,
+            app_rules: vec![
+                rule("any-chrome", "chrome", 1, vec![]),
+                rule(
+                    "meet",
+                    "chrome",
+                    5,
+                    vec![matcher(MatchField::WindowTitle, MatchKind::Regex, "^Meet - ", false)],
+                ),
+            ],
+            ..Config::default()
+        };
+
+        let meet = app("Chrome", "/opt/google/chrome/chrome", "Meet - standup");
+        let resolution = resolve(&config, Some(&meet));
+        assert_eq!(resolution.rule.map(|r| r.id).as_deref(), Some("meet"));
+        assert_eq!(resolution.matched_rule_ids, vec!["meet", "any-chrome"]);
+
+        let mail = app("Chrome", "/opt/google/chrome/chrome", "Inbox");
+        assert_eq!(resolve(&config, Some(&mail)).rule.map(|r| r.id).as_deref(), Some("any-chrome"));
+    }
+
+    #[test]
+    fn rules_override_typed_settings() {
+        let mut terminal = rule("terminal", "konsole", 0, vec![]);
+        terminal.auto_insert = Some(false);
+        terminal.stt_provider_id = Some("groq".to_string());
+        terminal.context_formatting = Some(json!({ "enabled": true, "context": "terminal" }));
+        terminal.shortcut = Some("key-combination".to_string());
+
+        let config = effective_config(Config::default(), Some(&terminal));
+        assert!(!config.auto_insert);
+        assert_eq!(config.stt_provider_id, "groq");
+        assert!(config.context_formatting.enabled);
+        assert_eq!(config.context_formatting.context.as_deref(), Some("terminal"));
+        assert_eq!(config.shortcut, Config::default().shortcut);
+
+        let unchanged = effective_config(Config::default(), None);
+        assert!(unchanged.auto_insert);
+        assert_eq!(unchanged.context_formatting.context, None);
+    }
+
+    #[test]
+    fn resolving_follows_edited_patterns() {
+        let editor = app("Code", "/usr/share/code/code", "main.rs - whispo");
+        let mut config = Config {
+            enable_app_rules: true,
+            app_rules: vec![rule(
+                "rust",
+                "code",
+                0,
+                vec![matcher(MatchField::WindowTitle, MatchKind::Regex, r"\.rs\b", false)],
+            )],
+            ..Config::default()
+        };
+        assert_eq!(resolve(&config, Some(&editor)).rule.map(|r| r.id).as_deref(), Some("rust"));
+        assert_eq!(resolve(&config, Some(&editor)).rule.map(|r| r.id).as_deref(), Some("rust"));
+
+        config.app_rules[0].matchers[0].pattern = r"\.ts\b".to_string();
+        assert!(resolve(&config, Some(&editor)).rule.is_none());
+
+        config.app_rules[0].matchers[0].pattern = "(".to_string();
+        assert!(resolve(&config, Some(&editor)).rule.is_none());
+    }
+
+    #[test]
+    fn refuses_rules_that_set_a_shortcut() {
+        let mut terminal = rule("terminal", "konsole", 0, vec![]);
+        assert_eq!(check_rule(&terminal), Ok(()));
+
+        // The settings form sends an empty value for "use default"
+        terminal.shortcut = Some(String::new());
+        assert_eq!(check_rule(&terminal), Ok(()));
+
+        terminal.shortcut = Some("hold-key".to_string());
+        terminal.hold_key = Some("AltLeft+KeyD".to_string());
+        let error = check_rule(&terminal).unwrap_err();
+        assert!(error.contains("shortcut, holdKey on rule \"konsole\""), "{}", error);
+
+        terminal.shortcut = None;
+        terminal.hold_key = None;
+        terminal.key_combination = Some("Ctrl+Shift+Space".to_string());
+        assert!(check_rule(&terminal).unwrap_err().contains("keyCombination"));
+    }
+
+    #[test]
+    fn matchers_deserialize_from_camel_case() {
+        let matcher: AppMatcher = serde_json::from_value(json!({
+            "field": "windowTitle",
+            "kind": "glob",
+            "pattern": "*whispo*",
+        }))
+        .unwrap();
+        assert_eq!(matcher.field, MatchField::WindowTitle);
+        assert!(!matcher.negate);
+    }
+}
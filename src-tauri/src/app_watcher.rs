@@ -0,0 +1,149 @@
+use crate::platform;
+use crate::types::ActiveApplication;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// A window has to keep focus this long to count, so alt-tabbing past
+/// several apps only reports the one the user lands on
+const DEBOUNCE: Duration = Duration::from_millis(600);
+
+/// Turns polled focus samples into settled focus changes
+#[derive(Debug, Default)]
+pub struct FocusDebouncer {
+    current: Option<FocusKey>,
+    pending: Option<(FocusKey, Instant)>,
+}
+
+type FocusKey = (String, String, String);
+
+fn focus_key(app: &ActiveApplication) -> FocusKey {
+    (app.name.clone(), app.executable.clone(), app.title.clone())
+}
+
+impl FocusDebouncer {
+    /// Whether `app` has now been focused for long enough and differs from
+    /// the last app reported
+    pub fn observe(&mut self, app: &ActiveApplication, now: Instant) -> bool {
+        let key = focus_key(app);
+        if self.current.as_ref() == Some(&key) {
+            self.pending = None;
+            return false;
+        }
+
+        match &self.pending {
+            Some((pending, since)) if *pending == key => {
+                if now.duration_since(*since) < DEBOUNCE {
+                    return false;
+                }
+                self.current = Some(key);
+                self.pending = None;
+                true
+            }
+            _ => {
+                self.pending = Some((key, now));
+                false
+            }
+        }
+    }
+}
+
+/// Whether `app` is one of Whispo's own windows, like the recording panel
+pub fn is_own_application(app: &ActiveApplication) -> bool {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+        .is_some_and(|own| own == app.executable)
+}
+
+/// Poll the focused application in the background and call `on_change`
+/// whenever a different one settles into focus. Whispo's own windows are
+/// ignored, so showing the recording panel doesn't count as a switch.
+pub fn watch(on_change: impl Fn(ActiveApplication) + Send + 'static) {
+    thread::spawn(move || {
+        let mut debouncer = FocusDebouncer::default();
+        loop {
+            if let Ok(app) = platform::get_active_application() {
+                if !is_own_application(&app) && debouncer.observe(&app, Instant::now()) {
+                    on_change(app);
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, title: &str) -> ActiveApplication {
+        ActiveApplication {
+            name: name.to_string(),
+            executable: name.to_lowercase(),
+            executable_path: None,
+            title: title.to_string(),
+            last_updated: 0,
+        }
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn reports_an_app_once_it_keeps_focus() {
+        let start = Instant::now();
+        let mut debouncer = FocusDebouncer::default();
+        let editor = app("Code", "main.rs");
+
+        assert!(!debouncer.observe(&editor, start));
+        assert!(!debouncer.observe(&editor, ms(start, 300)));
+        assert!(debouncer.observe(&editor, ms(start, 600)));
+        // Already reported
+        assert!(!debouncer.observe(&editor, ms(start, 900)));
+        assert!(!debouncer.observe(&editor, ms(start, 5_000)));
+    }
+
+    #[test]
+    fn alt_tabbing_past_apps_reports_only_the_last() {
+        let start = Instant::now();
+        let mut debouncer = FocusDebouncer::default();
+        let (editor, browser, terminal) = (app("Code", "main.rs"), app("Firefox", "Docs"), app("Konsole", "~"));
+
+        assert!(!debouncer.observe(&editor, start));
+        assert!(!debouncer.observe(&browser, ms(start, 300)));
+        assert!(!debouncer.observe(&terminal, ms(start, 600)));
+        // Each switch restarts the wait
+        assert!(!debouncer.observe(&terminal, ms(start, 900)));
+        assert!(debouncer.observe(&terminal, ms(start, 1_200)));
+    }
+
+    #[test]
+    fn coming_back_before_the_wait_is_not_a_change() {
+        let start = Instant::now();
+        let mut debouncer = FocusDebouncer::default();
+        let (editor, browser) = (app("Code", "main.rs"), app("Firefox", "Docs"));
+        debouncer.observe(&editor, start);
+        assert!(debouncer.observe(&editor, ms(start, 600)));
+
+        assert!(!debouncer.observe(&browser, ms(start, 900)));
+        assert!(!debouncer.observe(&editor, ms(start, 1_200)));
+        // The browser has to start its wait over
+        assert!(!debouncer.observe(&browser, ms(start, 1_600)));
+        assert!(!debouncer.observe(&browser, ms(start, 2_000)));
+        assert!(debouncer.observe(&browser, ms(start, 2_200)));
+    }
+
+    #[test]
+    fn a_new_window_title_is_a_change() {
+        let start = Instant::now();
+        let mut debouncer = FocusDebouncer::default();
+        debouncer.observe(&app("Code", "main.rs"), start);
+        assert!(debouncer.observe(&app("Code", "main.rs"), ms(start, 600)));
+
+        assert!(!debouncer.observe(&app("Code", "lib.rs"), ms(start, 700)));
+        assert!(debouncer.observe(&app("Code", "lib.rs"), ms(start, 1_300)));
+    }
+}
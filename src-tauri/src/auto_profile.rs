@@ -0,0 +1,284 @@
+use crate::platform;
+use crate::types::{ActivationCondition, ActiveApplication, ProfilesData, SettingsProfile};
+use chrono::{DateTime, Datelike, Local, NaiveTime, Timelike};
+use std::path::Path;
+use std::time::Duration;
+
+/// How often the background evaluator checks activation rules
+pub const EVALUATE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// What activation conditions are checked against
+#[derive(Debug, Clone)]
+pub struct ActivationContext {
+    pub now: DateTime<Local>,
+    pub active_app: Option<ActiveApplication>,
+}
+
+impl ActivationContext {
+    /// The current time and, if any rule needs it, the foreground application
+    pub fn current(profiles: &[SettingsProfile]) -> Self {
+        let needs_app = enabled_conditions(profiles).any(|c| matches!(c, ActivationCondition::App { .. }));
+
+        Self {
+            now: Local::now(),
+            active_app: if needs_app {
+                platform::get_active_application().ok()
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Whether any profile could be switched to automatically
+pub fn has_rules(profiles: &[SettingsProfile]) -> bool {
+    enabled_conditions(profiles).next().is_some()
+}
+
+fn enabled_conditions(profiles: &[SettingsProfile]) -> impl Iterator<Item = &ActivationCondition> {
+    profiles
+        .iter()
+        .filter_map(|p| p.activation.as_ref())
+        .filter(|a| a.enabled)
+        .flat_map(|a| a.conditions.iter())
+}
+
+/// Why the condition holds, or `None` if it doesn't
+pub fn check(condition: &ActivationCondition, context: &ActivationContext) -> Option<String> {
+    match condition {
+        ActivationCondition::Schedule { days, start, end } => {
+            let weekday = context.now.weekday().number_from_monday();
+            if !days.is_empty() && !days.contains(&weekday) {
+                return None;
+            }
+
+            let start = NaiveTime::parse_from_str(start, "%H:%M").ok()?;
+            let end = NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+            let now = NaiveTime::from_hms_opt(context.now.hour(), context.now.minute(), 0)?;
+            let within = if start <= end {
+                start <= now && now < end
+            } else {
+                now >= start || now < end
+            };
+
+            within.then(|| format!("schedule {}-{}", start.format("%H:%M"), end.format("%H:%M")))
+        }
+        ActivationCondition::App { app_name, executable } => {
+            let app = context.active_app.as_ref()?;
+            let contains = |actual: &str, expected: &Option<String>| {
+                expected
+                    .as_ref()
+                    .map(|e| actual.to_lowercase().contains(&e.to_lowercase()))
+                    .unwrap_or(true)
+            };
+
+            if app_name.is_none() && executable.is_none() {
+                return None;
+            }
+            (contains(&app.name, app_name) && contains(&app.executable, executable))
+                .then(|| format!("{} is in the foreground", app.name))
+        }
+        ActivationCondition::EnvVar { name, value } => {
+            let actual = std::env::var(name).ok()?;
+            match value {
+                Some(expected) if &actual != expected => None,
+                Some(expected) => Some(format!("{}={}", name, expected)),
+                None => Some(format!("{} is set", name)),
+            }
+        }
+        ActivationCondition::FileExists { path } => {
+            Path::new(path).exists().then(|| format!("{} exists", path))
+        }
+    }
+}
+
+/// The profile the rules pick and why: the highest-priority enabled
+/// profile whose conditions all hold, the earliest one on ties
+pub fn select(profiles: &[SettingsProfile], context: &ActivationContext) -> Option<(String, String)> {
+    let mut best: Option<(i32, String, String)> = None;
+
+    for profile in profiles {
+        let Some(activation) = profile.activation.as_ref().filter(|a| a.enabled) else {
+            continue;
+        };
+        if activation.conditions.is_empty() {
+            continue;
+        }
+
+        let reasons: Option<Vec<String>> = activation.conditions.iter().map(|c| check(c, context)).collect();
+        let Some(reasons) = reasons else {
+            continue;
+        };
+
+        if best.as_ref().map(|(priority, _, _)| activation.priority > *priority).unwrap_or(true) {
+            best = Some((activation.priority, profile.id.clone(), reasons.join(", ")));
+        }
+    }
+
+    best.map(|(_, id, reason)| (id, reason))
+}
+
+/// The switch the rules call for, if any. A pinned profile is left alone,
+/// and a profile is only switched off again if the rules switched it on.
+pub fn decide(data: &ProfilesData, context: &ActivationContext) -> Option<(Option<String>, String)> {
+    if data.pinned {
+        return None;
+    }
+
+    match select(&data.profiles, context) {
+        Some((id, reason)) if data.active_profile_id.as_ref() != Some(&id) => Some((Some(id), reason)),
+        Some(_) => None,
+        None => {
+            let switched_by_rules = data.last_switch.as_ref().map(|s| s.automatic).unwrap_or(false);
+            (data.active_profile_id.is_some() && switched_by_rules)
+                .then(|| (None, "no activation rule matches".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigStore;
+    use crate::types::{ProfileActivation, ProfileSwitch};
+    use crate::vault::Vault;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// 2024-01-01 was a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> ActivationContext {
+        ActivationContext {
+            now: Local.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap(),
+            active_app: None,
+        }
+    }
+
+    fn schedule(days: &[u32], start: &str, end: &str) -> ActivationCondition {
+        ActivationCondition::Schedule {
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn profile(id: &str, priority: i32, conditions: Vec<ActivationCondition>) -> SettingsProfile {
+        SettingsProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            config: json!({}),
+            parent_id: None,
+            activation: Some(ProfileActivation {
+                enabled: true,
+                conditions,
+                priority,
+            }),
+            created_at: 0,
+            updated_at: 0,
+            is_default: None,
+        }
+    }
+
+    #[test]
+    fn schedules_match_their_window_and_days() {
+        let office = schedule(&[1, 2, 3, 4, 5], "09:00", "17:00");
+        assert!(check(&office, &at(1, 9, 0)).is_some());
+        assert!(check(&office, &at(5, 16, 59)).is_some());
+        assert!(check(&office, &at(1, 17, 0)).is_none());
+        assert!(check(&office, &at(1, 8, 59)).is_none());
+        // Saturday
+        assert!(check(&office, &at(6, 12, 0)).is_none());
+
+        let daily = schedule(&[], "09:00", "17:00");
+        assert!(check(&daily, &at(7, 12, 0)).is_some());
+        assert!(check(&schedule(&[], "9am", "17:00"), &at(1, 12, 0)).is_none());
+    }
+
+    #[test]
+    fn schedules_wrap_past_midnight() {
+        let night = schedule(&[], "22:00", "06:00");
+        assert!(check(&night, &at(1, 22, 0)).is_some());
+        assert!(check(&night, &at(1, 23, 59)).is_some());
+        assert!(check(&night, &at(2, 0, 0)).is_some());
+        assert!(check(&night, &at(2, 5, 59)).is_some());
+        assert!(check(&night, &at(2, 6, 0)).is_none());
+        assert!(check(&night, &at(2, 12, 0)).is_none());
+    }
+
+    #[test]
+    fn picks_the_highest_priority_match() {
+        let profiles = vec![
+            profile("everyday", 0, vec![schedule(&[], "00:00", "23:59")]),
+            profile("office", 5, vec![schedule(&[1, 2, 3, 4, 5], "09:00", "17:00")]),
+            profile("also-office", 5, vec![schedule(&[], "09:00", "17:00")]),
+            profile("never", 10, vec![schedule(&[], "09:00", "17:00"), schedule(&[7], "09:00", "17:00")]),
+            profile("no-conditions", 20, vec![]),
+        ];
+
+        let (id, reason) = select(&profiles, &at(1, 10, 0)).unwrap();
+        assert_eq!(id, "office");
+        assert_eq!(reason, "schedule 09:00-17:00");
+        assert_eq!(select(&profiles, &at(6, 10, 0)).unwrap().0, "also-office");
+        assert_eq!(select(&profiles, &at(1, 20, 0)).unwrap().0, "everyday");
+
+        let mut disabled = profiles.clone();
+        for profile in disabled.iter_mut() {
+            profile.activation.as_mut().unwrap().enabled = false;
+        }
+        assert_eq!(select(&disabled, &at(1, 10, 0)), None);
+    }
+
+    #[test]
+    fn decides_when_to_switch_and_back() {
+        let mut data = ProfilesData {
+            profiles: vec![profile("office", 0, vec![schedule(&[], "09:00", "17:00")])],
+            ..Default::default()
+        };
+
+        let (id, _) = decide(&data, &at(1, 10, 0)).unwrap();
+        assert_eq!(id.as_deref(), Some("office"));
+
+        data.active_profile_id = Some("office".to_string());
+        data.last_switch = Some(ProfileSwitch {
+            profile_id: Some("office".to_string()),
+            automatic: true,
+            reason: String::new(),
+            switched_at: 0,
+        });
+        assert_eq!(decide(&data, &at(1, 11, 0)), None);
+        assert_eq!(decide(&data, &at(1, 18, 0)).unwrap().0, None);
+
+        // A profile the user picked stays on when the rules stop matching
+        data.last_switch.as_mut().unwrap().automatic = false;
+        assert_eq!(decide(&data, &at(1, 18, 0)), None);
+    }
+
+    #[test]
+    fn a_manual_switch_pins_the_profile() {
+        let dir = std::env::temp_dir().join(format!("whispo-auto-profile-pin-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = ConfigStore::new(dir.clone(), Arc::new(Vault::new(&dir))).unwrap();
+        let office = store.create_profile("Office".to_string(), None, None, None).unwrap();
+        let home = store.create_profile("Home".to_string(), None, None, None).unwrap();
+        let activation = json!({
+            "enabled": true,
+            "conditions": [{ "type": "schedule", "start": "09:00", "end": "17:00" }],
+        });
+        store.update_profile(office.id.clone(), json!({ "activation": activation })).unwrap();
+
+        let switch = store.apply_activation_rules(&at(1, 10, 0)).unwrap().unwrap();
+        assert_eq!(switch.profile_id.as_deref(), Some(office.id.as_str()));
+        assert!(switch.automatic);
+        assert!(!store.is_profile_pinned());
+
+        store.switch_profile(home.id.clone()).unwrap();
+        assert!(store.is_profile_pinned());
+        assert!(store.apply_activation_rules(&at(1, 11, 0)).unwrap().is_none());
+        assert_eq!(store.get_active_profile_id(), Some(home.id));
+
+        store.set_profile_pinned(false).unwrap();
+        let switch = store.apply_activation_rules(&at(1, 12, 0)).unwrap().unwrap();
+        assert_eq!(switch.profile_id, Some(office.id));
+    }
+}
@@ -0,0 +1,668 @@
+use crate::config::ConfigStore;
+use crate::history::{HistoryStore, HISTORY_FILE, RECORDINGS_DIR};
+use crate::trash::Trash;
+use crate::types::ProfilesData;
+use crate::vault::{Vault, DEFAULT_KEY_FILE, VAULT_FILE};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bumped whenever the archive layout changes in a way older builds can't read
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const CONFIG_FILE: &str = "config.json";
+const PROFILES_FILE: &str = "profiles.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: i64,
+    pub entries: Vec<BackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestoreMode {
+    /// Replace current data with the backup; the current data goes to the trash
+    Overwrite,
+    /// Keep current data and add whatever the backup has that is missing
+    Merge,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub config_restored: bool,
+    pub profiles_added: usize,
+    pub recordings_added: usize,
+    pub trash_entries: Vec<String>,
+}
+
+/// Write config, profiles, history and audio from `app_data_dir` into a zip
+/// archive. With the vault enabled, vault.json goes along so the encrypted
+/// data can be read again, and so does the key file if it's kept in
+/// `app_data_dir`; a passphrase still has to be entered after restoring.
+/// A key file kept elsewhere has to be restored by hand.
+pub fn create_backup(app_data_dir: &Path, destination: &Path) -> Result<BackupManifest> {
+    let file = File::create(destination)
+        .with_context(|| format!("Failed to create backup file {}", destination.display()))?;
+    let mut writer = ZipWriter::new(file);
+    let mut entries = Vec::new();
+
+    for relative in collect_backup_files(app_data_dir)? {
+        let data = fs::read(app_data_dir.join(&relative))?;
+        let method = if relative.ends_with(".webm") {
+            // Audio is already compressed
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+
+        writer.start_file(relative.as_str(), SimpleFileOptions::default().compression_method(method))?;
+        writer.write_all(&data)?;
+
+        entries.push(BackupEntry {
+            path: relative,
+            size: data.len() as u64,
+            sha256: sha256_hex(&data),
+        });
+    }
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64,
+        entries,
+    };
+
+    writer.start_file(MANIFEST_NAME, SimpleFileOptions::default())?;
+    writer.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+    writer.finish()?;
+
+    Ok(manifest)
+}
+
+/// Check the archive version and every entry's size and checksum
+pub fn verify_backup(archive_path: &Path) -> Result<BackupManifest> {
+    let mut archive = open_archive(archive_path)?;
+    let manifest = read_manifest(&mut archive)?;
+
+    for entry in &manifest.entries {
+        validate_entry_path(&entry.path)?;
+        let data = read_entry(&mut archive, &entry.path)?;
+
+        if data.len() as u64 != entry.size || sha256_hex(&data) != entry.sha256 {
+            anyhow::bail!("Backup entry {} is corrupt (checksum mismatch)", entry.path);
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Restore a verified backup into `app_data_dir`. config.json and
+/// profiles.json are written through the config store, which reloads them.
+pub fn restore_backup(
+    app_data_dir: &Path,
+    archive_path: &Path,
+    mode: RestoreMode,
+    vault: &Vault,
+    config_store: &ConfigStore,
+    history_store: &HistoryStore,
+    trash: &Trash,
+) -> Result<RestoreReport> {
+    let manifest = verify_backup(archive_path)?;
+    let staging_dir = app_data_dir.join(format!(".restore-{}", manifest.created_at));
+
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+
+    let result = extract_to(archive_path, &manifest, &staging_dir).and_then(|_| match mode {
+        RestoreMode::Overwrite => {
+            restore_overwrite(app_data_dir, &staging_dir, vault, config_store, history_store, trash)
+        }
+        RestoreMode::Merge => {
+            restore_merge(app_data_dir, &staging_dir, config_store, history_store)
+        }
+    });
+
+    let _ = fs::remove_dir_all(&staging_dir);
+    result
+}
+
+fn restore_overwrite(
+    app_data_dir: &Path,
+    staging_dir: &Path,
+    vault: &Vault,
+    config_store: &ConfigStore,
+    history_store: &HistoryStore,
+    trash: &Trash,
+) -> Result<RestoreReport> {
+    let mut report = RestoreReport::default();
+    let files = [CONFIG_FILE, PROFILES_FILE, VAULT_FILE, DEFAULT_KEY_FILE]
+        .map(|name| app_data_dir.join(name));
+    let files: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+
+    if let Some(entry) = trash.trash_config_files(&files, "restore")? {
+        report.trash_entries.push(entry.id);
+    }
+    if let Some(entry) = trash.trash_recordings_folder(history_store, "restore")? {
+        report.trash_entries.push(entry.id);
+    }
+
+    // The backup's data is encrypted with the backup's vault, so that comes
+    // first. A passphrase vault is locked until the user unlocks it.
+    let encrypted = staging_dir.join(VAULT_FILE).exists();
+    if encrypted {
+        vault.restore_from(staging_dir)?;
+    }
+
+    let (config, profiles) = read_config_files(staging_dir)?;
+    report.config_restored = config.is_some();
+    report.profiles_added = profiles.as_ref().map(|p| p.profiles.len()).unwrap_or(0);
+    if config.is_some() || profiles.is_some() {
+        config_store.restore(config, profiles)?;
+    }
+
+    // Encrypted recordings are moved into place as they are, since they
+    // can't be read while a passphrase vault is locked
+    let staged_recordings = staging_dir.join(RECORDINGS_DIR);
+    if encrypted && staged_recordings.exists() && !history_store.recordings_folder().exists() {
+        fs::rename(&staged_recordings, history_store.recordings_folder())
+            .context("Failed to move restored recordings into place")?;
+        report.recordings_added = history_store.load().map(|h| h.len()).unwrap_or(0);
+    } else if staged_recordings.exists() {
+        let incoming = history_store.read_history_at(&staged_recordings.join(HISTORY_FILE))?;
+        report.recordings_added = history_store.import(incoming, Some(&staged_recordings))?;
+    }
+
+    Ok(report)
+}
+
+fn restore_merge(
+    app_data_dir: &Path,
+    staging_dir: &Path,
+    config_store: &ConfigStore,
+    history_store: &HistoryStore,
+) -> Result<RestoreReport> {
+    let mut report = RestoreReport::default();
+
+    // Merged data has to share one key
+    let staged_vault = staging_dir.join(VAULT_FILE);
+    let current_vault = app_data_dir.join(VAULT_FILE);
+    if staged_vault.exists() && fs::read(&staged_vault).ok() != fs::read(&current_vault).ok() {
+        anyhow::bail!(
+            "This backup was encrypted with a different vault; restore it in overwrite mode instead"
+        );
+    }
+
+    let (backup_config, backup_profiles) = read_config_files(staging_dir)?;
+
+    // Config: current values win, the backup only fills in missing keys
+    let mut config = None;
+    if let Some(backup) = backup_config {
+        let mut current = config_store.user_config();
+        if fill_missing(&mut current, &backup) {
+            config = Some(current);
+            report.config_restored = true;
+        }
+    }
+
+    // Profiles: add the ones whose id isn't known yet
+    let mut profiles = None;
+    if let Some(backup) = backup_profiles {
+        let mut current = config_store.profiles_data();
+        if current.profiles.is_empty() {
+            current.version = backup.version;
+        }
+
+        for profile in backup.profiles {
+            if !current.profiles.iter().any(|p| p.id == profile.id) {
+                current.profiles.push(profile);
+                report.profiles_added += 1;
+            }
+        }
+
+        if report.profiles_added > 0 {
+            profiles = Some(current);
+        }
+    }
+
+    if config.is_some() || profiles.is_some() {
+        config_store.restore(config, profiles)?;
+    }
+
+    // History and audio: union by recording id
+    let staged_recordings = staging_dir.join(RECORDINGS_DIR);
+    if staged_recordings.exists() {
+        let incoming = history_store.read_history_at(&staged_recordings.join(HISTORY_FILE))?;
+        report.recordings_added = history_store.import(incoming, Some(&staged_recordings))?;
+    }
+
+    Ok(report)
+}
+
+/// Recursively copy keys from `source` that `target` doesn't have.
+/// Returns true if anything was added.
+fn fill_missing(target: &mut Value, source: &Value) -> bool {
+    let (Some(target_obj), Some(source_obj)) = (target.as_object_mut(), source.as_object()) else {
+        return false;
+    };
+
+    let mut changed = false;
+    for (key, value) in source_obj {
+        match target_obj.get_mut(key) {
+            Some(existing) => changed |= fill_missing(existing, value),
+            None => {
+                target_obj.insert(key.clone(), value.clone());
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// config.json and profiles.json from a staged backup or a trash entry, when there
+pub fn read_config_files(dir: &Path) -> Result<(Option<Value>, Option<ProfilesData>)> {
+    let config_path = dir.join(CONFIG_FILE);
+    let config = if config_path.exists() {
+        let content = fs::read_to_string(&config_path)?;
+        let config = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+        Some(config)
+    } else {
+        None
+    };
+
+    let profiles_path = dir.join(PROFILES_FILE);
+    let profiles = if profiles_path.exists() {
+        Some(load_profiles(&profiles_path)?)
+    } else {
+        None
+    };
+
+    Ok((config, profiles))
+}
+
+fn collect_backup_files(app_data_dir: &Path) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+
+    for name in [CONFIG_FILE, PROFILES_FILE, VAULT_FILE, DEFAULT_KEY_FILE] {
+        if app_data_dir.join(name).exists() {
+            files.push(name.to_string());
+        }
+    }
+
+    let recordings_folder = app_data_dir.join(RECORDINGS_DIR);
+    if recordings_folder.exists() {
+        let mut recordings = Vec::new();
+        for entry in fs::read_dir(&recordings_folder)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_file() && (name == HISTORY_FILE || name.ends_with(".webm")) {
+                recordings.push(format!("{}/{}", RECORDINGS_DIR, name));
+            }
+        }
+        recordings.sort();
+        files.extend(recordings);
+    }
+
+    Ok(files)
+}
+
+fn extract_to(archive_path: &Path, manifest: &BackupManifest, staging_dir: &Path) -> Result<()> {
+    let mut archive = open_archive(archive_path)?;
+
+    for entry in &manifest.entries {
+        let data = read_entry(&mut archive, &entry.path)?;
+        let target: PathBuf = staging_dir.join(&entry.path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(target, data)?;
+    }
+
+    Ok(())
+}
+
+/// Only the files we write ourselves are allowed, which also rules out path traversal
+fn validate_entry_path(path: &str) -> Result<()> {
+    let allowed = match path.split_once('/') {
+        None => [CONFIG_FILE, PROFILES_FILE, VAULT_FILE, DEFAULT_KEY_FILE].contains(&path),
+        Some((dir, name)) => {
+            dir == RECORDINGS_DIR
+                && !name.contains(['/', '\\'])
+                && !name.starts_with('.')
+                && (name == HISTORY_FILE || name.ends_with(".webm"))
+        }
+    };
+
+    if !allowed {
+        anyhow::bail!("Unexpected entry in backup: {}", path);
+    }
+    Ok(())
+}
+
+fn open_archive(archive_path: &Path) -> Result<ZipArchive<File>> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open backup {}", archive_path.display()))?;
+    ZipArchive::new(file).context("Backup is not a valid archive")
+}
+
+fn read_manifest(archive: &mut ZipArchive<File>) -> Result<BackupManifest> {
+    let data = read_entry(archive, MANIFEST_NAME)?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&data).context("Backup manifest is invalid")?;
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        anyhow::bail!(
+            "Backup format version {} is newer than supported version {}",
+            manifest.format_version,
+            BACKUP_FORMAT_VERSION
+        );
+    }
+
+    Ok(manifest)
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(name)
+        .with_context(|| format!("Backup is missing {}", name))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn load_profiles(path: &Path) -> Result<ProfilesData> {
+    let content = fs::read_to_string(path)?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::RecordingHistoryItem;
+    use crate::vault::VaultKeySource;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("whispo-backup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn recording(id: &str) -> RecordingHistoryItem {
+        RecordingHistoryItem {
+            id: id.to_string(),
+            created_at: 1,
+            duration: 1.0,
+            transcript: format!("transcript {}", id),
+            original_transcript: None,
+            is_original_shown: None,
+            pinned: None,
+            provider_id: None,
+            latency_ms: None,
+            app_name: None,
+            cost_usd: None,
+        }
+    }
+
+    struct Stores {
+        vault: Arc<Vault>,
+        config: ConfigStore,
+        history: HistoryStore,
+        trash: Trash,
+    }
+
+    fn stores(app_data_dir: &Path) -> Stores {
+        let vault = Arc::new(Vault::new(app_data_dir));
+        Stores {
+            config: ConfigStore::new(app_data_dir.to_path_buf(), vault.clone()).unwrap(),
+            history: HistoryStore::new(app_data_dir, vault.clone()),
+            trash: Trash::new(app_data_dir),
+            vault,
+        }
+    }
+
+    fn restore(
+        dir: &Path,
+        target: &Stores,
+        archive: &Path,
+        mode: RestoreMode,
+    ) -> Result<RestoreReport> {
+        let Stores { vault, config, history, trash } = target;
+        restore_backup(dir, archive, mode, vault, config, history, trash)
+    }
+
+    /// An install with a changed setting, one profile and one recording
+    fn populated(name: &str) -> PathBuf {
+        let dir = temp_dir(name);
+        let source = stores(&dir);
+        let mut config = source.config.get();
+        config["transcriptPostProcessingPrompt"] = json!("Restored prompt");
+        source.config.save(config).unwrap();
+        source.config.create_profile("Work".to_string(), None, None, None).unwrap();
+        source.history.save(&[recording("rec-1")]).unwrap();
+        source.history.save_audio("rec-1", b"audio").unwrap();
+        dir
+    }
+
+    #[test]
+    fn manifest_lists_every_file_with_its_checksum() {
+        let source = populated("manifest");
+        let archive = source.join("backup.zip");
+        let manifest = create_backup(&source, &archive).unwrap();
+
+        let paths: Vec<&str> = manifest.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["config.json", "profiles.json", "recordings/history.json", "recordings/rec-1.webm"]
+        );
+        let audio = manifest.entries.last().unwrap();
+        assert_eq!(audio.size, 5);
+        assert_eq!(audio.sha256, sha256_hex(b"audio"));
+        assert_eq!(manifest.format_version, BACKUP_FORMAT_VERSION);
+
+        assert_eq!(verify_backup(&archive).unwrap().entries.len(), 4);
+    }
+
+    #[test]
+    fn verify_rejects_a_corrupt_entry() {
+        let dir = temp_dir("corrupt");
+        let archive = dir.join("backup.zip");
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: "test".to_string(),
+            created_at: 0,
+            entries: vec![BackupEntry {
+                path: CONFIG_FILE.to_string(),
+                size: 2,
+                sha256: sha256_hex(b"{}"),
+            }],
+        };
+
+        let mut writer = ZipWriter::new(File::create(&archive).unwrap());
+        writer.start_file(CONFIG_FILE, SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"[]").unwrap();
+        writer.start_file(MANIFEST_NAME, SimpleFileOptions::default()).unwrap();
+        writer.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+        writer.finish().unwrap();
+
+        let error = verify_backup(&archive).unwrap_err().to_string();
+        assert!(error.contains("checksum mismatch"), "{}", error);
+    }
+
+    #[test]
+    fn only_our_own_files_are_allowed() {
+        for path in [
+            "config.json",
+            "profiles.json",
+            "vault.json",
+            "vault.key",
+            "recordings/history.json",
+            "recordings/abc.webm",
+        ] {
+            assert!(validate_entry_path(path).is_ok(), "{}", path);
+        }
+        for path in [
+            "../config.json",
+            "recordings/../config.json",
+            "recordings/.hidden.webm",
+            "recordings/nested/abc.webm",
+            "notes.txt",
+        ] {
+            assert!(validate_entry_path(path).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn overwrite_restores_into_the_running_stores() {
+        let source = populated("overwrite-source");
+        let archive = source.join("backup.zip");
+        create_backup(&source, &archive).unwrap();
+
+        let target_dir = temp_dir("overwrite-target");
+        let target = stores(&target_dir);
+        target.history.save(&[recording("rec-old")]).unwrap();
+
+        let report = restore(&target_dir, &target, &archive, RestoreMode::Overwrite).unwrap();
+
+        assert!(report.config_restored);
+        assert_eq!(report.profiles_added, 1);
+        assert_eq!(report.recordings_added, 1);
+        assert_eq!(report.trash_entries.len(), 1);
+        assert_eq!(target.config.config().transcript_post_processing_prompt, "Restored prompt");
+        assert_eq!(target.config.get_profiles()[0].name, "Work");
+        let ids: Vec<String> = target.history.load().unwrap().into_iter().map(|i| i.id).collect();
+        assert_eq!(ids, ["rec-1"]);
+        assert_eq!(target.history.read_audio("rec-1").unwrap(), b"audio");
+
+        // The store saw the restored file, so saving again keeps it
+        target.config.save(target.config.get()).unwrap();
+        assert_eq!(target.config.config().transcript_post_processing_prompt, "Restored prompt");
+    }
+
+    #[test]
+    fn merge_keeps_current_values_and_adds_what_is_missing() {
+        let source = populated("merge-source");
+        let archive = source.join("backup.zip");
+        create_backup(&source, &archive).unwrap();
+
+        let target_dir = temp_dir("merge-target");
+        let target = stores(&target_dir);
+        let mut config = target.config.get();
+        config["transcriptPostProcessingPrompt"] = json!("Current prompt");
+        target.config.save(config).unwrap();
+        target.history.save(&[recording("rec-old")]).unwrap();
+
+        let report = restore(&target_dir, &target, &archive, RestoreMode::Merge).unwrap();
+
+        assert_eq!(report.profiles_added, 1);
+        assert_eq!(report.recordings_added, 1);
+        assert!(report.trash_entries.is_empty());
+        assert_eq!(target.config.config().transcript_post_processing_prompt, "Current prompt");
+        assert_eq!(target.history.load().unwrap().len(), 2);
+    }
+
+    /// An install with the vault on, an API key and one recording
+    fn encrypted(name: &str, key_source: VaultKeySource) -> PathBuf {
+        let dir = temp_dir(name);
+        let source = stores(&dir);
+        source.vault.enable(key_source, Some("secret"), None).unwrap();
+        let mut config = source.config.get();
+        config["openaiApiKey"] = json!("sk-test-1234");
+        source.config.save(config).unwrap();
+        source.history.add(recording("rec-1"), b"audio").unwrap();
+        dir
+    }
+
+    #[test]
+    fn encrypted_backups_bring_their_vault() {
+        let source = encrypted("key-file-source", VaultKeySource::KeyFile);
+        let archive = source.join("backup.zip");
+        let manifest = create_backup(&source, &archive).unwrap();
+        assert!(manifest.entries.iter().any(|e| e.path == VAULT_FILE));
+        assert!(manifest.entries.iter().any(|e| e.path == DEFAULT_KEY_FILE));
+
+        let target_dir = temp_dir("key-file-target");
+        let target = stores(&target_dir);
+        restore(&target_dir, &target, &archive, RestoreMode::Overwrite).unwrap();
+
+        assert!(target.vault.status().unlocked);
+        assert_eq!(target.config.config().openai_api_key, "sk-test-1234");
+        assert_eq!(target.history.read_audio("rec-1").unwrap(), b"audio");
+    }
+
+    #[test]
+    fn passphrase_backups_restore_locked() {
+        let source = encrypted("passphrase-source", VaultKeySource::Passphrase);
+        let archive = source.join("backup.zip");
+        create_backup(&source, &archive).unwrap();
+
+        let target_dir = temp_dir("passphrase-target");
+        let target = stores(&target_dir);
+        let report = restore(&target_dir, &target, &archive, RestoreMode::Overwrite).unwrap();
+        assert!(report.config_restored);
+
+        let status = target.vault.status();
+        assert!(status.enabled && !status.unlocked);
+        assert!(crate::vault::is_encrypted_string(&target.config.config().openai_api_key));
+
+        target.vault.unlock("secret").unwrap();
+        assert_eq!(target.config.config().openai_api_key, "sk-test-1234");
+        assert_eq!(target.history.load().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn merge_refuses_a_different_vault() {
+        let source = encrypted("merge-vault-source", VaultKeySource::KeyFile);
+        let archive = source.join("backup.zip");
+        create_backup(&source, &archive).unwrap();
+
+        let target_dir = temp_dir("merge-vault-target");
+        let target = stores(&target_dir);
+        assert!(restore(&target_dir, &target, &archive, RestoreMode::Merge).is_err());
+        assert!(!target.vault.is_enabled());
+    }
+
+    #[test]
+    fn fill_missing_only_adds_keys() {
+        let mut current = json!({ "a": 1, "nested": { "b": 2 } });
+        let backup = json!({ "a": 9, "c": 3, "nested": { "b": 9, "d": 4 } });
+        assert!(fill_missing(&mut current, &backup));
+        assert_eq!(current, json!({ "a": 1, "c": 3, "nested": { "b": 2, "d": 4 } }));
+        assert!(!fill_missing(&mut current, &backup));
+    }
+}
@@ -0,0 +1,358 @@
+use crate::mcp::types::GlossaryEntry;
+use crate::types::{AppRule, ProfileActivation, SettingsProfile};
+use crate::vault::SECRET_KEYS;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+pub const BUNDLE_FORMAT: &str = "whispo-profiles";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A set of profiles that can be moved to another machine. Each profile
+/// carries the app rules and glossary it dictates with, so the receiver gets
+/// the whole setup rather than overrides of settings they don't have.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: i64,
+    pub profiles: Vec<BundledProfile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Only set when the parent is part of the same bundle
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Overrides without app rules and glossary, secrets stripped or templated
+    #[serde(default)]
+    pub config: Value,
+    #[serde(default)]
+    pub app_rules: Vec<AppRule>,
+    #[serde(default)]
+    pub glossary: Vec<GlossaryEntry>,
+    #[serde(default)]
+    pub activation: Option<ProfileActivation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleFormat {
+    Json,
+    Toml,
+}
+
+impl BundleFormat {
+    /// TOML for `.toml` files, JSON otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => BundleFormat::Toml,
+            _ => BundleFormat::Json,
+        }
+    }
+}
+
+/// What happens to API keys on export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretHandling {
+    /// Leave them out; the receiver's own keys apply
+    Strip,
+    /// Replace them with `${OPENAI_API_KEY}`-style placeholders that are
+    /// filled from the environment on import
+    Template,
+}
+
+/// What to do with an imported profile whose id already exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportConflict {
+    /// Import under a new id, keeping the existing profile
+    Rename,
+    /// Overwrite the existing profile
+    Replace,
+    /// Keep the existing profile and drop the imported one
+    Skip,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Ids of the profiles now in profiles.json, after renaming
+    pub imported: Vec<String>,
+    /// (bundle id, new id) pairs
+    pub renamed: Vec<(String, String)>,
+    pub replaced: Vec<String>,
+    pub skipped: Vec<String>,
+    /// Templated secrets with no matching environment variable; the
+    /// local key applies instead
+    pub missing_secrets: Vec<String>,
+}
+
+/// Placeholder written for a secret, e.g. `${OPENAI_API_KEY}` for "openaiApiKey"
+pub fn secret_placeholder(key: &str) -> String {
+    let mut name = String::new();
+    for c in key.chars() {
+        if c.is_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    format!("${{{}}}", name)
+}
+
+/// Remove or template the secrets in a profile's overrides
+pub fn scrub_secrets(config: &mut Value, handling: SecretHandling) {
+    let Value::Object(map) = config else {
+        return;
+    };
+    for key in SECRET_KEYS {
+        match (map.get(*key), handling) {
+            (None, _) => {}
+            (Some(Value::String(s)), SecretHandling::Template) if !s.is_empty() => {
+                map.insert(key.to_string(), Value::String(secret_placeholder(key)));
+            }
+            _ => {
+                map.remove(*key);
+            }
+        }
+    }
+}
+
+/// Fill templated secrets from the environment, as read by `env`.
+/// Placeholders without a variable are removed and returned, so the local key
+/// applies instead.
+pub fn resolve_secrets(config: &mut Value, env: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    let mut missing = Vec::new();
+    let Value::Object(map) = config else {
+        return missing;
+    };
+
+    for key in SECRET_KEYS {
+        let placeholder = secret_placeholder(key);
+        if map.get(*key).and_then(|v| v.as_str()) != Some(placeholder.as_str()) {
+            continue;
+        }
+        let variable = &placeholder[2..placeholder.len() - 1];
+        match env(variable) {
+            Some(value) if !value.is_empty() => {
+                map.insert(key.to_string(), Value::String(value));
+            }
+            _ => {
+                map.remove(*key);
+                missing.push(variable.to_string());
+            }
+        }
+    }
+    missing
+}
+
+/// Take the API keys out of imported overrides. Profiles don't hold keys,
+/// so the caller decides where they go.
+pub fn take_secrets(config: &mut Value) -> Map<String, Value> {
+    let mut secrets = Map::new();
+    if let Value::Object(map) = config {
+        for key in SECRET_KEYS {
+            if let Some(value) = map.remove(*key) {
+                secrets.insert(key.to_string(), value);
+            }
+        }
+    }
+    secrets
+}
+
+impl BundledProfile {
+    /// Split a profile's overrides into config, app rules and glossary
+    pub fn new(
+        profile: &SettingsProfile,
+        mut config: Value,
+        app_rules: Vec<AppRule>,
+        glossary: Vec<GlossaryEntry>,
+    ) -> Self {
+        if let Value::Object(map) = &mut config {
+            map.remove("appRules");
+            map.remove("glossary");
+        }
+        Self {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            description: profile.description.clone(),
+            parent_id: None,
+            config,
+            app_rules,
+            glossary,
+            activation: None,
+        }
+    }
+
+    /// The profile's overrides with its app rules and glossary folded back in
+    pub fn overrides(&self) -> Result<Value> {
+        let mut config = match &self.config {
+            Value::Object(_) => self.config.clone(),
+            _ => Value::Object(Default::default()),
+        };
+        config["appRules"] = serde_json::to_value(&self.app_rules)?;
+        config["glossary"] = serde_json::to_value(&self.glossary)?;
+        Ok(config)
+    }
+}
+
+impl ProfileBundle {
+    pub fn serialize(&self, format: BundleFormat) -> Result<String> {
+        match format {
+            BundleFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            BundleFormat::Toml => {
+                // TOML has no null; a missing key means the same thing here
+                let mut value = serde_json::to_value(self)?;
+                strip_nulls(&mut value);
+                Ok(toml::to_string_pretty(&value)?)
+            }
+        }
+    }
+
+    pub fn parse(content: &str, format: BundleFormat) -> Result<Self> {
+        let bundle: ProfileBundle = match format {
+            BundleFormat::Json => serde_json::from_str(content).context("Invalid profile bundle")?,
+            BundleFormat::Toml => toml::from_str(content).context("Invalid profile bundle")?,
+        };
+
+        if bundle.format != BUNDLE_FORMAT {
+            anyhow::bail!("Not a Whispo profile bundle");
+        }
+        if bundle.version > BUNDLE_VERSION {
+            anyhow::bail!("Profile bundle was written by a newer version of Whispo");
+        }
+        Ok(bundle)
+    }
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, item| !item.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle() -> ProfileBundle {
+        let profile = |id: &str, parent_id: Option<&str>| -> BundledProfile {
+            serde_json::from_value(json!({
+                "id": id,
+                "name": format!("{} profile", id),
+                "parentId": parent_id,
+                "config": {
+                    "shortcut": "key-combination",
+                    "voiceActivation": { "sensitivity": 70 },
+                    "openaiApiKey": "${OPENAI_API_KEY}",
+                },
+                "appRules": [{
+                    "id": "rule-1",
+                    "appName": "Slack",
+                    "executable": null,
+                    "enabled": true,
+                    "shortcut": null,
+                    "holdKey": null,
+                    "keyCombination": null,
+                    "sttProviderId": "groq",
+                    "transcriptPostProcessingEnabled": null,
+                    "transcriptPostProcessingProviderId": null,
+                    "transcriptPostProcessingPrompt": null,
+                    "autoInsert": true,
+                    "priority": 1,
+                    "contextFormatting": null,
+                }],
+                "glossary": [{ "term": "whispo", "replacement": "Whispo", "context": null }],
+                "activation": {
+                    "enabled": true,
+                    "conditions": [{ "type": "schedule", "days": [1, 2], "start": "09:00", "end": "17:00" }],
+                    "priority": 2,
+                },
+            }))
+            .unwrap()
+        };
+        ProfileBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: 1_700_000_000_000,
+            profiles: vec![profile("work", None), profile("meetings", Some("work"))],
+        }
+    }
+
+    #[test]
+    fn round_trips_json_and_toml() {
+        let original = serde_json::to_value(bundle()).unwrap();
+        for format in [BundleFormat::Json, BundleFormat::Toml] {
+            let content = bundle().serialize(format).unwrap();
+            let parsed = ProfileBundle::parse(&content, format).unwrap();
+            assert_eq!(serde_json::to_value(parsed).unwrap(), original, "{:?}", format);
+        }
+        assert!(ProfileBundle::parse(&bundle().serialize(BundleFormat::Toml).unwrap(), BundleFormat::Json).is_err());
+    }
+
+    #[test]
+    fn picks_the_format_from_the_extension() {
+        assert_eq!(BundleFormat::from_path(Path::new("profiles.toml")), BundleFormat::Toml);
+        assert_eq!(BundleFormat::from_path(Path::new("profiles.TOML")), BundleFormat::Toml);
+        assert_eq!(BundleFormat::from_path(Path::new("profiles.json")), BundleFormat::Json);
+        assert_eq!(BundleFormat::from_path(Path::new("profiles")), BundleFormat::Json);
+    }
+
+    #[test]
+    fn refuses_other_files_and_newer_bundles() {
+        let mut other = bundle();
+        other.format = "something-else".to_string();
+        let content = other.serialize(BundleFormat::Json).unwrap();
+        assert!(ProfileBundle::parse(&content, BundleFormat::Json).is_err());
+
+        let mut newer = bundle();
+        newer.version = BUNDLE_VERSION + 1;
+        let content = newer.serialize(BundleFormat::Json).unwrap();
+        assert!(ProfileBundle::parse(&content, BundleFormat::Json).is_err());
+    }
+
+    #[test]
+    fn strips_or_templates_secrets() {
+        let config = json!({ "openaiApiKey": "sk-openai", "groqApiKey": "", "shortcut": "hold-key" });
+
+        let mut stripped = config.clone();
+        scrub_secrets(&mut stripped, SecretHandling::Strip);
+        assert_eq!(stripped, json!({ "shortcut": "hold-key" }));
+
+        let mut templated = config.clone();
+        scrub_secrets(&mut templated, SecretHandling::Template);
+        assert_eq!(templated, json!({ "openaiApiKey": "${OPENAI_API_KEY}", "shortcut": "hold-key" }));
+        assert_eq!(secret_placeholder("geminiApiKey"), "${GEMINI_API_KEY}");
+    }
+
+    #[test]
+    fn fills_templated_secrets_from_the_environment() {
+        let env = |name: &str| (name == "GEMINI_API_KEY").then(|| "gm-from-env".to_string());
+        let mut config = json!({
+            "geminiApiKey": "${GEMINI_API_KEY}",
+            "openaiApiKey": "${OPENAI_API_KEY}",
+            "shortcut": "hold-key",
+        });
+        let missing = resolve_secrets(&mut config, env);
+        assert_eq!(missing, ["OPENAI_API_KEY"]);
+        assert_eq!(config, json!({ "geminiApiKey": "gm-from-env", "shortcut": "hold-key" }));
+
+        let secrets = take_secrets(&mut config);
+        assert_eq!(config, json!({ "shortcut": "hold-key" }));
+        assert_eq!(Value::Object(secrets), json!({ "geminiApiKey": "gm-from-env" }));
+    }
+}
@@ -0,0 +1,522 @@
+use super::schema::FieldError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+const ENV_PREFIX: &str = "WHISPO_";
+/// Overrides where the system file is read from, mostly for testing deployments
+pub const SYSTEM_CONFIG_ENV: &str = "WHISPO_SYSTEM_CONFIG";
+
+/// Sources of configuration, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigLayer {
+    Defaults,
+    System,
+    User,
+    Environment,
+    Profile,
+}
+
+/// Org-wide settings managed by an administrator, e.g.
+///
+/// ```json
+/// { "config": { "openaiBaseUrl": "https://llm.example.com/v1" }, "locked": ["openaiBaseUrl"] }
+/// ```
+///
+/// Locked keys are dotted paths that no higher layer can override.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SystemConfig {
+    pub config: Value,
+    pub locked: Vec<String>,
+}
+
+pub fn system_config_path() -> PathBuf {
+    if let Ok(path) = std::env::var(SYSTEM_CONFIG_ENV) {
+        return PathBuf::from(path);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let program_data = std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+        PathBuf::from(program_data).join("Whispo").join("config.json")
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/Whispo/config.json")
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/whispo/config.json")
+    }
+}
+
+/// Read the system file. It belongs to the administrator, so unlike config.json
+/// a broken file is reported and ignored rather than quarantined.
+pub fn load_system_config() -> (SystemConfig, Option<FieldError>) {
+    let path = system_config_path();
+    if !path.exists() {
+        return (SystemConfig::default(), None);
+    }
+
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| serde_json::from_str::<SystemConfig>(&content).map_err(|e| e.to_string()));
+
+    match parsed {
+        Ok(system) => (system, None),
+        Err(e) => (
+            SystemConfig::default(),
+            Some(FieldError::new(
+                path.display().to_string(),
+                format!("System config ignored: {}", e),
+            )),
+        ),
+    }
+}
+
+/// Build the environment layer from `WHISPO_*` variables. A double underscore
+/// separates nesting levels, so `WHISPO_VOICE_ACTIVATION__SENSITIVITY=40` sets
+/// `voiceActivation.sensitivity`. Values are read as JSON when they parse,
+/// otherwise as strings.
+pub fn env_layer(vars: impl IntoIterator<Item = (String, String)>) -> Value {
+    let mut layer = Value::Object(Map::new());
+
+    for (name, raw) in vars {
+        if name == SYSTEM_CONFIG_ENV {
+            continue;
+        }
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+
+        let path: Vec<String> = key.split("__").map(screaming_snake_to_camel).collect();
+        if path.iter().any(|segment| segment.is_empty()) {
+            continue;
+        }
+
+        let value = serde_json::from_str(&raw).unwrap_or(Value::String(raw));
+        set_path(&mut layer, &path.join("."), value);
+    }
+
+    layer
+}
+
+fn screaming_snake_to_camel(name: &str) -> String {
+    let mut result = String::new();
+    for (i, word) in name.split('_').filter(|w| !w.is_empty()).enumerate() {
+        let word = word.to_lowercase();
+        if i == 0 {
+            result.push_str(&word);
+        } else {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                result.extend(first.to_uppercase());
+                result.push_str(chars.as_str());
+            }
+        }
+    }
+    result
+}
+
+/// Every layer as currently loaded, with secrets already decrypted
+#[derive(Debug, Clone)]
+pub struct Layers {
+    pub defaults: Value,
+    pub system: SystemConfig,
+    pub user: Value,
+    pub env: Value,
+    pub profile: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyOrigin {
+    /// Dotted path of a leaf key, e.g. "voiceActivation.sensitivity"
+    pub path: String,
+    pub value: Value,
+    /// Layer the effective value comes from
+    pub layer: ConfigLayer,
+    /// Other layers that also set this key but lost, to a higher layer or a lock
+    pub overridden: Vec<ConfigLayer>,
+    pub locked: bool,
+}
+
+impl Layers {
+    fn layer(&self, layer: ConfigLayer) -> Option<&Value> {
+        match layer {
+            ConfigLayer::Defaults => Some(&self.defaults),
+            ConfigLayer::System => Some(&self.system.config),
+            ConfigLayer::User => Some(&self.user),
+            ConfigLayer::Environment => Some(&self.env),
+            ConfigLayer::Profile => self.profile.as_ref(),
+        }
+    }
+
+    fn all() -> [ConfigLayer; 5] {
+        [
+            ConfigLayer::Defaults,
+            ConfigLayer::System,
+            ConfigLayer::User,
+            ConfigLayer::Environment,
+            ConfigLayer::Profile,
+        ]
+    }
+
+    /// Merge every layer below `top` (exclusive), without applying locks
+    pub fn merged_below(&self, top: ConfigLayer) -> Value {
+        let mut merged = Value::Object(Map::new());
+        for layer in Self::all().into_iter().filter(|l| *l < top) {
+            // An absent system file deserializes to `null`, which must not
+            // replace everything merged so far
+            if let Some(value @ Value::Object(_)) = self.layer(layer) {
+                deep_merge(&mut merged, value);
+            }
+        }
+        merged
+    }
+
+    /// Merge all layers, then put locked keys back to their admin values
+    pub fn merged(&self) -> Value {
+        let mut merged = self.merged_below(ConfigLayer::Profile);
+        if let Some(profile @ Value::Object(_)) = &self.profile {
+            deep_merge(&mut merged, profile);
+        }
+
+        let admin = self.merged_below(ConfigLayer::User);
+        for path in &self.system.locked {
+            match get_path(&admin, path) {
+                Some(value) => set_path(&mut merged, path, value.clone()),
+                None => remove_path(&mut merged, path),
+            }
+        }
+        merged
+    }
+
+    pub fn is_locked(&self, path: &str) -> bool {
+        self.system
+            .locked
+            .iter()
+            .any(|locked| path == locked || path.starts_with(&format!("{}.", locked)))
+    }
+
+    /// Which layer set each leaf key of `effective`
+    pub fn explain(&self, effective: &Value) -> Vec<KeyOrigin> {
+        leaf_paths(effective)
+            .into_iter()
+            .map(|path| {
+                let mut setters: Vec<ConfigLayer> = Self::all()
+                    .into_iter()
+                    .filter(|layer| self.layer(*layer).and_then(|v| get_path(v, &path)).is_some())
+                    .collect();
+                let locked = self.is_locked(&path);
+
+                if locked {
+                    setters.retain(|l| *l <= ConfigLayer::System);
+                }
+                let layer = setters.pop().unwrap_or(ConfigLayer::Defaults);
+                let overridden = Self::all()
+                    .into_iter()
+                    .filter(|l| *l != layer)
+                    .filter(|l| self.layer(*l).and_then(|v| get_path(v, &path)).is_some())
+                    .collect();
+
+                KeyOrigin {
+                    value: get_path(effective, &path).cloned().unwrap_or(Value::Null),
+                    path,
+                    layer,
+                    overridden,
+                    locked,
+                }
+            })
+            .collect()
+    }
+
+    /// Locked keys whose value in `config` differs from the admin value
+    pub fn locked_violations(&self, config: &Value) -> Vec<FieldError> {
+        let admin = self.merged();
+        self.system
+            .locked
+            .iter()
+            .filter(|path| get_path(config, path) != get_path(&admin, path))
+            .map(|path| FieldError::new(path.clone(), "locked by your administrator"))
+            .collect()
+    }
+}
+
+/// Recursively overlay `overlay` onto `target`. Objects merge key by key;
+/// anything else, arrays included, replaces the target value.
+pub fn deep_merge(target: &mut Value, overlay: &Value) {
+    match (target, overlay) {
+        (Value::Object(target), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match target.get_mut(key) {
+                    Some(existing) if existing.is_object() && value.is_object() => deep_merge(existing, value),
+                    _ => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, overlay) => *target = overlay.clone(),
+    }
+}
+
+/// The parts of `value` that differ from `base`, recursing into objects.
+/// Deep-merging the result onto `base` gives back `value`, apart from keys
+/// that `value` doesn't have.
+pub fn diff(value: &Value, base: &Value) -> Value {
+    let (Value::Object(value), Value::Object(base)) = (value, base) else {
+        return value.clone();
+    };
+
+    let mut result = Map::new();
+    for (key, item) in value {
+        match base.get(key) {
+            Some(base_item) if base_item == item => {}
+            Some(base_item) if base_item.is_object() && item.is_object() => {
+                let nested = diff(item, base_item);
+                if nested.as_object().map(|o| !o.is_empty()).unwrap_or(true) {
+                    result.insert(key.clone(), nested);
+                }
+            }
+            _ => {
+                result.insert(key.clone(), item.clone());
+            }
+        }
+    }
+    Value::Object(result)
+}
+
+pub fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |current, key| current.get(key))
+}
+
+pub fn set_path(value: &mut Value, path: &str, new_value: Value) {
+    let mut current = value;
+    let mut keys = path.split('.').peekable();
+
+    while let Some(key) = keys.next() {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        let object = current.as_object_mut().unwrap();
+
+        if keys.peek().is_none() {
+            object.insert(key.to_string(), new_value);
+            return;
+        }
+        current = object.entry(key.to_string()).or_insert_with(|| Value::Object(Map::new()));
+    }
+}
+
+pub fn remove_path(value: &mut Value, path: &str) {
+    match path.rsplit_once('.') {
+        Some((parent, key)) => {
+            if let Some(Value::Object(parent)) = get_path_mut(value, parent) {
+                parent.remove(key);
+            }
+        }
+        None => {
+            if let Value::Object(object) = value {
+                object.remove(path);
+            }
+        }
+    }
+}
+
+/// Drop objects left empty, e.g. a section whose only key came from the environment
+pub fn prune_empty_objects(value: &mut Value) {
+    if let Value::Object(object) = value {
+        for item in object.values_mut() {
+            prune_empty_objects(item);
+        }
+        object.retain(|_, item| !matches!(item, Value::Object(o) if o.is_empty()));
+    }
+}
+
+fn get_path_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    path.split('.').try_fold(value, |current, key| current.get_mut(key))
+}
+
+/// Dotted paths of every non-object value. Arrays count as leaves.
+pub fn leaf_paths(value: &Value) -> Vec<String> {
+    fn walk(value: &Value, prefix: &str, paths: &mut Vec<String>) {
+        match value {
+            Value::Object(object) if !object.is_empty() => {
+                for (key, item) in object {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(item, &path, paths);
+                }
+            }
+            _ if !prefix.is_empty() => paths.push(prefix.to_string()),
+            _ => {}
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(value, "", &mut paths);
+    paths
+}
+
+pub fn env_vars() -> Vec<(String, String)> {
+    std::env::vars().filter(|(name, _)| name.starts_with(ENV_PREFIX)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layers() -> Layers {
+        Layers {
+            defaults: json!({
+                "openaiBaseUrl": "https://api.openai.com/v1",
+                "shortcut": "hold-key",
+                "voiceActivation": { "enabled": false, "sensitivity": 50 },
+            }),
+            system: SystemConfig {
+                config: json!({ "openaiBaseUrl": "https://llm.example.com/v1" }),
+                locked: vec!["openaiBaseUrl".to_string(), "voiceActivation".to_string()],
+            },
+            user: json!({
+                "openaiBaseUrl": "https://user.example.com/v1",
+                "shortcut": "ctrl-slash",
+                "voiceActivation": { "sensitivity": 80 },
+            }),
+            env: json!({ "shortcut": "hold-key" }),
+            profile: Some(json!({
+                "openaiBaseUrl": "https://profile.example.com/v1",
+                "voiceActivation": { "enabled": true },
+            })),
+        }
+    }
+
+    #[test]
+    fn deep_merge_merges_objects_and_replaces_the_rest() {
+        let mut target = json!({
+            "a": { "b": 1, "c": [1, 2] },
+            "d": "kept",
+            "e": { "f": 1 },
+        });
+        deep_merge(&mut target, &json!({ "a": { "c": [3] }, "e": 5, "g": null }));
+        assert_eq!(
+            target,
+            json!({ "a": { "b": 1, "c": [3] }, "d": "kept", "e": 5, "g": null })
+        );
+
+        let mut scalar = json!(1);
+        deep_merge(&mut scalar, &json!({ "a": 1 }));
+        assert_eq!(scalar, json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn diff_keeps_only_what_changed() {
+        let base = json!({ "a": { "b": 1, "c": 2 }, "d": [1], "e": "same" });
+        let value = json!({ "a": { "b": 1, "c": 3 }, "d": [1, 2], "e": "same", "f": true });
+        let changes = diff(&value, &base);
+        assert_eq!(changes, json!({ "a": { "c": 3 }, "d": [1, 2], "f": true }));
+
+        let mut merged = base.clone();
+        deep_merge(&mut merged, &changes);
+        assert_eq!(merged, value);
+        assert_eq!(diff(&base, &base), json!({}));
+    }
+
+    #[test]
+    fn env_variables_nest_on_double_underscores() {
+        let vars = [
+            ("WHISPO_VOICE_ACTIVATION__SENSITIVITY", "40"),
+            ("WHISPO_VOICE_ACTIVATION__ENABLED", "true"),
+            ("WHISPO_OPENAI_BASE_URL", "https://env.example.com/v1"),
+            ("WHISPO_CONTEXT_FORMATTING__SHELL__COMMANDS", r#"{"kube control":"kubectl"}"#),
+            (SYSTEM_CONFIG_ENV, "/tmp/system.json"),
+            ("WHISPO_BROKEN____KEY", "1"),
+            ("HOME", "/root"),
+        ];
+        let layer = env_layer(vars.iter().map(|(name, value)| (name.to_string(), value.to_string())));
+        assert_eq!(
+            layer,
+            json!({
+                "voiceActivation": { "sensitivity": 40, "enabled": true },
+                "openaiBaseUrl": "https://env.example.com/v1",
+                "contextFormatting": { "shell": { "commands": { "kube control": "kubectl" } } },
+            })
+        );
+    }
+
+    #[test]
+    fn converts_variable_names_to_camel_case() {
+        assert_eq!(screaming_snake_to_camel("OPENAI_BASE_URL"), "openaiBaseUrl");
+        assert_eq!(screaming_snake_to_camel("STT_PROVIDER_ID"), "sttProviderId");
+        assert_eq!(screaming_snake_to_camel("SHORTCUT"), "shortcut");
+        assert_eq!(screaming_snake_to_camel("_LEADING__DOUBLE_"), "leadingDouble");
+        assert_eq!(screaming_snake_to_camel(""), "");
+    }
+
+    #[test]
+    fn locks_beat_the_user_and_profile_layers() {
+        let layers = layers();
+        let merged = layers.merged();
+        assert_eq!(merged["openaiBaseUrl"], "https://llm.example.com/v1");
+        // A locked section comes back whole from below the user layer
+        assert_eq!(merged["voiceActivation"], json!({ "enabled": false, "sensitivity": 50 }));
+        assert_eq!(merged["shortcut"], "hold-key");
+
+        assert!(layers.is_locked("voiceActivation.sensitivity"));
+        assert!(!layers.is_locked("voiceActivationShortcut"));
+
+        let mut edited = merged.clone();
+        edited["voiceActivation"]["sensitivity"] = json!(80);
+        let violations = layers.locked_violations(&edited);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "voiceActivation");
+        assert!(layers.locked_violations(&merged).is_empty());
+    }
+
+    #[test]
+    fn unlocked_keys_follow_layer_precedence() {
+        let layers = Layers {
+            system: SystemConfig::default(),
+            ..layers()
+        };
+        let merged = layers.merged();
+        assert_eq!(merged["openaiBaseUrl"], "https://profile.example.com/v1");
+        assert_eq!(merged["voiceActivation"], json!({ "enabled": true, "sensitivity": 80 }));
+        assert_eq!(merged["shortcut"], "hold-key");
+        assert_eq!(layers.merged_below(ConfigLayer::Profile)["openaiBaseUrl"], "https://user.example.com/v1");
+    }
+
+    #[test]
+    fn explains_where_each_key_came_from() {
+        let layers = layers();
+        let origins = layers.explain(&layers.merged());
+        let origin = |path: &str| origins.iter().find(|o| o.path == path).unwrap();
+
+        let base_url = origin("openaiBaseUrl");
+        assert_eq!(base_url.layer, ConfigLayer::System);
+        assert!(base_url.locked);
+        assert_eq!(
+            base_url.overridden,
+            vec![ConfigLayer::Defaults, ConfigLayer::User, ConfigLayer::Profile]
+        );
+
+        let sensitivity = origin("voiceActivation.sensitivity");
+        assert_eq!(sensitivity.layer, ConfigLayer::Defaults);
+        assert_eq!(sensitivity.value, json!(50));
+        assert_eq!(sensitivity.overridden, vec![ConfigLayer::User]);
+
+        let shortcut = origin("shortcut");
+        assert_eq!(shortcut.layer, ConfigLayer::Environment);
+        assert!(!shortcut.locked);
+        assert_eq!(shortcut.overridden, vec![ConfigLayer::Defaults, ConfigLayer::User]);
+    }
+}
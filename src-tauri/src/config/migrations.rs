@@ -0,0 +1,205 @@
+use super::layers;
+use super::schema::Config;
+use super::storage::write_atomic;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+struct Migration {
+    apply: fn(&mut Map<String, Value>),
+    /// Whether the step also applies to configs stored inside profiles.json
+    profiles: bool,
+}
+
+/// Ordered migration chain. `MIGRATIONS[n]` upgrades a config from version
+/// `n` to `n + 1`; files without a `configVersion` key are version 0.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        apply: migrate_hold_key,
+        profiles: true,
+    },
+    Migration {
+        apply: strip_defaults,
+        profiles: false,
+    },
+];
+
+pub const CURRENT_CONFIG_VERSION: u32 = MIGRATIONS.len() as u32;
+
+pub const VERSION_KEY: &str = "configVersion";
+
+pub fn config_version(config: &Map<String, Value>) -> u32 {
+    config
+        .get(VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// Run every migration newer than the config's version, in order. Configs
+/// written by a newer build are left alone. Returns the version migrated from,
+/// or `None` if nothing changed.
+pub fn migrate(config: &mut Map<String, Value>) -> Option<u32> {
+    migrate_with(config, false, |_, _| Ok(())).unwrap_or(None)
+}
+
+/// Migrate a config stored in a profile, skipping steps that only make sense
+/// for config.json
+pub fn migrate_profile_config(config: &mut Map<String, Value>) -> Option<u32> {
+    migrate_with(config, true, |_, _| Ok(())).unwrap_or(None)
+}
+
+/// Like `migrate`, but writes `<config_path>.bak-N` with the version N file
+/// before each step, so any upgrade can be undone by hand.
+pub fn migrate_file(config_path: &Path, config: &mut Map<String, Value>) -> Result<Option<u32>> {
+    migrate_with(config, false, |version, before| {
+        let backup_path = backup_path(config_path, version);
+        write_atomic(&backup_path, serde_json::to_string_pretty(before)?.as_bytes())
+            .with_context(|| format!("Failed to write {}", backup_path.display()))
+    })
+}
+
+pub fn backup_path(config_path: &Path, version: u32) -> PathBuf {
+    let mut name = config_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", version));
+    config_path.with_file_name(name)
+}
+
+fn migrate_with(
+    config: &mut Map<String, Value>,
+    profile: bool,
+    mut before_step: impl FnMut(u32, &Map<String, Value>) -> Result<()>,
+) -> Result<Option<u32>> {
+    let from = config_version(config);
+    if from >= CURRENT_CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        before_step(version as u32, config)?;
+        if migration.profiles || !profile {
+            (migration.apply)(config);
+        }
+        config.insert(VERSION_KEY.to_string(), Value::from(version as u32 + 1));
+    }
+
+    Ok(Some(from))
+}
+
+/// v0 -> v1: the Rust defaults used a bare "Control" hold key, which the
+/// settings UI can't represent. Fall back to the TypeScript default. A missing
+/// key is left missing, so sparse profile configs don't gain an override.
+fn migrate_hold_key(config: &mut Map<String, Value>) {
+    let Some(hold_key) = config.get("holdKey").and_then(|v| v.as_str()) else {
+        return;
+    };
+    if !hold_key.starts_with("AltLeft+") && !hold_key.starts_with("AltRight+") {
+        config.insert("holdKey".to_string(), Value::from("AltLeft+Space"));
+    }
+}
+
+/// v1 -> v2: config.json became the user layer on top of the built-in and
+/// system defaults. Values that merely repeat a built-in default would hide
+/// the system ones, so they are dropped.
+fn strip_defaults(config: &mut Map<String, Value>) {
+    let Ok(defaults) = serde_json::to_value(Config::default()) else {
+        return;
+    };
+    if let Value::Object(stripped) = layers::diff(&Value::Object(config.clone()), &defaults) {
+        *config = stripped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn rewrites_a_non_alt_hold_key() {
+        let mut config = object(json!({ "holdKey": "Control" }));
+        assert_eq!(migrate_profile_config(&mut config), Some(0));
+        assert_eq!(config["holdKey"], "AltLeft+Space");
+        assert_eq!(config_version(&config), CURRENT_CONFIG_VERSION);
+    }
+
+    #[test]
+    fn keeps_an_alt_hold_key() {
+        let mut config = object(json!({ "holdKey": "AltRight+KeyD" }));
+        migrate_profile_config(&mut config);
+        assert_eq!(config["holdKey"], "AltRight+KeyD");
+    }
+
+    #[test]
+    fn leaves_a_missing_hold_key_missing() {
+        let mut profile = object(json!({ "language": "de" }));
+        migrate_profile_config(&mut profile);
+        assert!(!profile.contains_key("holdKey"));
+
+        let mut config = object(json!({ "sttProviderId": "groq" }));
+        migrate(&mut config);
+        assert!(!config.contains_key("holdKey"));
+    }
+
+    #[test]
+    fn strips_defaults_from_config_json_only() {
+        let raw = json!({ "sttProviderId": "openai", "groqSttModel": "distil-whisper" });
+
+        let mut config = object(raw.clone());
+        migrate(&mut config);
+        assert!(!config.contains_key("sttProviderId"));
+        assert_eq!(config["groqSttModel"], "distil-whisper");
+
+        let mut profile = object(raw);
+        migrate_profile_config(&mut profile);
+        assert_eq!(profile["sttProviderId"], "openai");
+    }
+
+    #[test]
+    fn leaves_current_and_newer_versions_alone() {
+        let mut current = object(json!({ "configVersion": CURRENT_CONFIG_VERSION, "holdKey": "Control" }));
+        assert_eq!(migrate(&mut current), None);
+        assert_eq!(current["holdKey"], "Control");
+
+        let mut newer = object(json!({ "configVersion": CURRENT_CONFIG_VERSION + 1, "futureKey": true }));
+        let before = newer.clone();
+        assert_eq!(migrate(&mut newer), None);
+        assert_eq!(newer, before);
+    }
+
+    #[test]
+    fn resumes_from_the_stored_version() {
+        // Already past the hold key step, so its value is kept as is
+        let mut config = object(json!({ "configVersion": 1, "holdKey": "Control" }));
+        assert_eq!(migrate_profile_config(&mut config), Some(1));
+        assert_eq!(config["holdKey"], "Control");
+    }
+
+    #[test]
+    fn migrate_file_backs_up_every_version() {
+        let dir = std::env::temp_dir().join(format!("whispo-migrations-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        let mut config = object(json!({ "holdKey": "Control" }));
+        assert_eq!(migrate_file(&path, &mut config).unwrap(), Some(0));
+
+        let read = |version| -> Value {
+            serde_json::from_str(&fs::read_to_string(backup_path(&path, version)).unwrap()).unwrap()
+        };
+        assert_eq!(read(0), json!({ "holdKey": "Control" }));
+        assert_eq!(read(1), json!({ "holdKey": "AltLeft+Space", "configVersion": 1 }));
+        assert!(!backup_path(&path, CURRENT_CONFIG_VERSION).exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
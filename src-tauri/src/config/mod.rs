@@ -0,0 +1,1183 @@
+mod bundle;
+mod layers;
+mod migrations;
+mod profiles;
+mod schema;
+mod storage;
+mod watcher;
+
+pub use bundle::{ImportConflict, ImportReport, SecretHandling};
+pub use layers::KeyOrigin;
+pub use profiles::ProfileEffectiveConfig;
+pub use schema::{
+    Config, ContextFormattingConfig, FieldError, LanguageRules, SaveConfigError, ShellConfig, StreamingDictationConfig,
+    APPLICATION_CONTEXTS,
+};
+
+use bundle::{BundleFormat, BundledProfile, ProfileBundle};
+use layers::{ConfigLayer, Layers, SystemConfig};
+use schema::ValidationErrors;
+use storage::{FileLock, FileStamp};
+
+pub(crate) use storage::write_atomic;
+
+use crate::auto_profile::{self, ActivationContext};
+use crate::types::{ProfileActivation, ProfileSwitch, ProfilesData, SettingsProfile};
+use crate::vault::{self, Vault};
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Config is merged from several layers, lowest precedence first: built-in
+/// defaults, the admin's system file, the user's config.json, `WHISPO_*`
+/// environment variables and the active profile. Keys the system file marks
+/// as locked keep their admin value whatever the higher layers say.
+pub struct ConfigStore {
+    config_path: PathBuf,
+    profiles_path: PathBuf,
+    lock_path: PathBuf,
+    /// config.json as on disk: only the keys the user changed, secrets sealed
+    user_config: Mutex<Value>,
+    system_config: Mutex<SystemConfig>,
+    env_config: Mutex<Value>,
+    /// Every layer merged, with secrets still sealed
+    current_config: Mutex<Value>,
+    profiles_data: Mutex<ProfilesData>,
+    load_errors: Mutex<Vec<FieldError>>,
+    /// What config.json and profiles.json looked like when we last read or
+    /// wrote them, to notice edits made by other processes
+    config_stamp: Mutex<FileStamp>,
+    profiles_stamp: Mutex<FileStamp>,
+    vault: Arc<Vault>,
+}
+
+struct LoadedFiles {
+    user_config: Value,
+    system_config: SystemConfig,
+    env_config: Value,
+    profiles: ProfilesData,
+    errors: Vec<FieldError>,
+    config_stamp: FileStamp,
+    profiles_stamp: FileStamp,
+}
+
+impl ConfigStore {
+    pub fn new(app_data_dir: PathBuf, vault: Arc<Vault>) -> Result<Self> {
+        fs::create_dir_all(&app_data_dir).context("Failed to create app data directory")?;
+
+        let config_path = app_data_dir.join("config.json");
+        let profiles_path = app_data_dir.join("profiles.json");
+        let lock_path = app_data_dir.join(storage::LOCK_FILE);
+
+        let store = Self {
+            config_path,
+            profiles_path,
+            lock_path,
+            user_config: Mutex::new(Value::Object(Map::new())),
+            system_config: Mutex::new(SystemConfig::default()),
+            env_config: Mutex::new(Value::Object(Map::new())),
+            current_config: Mutex::new(default_config()),
+            profiles_data: Mutex::new(ProfilesData::default()),
+            load_errors: Mutex::new(vec![]),
+            config_stamp: Mutex::new(None),
+            profiles_stamp: Mutex::new(None),
+            vault,
+        };
+        store.reload()?;
+
+        Ok(store)
+    }
+
+    /// Current config with secrets decrypted
+    pub fn get(&self) -> Value {
+        let mut config = self.current_config.lock().unwrap().clone();
+        self.vault.open_secrets(&mut config);
+        config
+    }
+
+    /// Typed view of the current config, with secrets decrypted
+    pub fn config(&self) -> Config {
+        Config::from_value_lenient(&self.get()).0
+    }
+
+    /// Problems found on the last load: fields reset to defaults and files
+    /// that were quarantined or ignored because they couldn't be parsed
+    pub fn load_errors(&self) -> Vec<FieldError> {
+        self.load_errors.lock().unwrap().clone()
+    }
+
+    /// Current config with secrets masked, for the UI and MCP clients
+    pub fn get_masked(&self) -> Value {
+        let mut config = self.get();
+        vault::mask_secrets(&mut config);
+        config
+    }
+
+    /// Which layer each key's value comes from. `prefix` narrows the result
+    /// to one key or section, e.g. "voiceActivation".
+    pub fn explain(&self, prefix: Option<&str>) -> Vec<KeyOrigin> {
+        self.layers(false)
+            .explain(&self.get_masked())
+            .into_iter()
+            .filter(|origin| {
+                prefix
+                    .map(|p| origin.path == p || origin.path.starts_with(&format!("{}.", p)))
+                    .unwrap_or(true)
+            })
+            .collect()
+    }
+
+    /// Re-read every layer, e.g. after a backup restore
+    pub fn reload(&self) -> Result<()> {
+        let loaded = load_files(&self.config_path, &self.profiles_path, &self.lock_path)?;
+        *self.user_config.lock().unwrap() = loaded.user_config;
+        *self.system_config.lock().unwrap() = loaded.system_config;
+        *self.env_config.lock().unwrap() = loaded.env_config;
+        *self.profiles_data.lock().unwrap() = loaded.profiles;
+        *self.config_stamp.lock().unwrap() = loaded.config_stamp;
+        *self.profiles_stamp.lock().unwrap() = loaded.profiles_stamp;
+        self.upgrade_profiles()?;
+
+        let mut errors = loaded.errors;
+        errors.extend(self.rebuild());
+        *self.load_errors.lock().unwrap() = errors;
+        Ok(())
+    }
+
+    /// Names of the files that changed on disk since we last read or wrote them
+    pub fn external_changes(&self) -> Result<Vec<String>> {
+        let mut changed = Vec::new();
+        for (path, known) in [
+            (&self.config_path, &self.config_stamp),
+            (&self.profiles_path, &self.profiles_stamp),
+        ] {
+            if storage::stamp(path)? != *known.lock().unwrap() {
+                changed.push(path.file_name().unwrap_or_default().to_string_lossy().to_string());
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Check config.json and profiles.json as they are on disk now, without
+    /// applying them. Used before hot-reloading so a half-finished hand edit
+    /// doesn't replace a working config.
+    pub fn validate_files(&self) -> Result<Vec<FieldError>> {
+        let _lock = FileLock::acquire(&self.lock_path)?;
+        let mut errors = Vec::new();
+
+        if self.config_path.exists() {
+            let content = fs::read_to_string(&self.config_path)?;
+            match serde_json::from_str::<Value>(&content) {
+                Ok(Value::Object(mut raw)) => {
+                    migrations::migrate(&mut raw);
+                    let mut layers = self.layers(false);
+                    layers.user = Value::Object(raw);
+
+                    let (config, parse_errors) = Config::from_value_lenient(&layers.merged());
+                    errors.extend(parse_errors);
+                    errors.extend(config.validate());
+                }
+                Ok(_) => errors.push(FieldError::new("config.json", "must be a JSON object")),
+                Err(e) => errors.push(FieldError::new("config.json", format!("Invalid JSON: {}", e))),
+            }
+        }
+
+        if self.profiles_path.exists() {
+            let content = fs::read_to_string(&self.profiles_path)?;
+            if let Err(e) = serde_json::from_str::<ProfilesData>(&content) {
+                errors.push(FieldError::new("profiles.json", format!("Invalid profiles: {}", e)));
+            }
+        }
+
+        Ok(errors)
+    }
+
+    /// Call `on_change` when config.json or profiles.json change on disk
+    pub fn watch(&self, on_change: impl Fn() + Send + 'static) -> Result<()> {
+        let app_data_dir = self.config_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        watcher::watch(app_data_dir, on_change)
+    }
+
+    /// Validate and persist a full effective config, as returned by `get`.
+    /// Only what differs from the layers below is stored: in the active
+    /// profile if there is one, otherwise in config.json. Secrets and
+    /// provider settings always go to config.json. Fails with
+    /// `ValidationErrors` listing every invalid or locked field.
+    pub fn save(&self, mut config: Value) -> Result<()> {
+        // Masked secrets come back from the UI unchanged; keep the stored value
+        {
+            let current = self.current_config.lock().unwrap();
+            for key in vault::SECRET_KEYS {
+                let is_masked = config.get(*key).and_then(|v| v.as_str()).map(vault::is_masked);
+                if is_masked == Some(true) {
+                    config[*key] = current.get(*key).cloned().unwrap_or(Value::String(String::new()));
+                }
+            }
+        }
+
+        let config = serde_json::to_value(Config::parse(&config)?)?;
+        let layers = self.layers(true);
+
+        let violations = layers.locked_violations(&config);
+        if !violations.is_empty() {
+            return Err(ValidationErrors(violations).into());
+        }
+
+        // What config.json would hold without a profile. Values from the
+        // environment or locked by the admin aren't the user's to persist;
+        // keep whatever config.json had.
+        let mut user = layers::diff(&config, &layers.merged_below(ConfigLayer::User));
+        let env_paths = layers::leaf_paths(&layers.env);
+        for path in env_paths.iter().chain(layers.system.locked.iter()) {
+            match layers::get_path(&layers.user, path) {
+                Some(value) => layers::set_path(&mut user, path, value.clone()),
+                None => layers::remove_path(&mut user, path),
+            }
+        }
+        layers::prune_empty_objects(&mut user);
+        user[migrations::VERSION_KEY] = Value::from(migrations::CURRENT_CONFIG_VERSION);
+
+        match self.get_active_profile_id() {
+            Some(profile_id) => {
+                // Secrets and provider settings still go to config.json
+                let mut global = layers.user.clone();
+                for key in profiles::global_keys() {
+                    match user.get(key) {
+                        Some(value) => global[key] = value.clone(),
+                        None => layers::remove_path(&mut global, key),
+                    }
+                }
+                global[migrations::VERSION_KEY] = Value::from(migrations::CURRENT_CONFIG_VERSION);
+                if global != layers.user {
+                    self.write_user_config(global, true)?;
+                }
+
+                // Keys the parents already set stay with the parents
+                let mut base = layers.merged_below(ConfigLayer::Profile);
+                let mut parents = self.profile_chain(&profile_id);
+                parents.pop();
+                for parent in parents.iter_mut() {
+                    self.vault.open_secrets(&mut parent.config);
+                }
+                layers::deep_merge(&mut base, &profiles::merge_chain(&parents.iter().collect::<Vec<_>>()));
+
+                let mut overrides = layers::diff(&config, &base);
+                profiles::remove_global_keys(&mut overrides);
+                self.set_profile_config(&profile_id, overrides)?;
+            }
+            None => self.write_user_config(user, true)?,
+        }
+
+        self.rebuild();
+        self.load_errors.lock().unwrap().clear();
+        Ok(())
+    }
+
+    pub fn save_typed(&self, config: &Config) -> Result<()> {
+        self.save(serde_json::to_value(config)?)
+    }
+
+    /// Rewrite config.json and profiles.json after the vault was enabled or
+    /// before it is disabled
+    pub fn rewrite_secrets(&self, encrypt: bool) -> Result<()> {
+        let mut user = self.user_config.lock().unwrap().clone();
+        self.vault.open_secrets(&mut user);
+        self.write_user_config(user, encrypt)?;
+
+        {
+            let mut data = self.profiles_data.lock().unwrap();
+            for profile in data.profiles.iter_mut() {
+                self.vault.open_secrets(&mut profile.config);
+            }
+        }
+        self.write_profiles(encrypt)?;
+
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Layers as loaded. With `open`, secrets are decrypted so values can be
+    /// compared with what the UI sends back.
+    fn layers(&self, open: bool) -> Layers {
+        let mut user = self.user_config.lock().unwrap().clone();
+        let mut profile = self.get_active_profile_id().map(|id| self.profile_overrides(&id));
+        if open {
+            self.vault.open_secrets(&mut user);
+            if let Some(profile) = profile.as_mut() {
+                self.vault.open_secrets(profile);
+            }
+        }
+
+        Layers {
+            defaults: default_config(),
+            system: self.system_config.lock().unwrap().clone(),
+            user,
+            env: self.env_config.lock().unwrap().clone(),
+            profile,
+        }
+    }
+
+    /// Recompute the merged config. Returns the fields that had to be reset
+    /// to defaults because no layer gave them a usable value.
+    fn rebuild(&self) -> Vec<FieldError> {
+        let merged = self.layers(false).merged();
+        let (config, errors) = Config::from_value_lenient(&merged);
+        *self.current_config.lock().unwrap() = serde_json::to_value(config).unwrap_or(merged);
+        errors
+    }
+
+    fn write_user_config(&self, mut user: Value, seal: bool) -> Result<()> {
+        if seal {
+            self.vault.seal_secrets(&mut user)?;
+        }
+        let content = serde_json::to_string_pretty(&user)?;
+        self.write_file(&self.config_path, &self.config_stamp, &content)?;
+        *self.user_config.lock().unwrap() = user;
+        Ok(())
+    }
+
+    /// config.json as on disk: only the keys the user changed, secrets sealed
+    pub fn user_config(&self) -> Value {
+        self.user_config.lock().unwrap().clone()
+    }
+
+    /// Replace config.json and profiles.json, e.g. from a backup or the
+    /// trash, under the same lock and atomic write as a save. Every layer
+    /// is re-read afterwards, so the running app doesn't keep the old config
+    /// in memory and write it back on the next save.
+    pub fn restore(&self, config: Option<Value>, profiles: Option<ProfilesData>) -> Result<()> {
+        if let Some(config) = config {
+            let content = serde_json::to_string_pretty(&config)?;
+            self.write_file(&self.config_path, &self.config_stamp, &content)?;
+        }
+        if let Some(profiles) = profiles {
+            let content = serde_json::to_string_pretty(&profiles)?;
+            self.write_file(&self.profiles_path, &self.profiles_stamp, &content)?;
+        }
+        self.reload()
+    }
+
+    // ===== PROFILES MANAGEMENT =====
+
+    pub fn get_profiles(&self) -> Vec<SettingsProfile> {
+        self.profiles_data.lock().unwrap().profiles.clone()
+    }
+
+    /// profiles.json as on disk, secrets sealed
+    pub fn profiles_data(&self) -> ProfilesData {
+        self.profiles_data.lock().unwrap().clone()
+    }
+
+    pub fn get_active_profile_id(&self) -> Option<String> {
+        self.profiles_data.lock().unwrap().active_profile_id.clone()
+    }
+
+    /// The profile and its ancestors, root first
+    fn profile_chain(&self, profile_id: &str) -> Vec<SettingsProfile> {
+        let data = self.profiles_data.lock().unwrap();
+        profiles::chain(&data.profiles, profile_id).into_iter().cloned().collect()
+    }
+
+    /// Overrides of a profile and all its ancestors merged together, secrets sealed
+    fn profile_overrides(&self, profile_id: &str) -> Value {
+        let data = self.profiles_data.lock().unwrap();
+        profiles::merge_chain(&profiles::chain(&data.profiles, profile_id))
+    }
+
+    /// Layers with secrets opened, as they are with `profile_id` active
+    fn layers_for_profile(&self, profile_id: Option<&str>) -> Layers {
+        let mut layers = self.layers(true);
+        layers.profile = profile_id.map(|id| {
+            let mut overrides = self.profile_overrides(id);
+            self.vault.open_secrets(&mut overrides);
+            overrides
+        });
+        layers
+    }
+
+    /// What a profile changes: its own overrides, the config it gives once
+    /// active, and every key that differs from the config without profiles
+    pub fn profile_effective(&self, profile_id: &str) -> Result<ProfileEffectiveConfig> {
+        let chain = self.profile_chain(profile_id);
+        let profile = chain.last().context("Profile not found")?;
+
+        let mut base = self.layers_for_profile(None).merged();
+        let mut effective = self.layers_for_profile(Some(profile_id)).merged();
+
+        let mut own = profile.config.clone();
+        for config in [&mut base, &mut effective, &mut own] {
+            self.vault.open_secrets(config);
+            vault::mask_secrets(config);
+        }
+
+        Ok(ProfileEffectiveConfig {
+            profile_id: profile.id.clone(),
+            inherits_from: chain[..chain.len() - 1].iter().map(|p| p.id.clone()).collect(),
+            changes: profiles::changes(&effective, &base),
+            overrides: own,
+            effective,
+        })
+    }
+
+    pub fn create_profile(
+        &self,
+        name: String,
+        description: Option<String>,
+        base_config: Option<Value>,
+        parent_id: Option<String>,
+    ) -> Result<SettingsProfile> {
+        if let Some(parent_id) = &parent_id {
+            if !self.get_profiles().iter().any(|p| &p.id == parent_id) {
+                anyhow::bail!("Parent profile not found");
+            }
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+
+        let mut data = self.profiles_data.lock().unwrap();
+
+        // Profiles created within the same millisecond still need distinct ids
+        let mut id = format!("profile_{}", timestamp);
+        let mut suffix = 1;
+        while data.profiles.iter().any(|p| p.id == id) {
+            id = format!("profile_{}_{}", timestamp, suffix);
+            suffix += 1;
+        }
+
+        let profile = SettingsProfile {
+            id,
+            name,
+            description,
+            // A new profile overrides nothing until the user changes something
+            config: base_config.unwrap_or_else(|| Value::Object(Map::new())),
+            parent_id,
+            activation: None,
+            created_at: timestamp,
+            updated_at: timestamp,
+            is_default: None,
+        };
+
+        data.profiles.push(profile.clone());
+        drop(data);
+
+        self.save_profiles()?;
+
+        Ok(profile)
+    }
+
+    pub fn update_profile(&self, profile_id: String, updates: Value) -> Result<()> {
+        let mut data = self.profiles_data.lock().unwrap();
+
+        // `"parentId": null` detaches the profile from its parent
+        let parent_id = match updates.get("parentId") {
+            Some(Value::String(parent_id)) => {
+                if !data.profiles.iter().any(|p| &p.id == parent_id) {
+                    anyhow::bail!("Parent profile not found");
+                }
+                if profiles::creates_cycle(&data.profiles, &profile_id, parent_id) {
+                    anyhow::bail!("A profile can't inherit from itself or its descendants");
+                }
+                Some(Some(parent_id.clone()))
+            }
+            Some(Value::Null) => Some(None),
+            _ => None,
+        };
+        let activation = match updates.get("activation") {
+            Some(value) => Some(
+                serde_json::from_value::<Option<ProfileActivation>>(value.clone())
+                    .context("Invalid activation rules")?,
+            ),
+            None => None,
+        };
+
+        if let Some(profile) = data.profiles.iter_mut().find(|p| p.id == profile_id) {
+            // Merge updates into profile
+            if let Some(name) = updates.get("name").and_then(|v| v.as_str()) {
+                profile.name = name.to_string();
+            }
+            if let Some(desc) = updates.get("description").and_then(|v| v.as_str()) {
+                profile.description = Some(desc.to_string());
+            }
+            if let Some(config) = updates.get("config") {
+                profile.config = config.clone();
+            }
+            if let Some(parent_id) = parent_id {
+                profile.parent_id = parent_id;
+            }
+            if let Some(activation) = activation {
+                profile.activation = activation;
+            }
+
+            profile.updated_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64;
+
+            drop(data);
+            self.save_profiles()?;
+            self.rebuild();
+            Ok(())
+        } else {
+            anyhow::bail!("Profile not found")
+        }
+    }
+
+    pub fn delete_profile(&self, profile_id: String) -> Result<bool> {
+        let mut data = self.profiles_data.lock().unwrap();
+
+        let Some(index) = data.profiles.iter().position(|p| p.id == profile_id) else {
+            return Ok(false);
+        };
+        let deleted = data.profiles.remove(index);
+
+        // Children move up to the deleted profile's parent and take over its
+        // overrides, so their effective config doesn't change
+        for child in data.profiles.iter_mut() {
+            if child.parent_id.as_ref() == Some(&profile_id) {
+                let mut config = deleted.config.clone();
+                layers::deep_merge(&mut config, &child.config);
+                child.config = config;
+                child.parent_id = deleted.parent_id.clone();
+            }
+        }
+
+        // If deleted profile was active, clear active profile
+        if data.active_profile_id.as_ref() == Some(&profile_id) {
+            data.active_profile_id = None;
+        }
+
+        drop(data);
+        self.save_profiles()?;
+        self.rebuild();
+        Ok(true)
+    }
+
+    /// Make a profile the top config layer. config.json is left alone, so
+    /// switching back restores the user's own settings. A manual switch pins
+    /// the profile against activation rules.
+    pub fn switch_profile(&self, profile_id: String) -> Result<bool> {
+        if !self.get_profiles().iter().any(|p| p.id == profile_id) {
+            return Ok(false);
+        }
+        self.record_switch(Some(profile_id), false, "switched manually".to_string())?;
+        Ok(true)
+    }
+
+    /// Go back to the user's config without any profile on top
+    pub fn clear_active_profile(&self) -> Result<()> {
+        self.record_switch(None, false, "deactivated manually".to_string())?;
+        Ok(())
+    }
+
+    pub fn is_profile_pinned(&self) -> bool {
+        self.profiles_data.lock().unwrap().pinned
+    }
+
+    pub fn last_profile_switch(&self) -> Option<ProfileSwitch> {
+        self.profiles_data.lock().unwrap().last_switch.clone()
+    }
+
+    /// Unpinning hands the choice of profile back to the activation rules
+    pub fn set_profile_pinned(&self, pinned: bool) -> Result<()> {
+        self.profiles_data.lock().unwrap().pinned = pinned;
+        self.save_profiles()
+    }
+
+    /// Switch to the profile the activation rules pick, unless a profile is
+    /// pinned. Returns the switch made, if any.
+    pub fn apply_activation_rules(&self, context: &ActivationContext) -> Result<Option<ProfileSwitch>> {
+        let decision = auto_profile::decide(&self.profiles_data.lock().unwrap(), context);
+        match decision {
+            Some((profile_id, reason)) => Ok(Some(self.record_switch(profile_id, true, reason)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn record_switch(&self, profile_id: Option<String>, automatic: bool, reason: String) -> Result<ProfileSwitch> {
+        let switch = ProfileSwitch {
+            profile_id: profile_id.clone(),
+            automatic,
+            reason,
+            switched_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+        };
+
+        {
+            let mut data = self.profiles_data.lock().unwrap();
+            data.active_profile_id = profile_id;
+            data.pinned = !automatic;
+            data.last_switch = Some(switch.clone());
+        }
+
+        self.save_profiles()?;
+        self.rebuild();
+        Ok(switch)
+    }
+
+    fn set_profile_config(&self, profile_id: &str, config: Value) -> Result<()> {
+        let mut data = self.profiles_data.lock().unwrap();
+        let profile = data
+            .profiles
+            .iter_mut()
+            .find(|p| p.id == profile_id)
+            .context("Profile not found")?;
+
+        profile.config = config;
+        profile.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        drop(data);
+
+        self.save_profiles()
+    }
+
+    /// Profiles written before inheritance hold a full config snapshot.
+    /// Reduce each to the keys that differ from the config below it, keeping
+    /// the old file as profiles.json.bak-0.
+    fn upgrade_profiles(&self) -> Result<()> {
+        let mut base = self.layers(true);
+        base.profile = None;
+        let base = base.merged_below(ConfigLayer::Profile);
+
+        let mut data = self.profiles_data.lock().unwrap();
+        if data.version >= profiles::PROFILES_VERSION {
+            return Ok(());
+        }
+
+        if self.profiles_path.exists() {
+            let backup_path = migrations::backup_path(&self.profiles_path, data.version);
+            fs::copy(&self.profiles_path, &backup_path)
+                .with_context(|| format!("Failed to write {}", backup_path.display()))?;
+        }
+
+        for profile in data.profiles.iter_mut() {
+            self.vault.open_secrets(&mut profile.config);
+            profile.config = layers::diff(&profile.config, &base);
+        }
+        data.version = profiles::PROFILES_VERSION;
+        drop(data);
+
+        self.save_profiles()
+    }
+
+    fn save_profiles(&self) -> Result<()> {
+        self.write_profiles(true)
+    }
+
+    fn write_profiles(&self, seal: bool) -> Result<()> {
+        let mut data = self.profiles_data.lock().unwrap();
+        for profile in data.profiles.iter_mut() {
+            if seal {
+                self.vault.seal_secrets(&mut profile.config)?;
+            }
+            // Overrides are sparse, so without this a profile would look like
+            // version 0 and be migrated again on every load
+            if let Value::Object(config) = &mut profile.config {
+                config.insert(
+                    migrations::VERSION_KEY.to_string(),
+                    Value::from(migrations::CURRENT_CONFIG_VERSION),
+                );
+            }
+        }
+        let content = serde_json::to_string_pretty(&*data)?;
+        self.write_file(&self.profiles_path, &self.profiles_stamp, &content)
+    }
+
+    // ===== PROFILE BUNDLES =====
+
+    /// Write profiles to a JSON or TOML bundle, chosen by the file extension.
+    /// A profile whose parent isn't exported too gets the parent's overrides
+    /// folded in. Returns the number of profiles written.
+    pub fn export_profiles(&self, profile_ids: &[String], path: &Path, secrets: SecretHandling) -> Result<usize> {
+        let all = self.get_profiles();
+        let mut bundled = Vec::new();
+
+        for id in profile_ids {
+            let chain = profiles::chain(&all, id);
+            let Some((profile, ancestors)) = chain.split_last() else {
+                anyhow::bail!("Profile {} not found", id);
+            };
+
+            let parent = ancestors.iter().rposition(|p| profile_ids.contains(&p.id));
+            let folded = &chain[parent.map(|i| i + 1).unwrap_or(0)..];
+            let mut config = profiles::merge_chain(folded);
+
+            // API keys, app rules and glossary as they are with the profile
+            // active, so the bundle doesn't depend on this machine's config.json
+            let merged = self.layers_for_profile(Some(id)).merged();
+            for key in vault::SECRET_KEYS {
+                if let Some(secret) = merged.get(*key) {
+                    config[*key] = secret.clone();
+                }
+            }
+            bundle::scrub_secrets(&mut config, secrets);
+            let effective = Config::from_value_lenient(&merged).0;
+            let mut entry = BundledProfile::new(profile, config, effective.app_rules, effective.glossary);
+            entry.activation = profile.activation.clone();
+            entry.parent_id = parent.map(|i| ancestors[i].id.clone());
+            bundled.push(entry);
+        }
+
+        let bundle = ProfileBundle {
+            format: bundle::BUNDLE_FORMAT.to_string(),
+            version: bundle::BUNDLE_VERSION,
+            exported_at: chrono::Utc::now().timestamp_millis(),
+            profiles: bundled,
+        };
+        let content = bundle.serialize(BundleFormat::from_path(path))?;
+        storage::write_atomic(path, content.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(bundle.profiles.len())
+    }
+
+    /// Add the profiles from a bundle. Nothing is imported if any of them
+    /// is invalid. API keys filled from the environment go to config.json,
+    /// where no key is set yet.
+    pub fn import_profiles(&self, path: &Path, on_conflict: ImportConflict) -> Result<ImportReport> {
+        self.import_profiles_with_env(path, on_conflict, |name| std::env::var(name).ok())
+    }
+
+    /// `import_profiles` with the environment read through `env`
+    fn import_profiles_with_env(
+        &self,
+        path: &Path,
+        on_conflict: ImportConflict,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<ImportReport> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let bundle = ProfileBundle::parse(&content, BundleFormat::from_path(path))?;
+
+        let mut report = ImportReport::default();
+        let mut incoming = Vec::new();
+        for bundled in &bundle.profiles {
+            let mut config = bundled.overrides()?;
+            report.missing_secrets.extend(bundle::resolve_secrets(&mut config, &env));
+            let secrets = bundle::take_secrets(&mut config);
+            if let Value::Object(map) = &mut config {
+                migrations::migrate_profile_config(map);
+            }
+
+            let mut effective = default_config();
+            layers::deep_merge(&mut effective, &config);
+            Config::parse(&effective).map_err(|e| anyhow::anyhow!("Profile \"{}\" is invalid: {}", bundled.name, e))?;
+            incoming.push((bundled, config, secrets));
+        }
+        report.missing_secrets.sort();
+        report.missing_secrets.dedup();
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        let mut data = self.profiles_data.lock().unwrap();
+
+        // Bundle ids mapped to the ids the profiles end up with
+        let mut ids = HashMap::new();
+        let mut imported = Vec::new();
+        let mut secrets = Map::new();
+        for (index, (bundled, config, bundled_secrets)) in incoming.into_iter().enumerate() {
+            let existing = data.profiles.iter().position(|p| p.id == bundled.id);
+            let mut profile = SettingsProfile {
+                id: bundled.id.clone(),
+                name: bundled.name.clone(),
+                description: bundled.description.clone(),
+                config,
+                parent_id: bundled.parent_id.clone(),
+                activation: bundled.activation.clone(),
+                created_at: timestamp,
+                updated_at: timestamp,
+                is_default: None,
+            };
+
+            match (existing, on_conflict) {
+                (None, _) => {}
+                (Some(_), ImportConflict::Skip) => {
+                    report.skipped.push(bundled.id.clone());
+                    continue;
+                }
+                (Some(index), ImportConflict::Replace) => {
+                    profile.created_at = data.profiles[index].created_at;
+                    data.profiles.remove(index);
+                    report.replaced.push(bundled.id.clone());
+                }
+                (Some(_), ImportConflict::Rename) => {
+                    profile.id = format!("profile_{}_{}", timestamp, index);
+                    if data.profiles.iter().any(|p| p.name == profile.name) {
+                        profile.name = format!("{} (imported)", profile.name);
+                    }
+                    report.renamed.push((bundled.id.clone(), profile.id.clone()));
+                }
+            }
+
+            secrets.extend(bundled_secrets);
+            ids.insert(bundled.id.clone(), profile.id.clone());
+            imported.push(profile);
+        }
+
+        // Parents keep pointing at whichever profile now has their bundle id;
+        // a skipped parent resolves to the local profile of that id
+        for profile in imported.iter_mut() {
+            profile.parent_id = profile.parent_id.take().map(|p| ids.get(&p).cloned().unwrap_or(p));
+        }
+        report.imported = imported.iter().map(|p| p.id.clone()).collect();
+        data.profiles.extend(imported);
+
+        let known: Vec<String> = data.profiles.iter().map(|p| p.id.clone()).collect();
+        for profile in data.profiles.iter_mut() {
+            if profile.parent_id.as_ref().is_some_and(|p| !known.contains(p)) {
+                profile.parent_id = None;
+            }
+        }
+        drop(data);
+
+        self.save_profiles()?;
+
+        // Keys from the environment fill in ones this machine doesn't have
+        // yet, but never replace the user's own
+        let current = self.get();
+        let mut user = self.user_config.lock().unwrap().clone();
+        self.vault.open_secrets(&mut user);
+        let mut filled = false;
+        for (key, secret) in secrets {
+            if current.get(&key).and_then(|v| v.as_str()).unwrap_or_default().is_empty() {
+                user[key.as_str()] = secret;
+                filled = true;
+            }
+        }
+        if filled {
+            self.write_user_config(user, true)?;
+        }
+
+        self.rebuild();
+        Ok(report)
+    }
+
+    /// Atomically replace `path` while holding the cross-process lock. Fails
+    /// with `ExternalEditError` if the file changed since we last saw it.
+    fn write_file(&self, path: &Path, known: &Mutex<FileStamp>, content: &str) -> Result<()> {
+        let _lock = FileLock::acquire(&self.lock_path)?;
+        let mut known = known.lock().unwrap();
+
+        if storage::stamp(path)? != *known {
+            return Err(storage::ExternalEditError {
+                path: path.to_path_buf(),
+            }
+            .into());
+        }
+
+        storage::write_atomic(path, content.as_bytes())?;
+        *known = Some(storage::stamp_bytes(content.as_bytes()));
+        Ok(())
+    }
+}
+
+/// Load every layer. The files are read under the cross-process lock, so we
+/// never see another instance halfway through a migration or quarantine.
+fn load_files(config_path: &Path, profiles_path: &Path, lock_path: &Path) -> Result<LoadedFiles> {
+    let _lock = FileLock::acquire(lock_path)?;
+
+    let (user_config, mut errors) = load_config(config_path)?;
+    let (profiles, profile_errors) = load_profiles(profiles_path)?;
+    errors.extend(profile_errors);
+
+    let (system_config, system_error) = layers::load_system_config();
+    errors.extend(system_error);
+
+    Ok(LoadedFiles {
+        user_config,
+        system_config,
+        env_config: layers::env_layer(layers::env_vars()),
+        profiles,
+        errors,
+        config_stamp: storage::stamp(config_path)?,
+        profiles_stamp: storage::stamp(profiles_path)?,
+    })
+}
+
+/// Read config.json as the user layer, migrating it to the current version
+fn load_config(config_path: &Path) -> Result<(Value, Vec<FieldError>)> {
+    let empty = || Value::Object(Map::new());
+
+    if !config_path.exists() {
+        return Ok((empty(), vec![]));
+    }
+
+    let content = fs::read_to_string(config_path)?;
+    let mut raw = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(raw)) => raw,
+        _ => {
+            let error = quarantine(config_path)?;
+            return Ok((empty(), vec![error]));
+        }
+    };
+
+    if migrations::migrate_file(config_path, &mut raw)?.is_some() {
+        storage::write_atomic(config_path, serde_json::to_string_pretty(&raw)?.as_bytes())?;
+    }
+
+    Ok((Value::Object(raw), vec![]))
+}
+
+fn load_profiles(profiles_path: &Path) -> Result<(ProfilesData, Vec<FieldError>)> {
+    let empty = || ProfilesData {
+        version: profiles::PROFILES_VERSION,
+        ..Default::default()
+    };
+
+    if !profiles_path.exists() {
+        return Ok((empty(), vec![]));
+    }
+
+    let content = fs::read_to_string(profiles_path)?;
+    let Ok(mut data) = serde_json::from_str::<ProfilesData>(&content) else {
+        let error = quarantine(profiles_path)?;
+        return Ok((empty(), vec![error]));
+    };
+
+    // Profile configs are migrated in memory; they are persisted on the next save
+    for profile in data.profiles.iter_mut() {
+        if let Value::Object(config) = &mut profile.config {
+            migrations::migrate_profile_config(config);
+        }
+    }
+
+    Ok((data, vec![]))
+}
+
+/// Move an unparseable file aside so the user can recover it by hand
+pub(crate) fn quarantine(path: &Path) -> Result<FieldError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".corrupt-{}", timestamp));
+    let target = path.with_file_name(name);
+
+    fs::rename(path, &target)
+        .with_context(|| format!("Failed to quarantine {}", path.display()))?;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    Ok(FieldError::new(
+        file_name.to_string(),
+        format!("File could not be parsed and was moved to {}", target.display()),
+    ))
+}
+
+fn default_config() -> Value {
+    serde_json::to_value(Config::default()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("whispo-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn open_store(dir: &Path) -> ConfigStore {
+        ConfigStore::new(dir.to_path_buf(), Arc::new(Vault::new(dir))).unwrap()
+    }
+
+    /// A profile's overrides without the version stamp every saved profile gets
+    fn overrides(profile: &SettingsProfile) -> Value {
+        let mut config = profile.config.clone();
+        layers::remove_path(&mut config, migrations::VERSION_KEY);
+        config
+    }
+
+    fn read_json(path: PathBuf) -> Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn refuses_to_save_over_an_external_edit() {
+        let dir = temp_dir("external-edit");
+        let store = open_store(&dir);
+        let mut config = store.get();
+        config["autoInsert"] = json!(false);
+        store.save(config.clone()).unwrap();
+
+        let hand_edit = r#"{ "configVersion": 2, "sttProviderId": "groq" }"#;
+        fs::write(dir.join("config.json"), hand_edit).unwrap();
+        assert_eq!(store.external_changes().unwrap(), vec!["config.json"]);
+
+        config["autoInsert"] = json!(true);
+        let error = store.save(config.clone()).unwrap_err();
+        assert!(error.downcast_ref::<storage::ExternalEditError>().is_some());
+        assert_eq!(fs::read_to_string(dir.join("config.json")).unwrap(), hand_edit);
+
+        // Saving works again once the edit has been read
+        store.reload().unwrap();
+        assert!(store.external_changes().unwrap().is_empty());
+        let mut config = store.get();
+        config["autoInsert"] = json!(false);
+        store.save(config).unwrap();
+        let saved = read_json(dir.join("config.json"));
+        assert_eq!(saved["sttProviderId"], "groq");
+        assert_eq!(saved["autoInsert"], false);
+    }
+
+    #[test]
+    fn saves_global_keys_to_config_json_while_a_profile_is_active() {
+        let dir = temp_dir("global-keys");
+        let store = open_store(&dir);
+        let profile = store.create_profile("Work".to_string(), None, None, None).unwrap();
+        store.switch_profile(profile.id.clone()).unwrap();
+
+        let mut config = store.get();
+        config["openaiApiKey"] = json!("sk-shared");
+        config["groqBaseUrl"] = json!("https://groq.example.com/v1");
+        config["shortcut"] = json!("key-combination");
+        store.save(config).unwrap();
+
+        let user = read_json(dir.join("config.json"));
+        assert_eq!(user["openaiApiKey"], "sk-shared");
+        assert_eq!(user["groqBaseUrl"], "https://groq.example.com/v1");
+        assert!(user.get("shortcut").is_none());
+
+        let profile = store.get_profiles().into_iter().find(|p| p.id == profile.id).unwrap();
+        assert_eq!(overrides(&profile), json!({ "shortcut": "key-combination" }));
+
+        // Other profiles, and no profile at all, see the same key
+        store.clear_active_profile().unwrap();
+        assert_eq!(store.config().openai_api_key, "sk-shared");
+        assert_eq!(store.config().shortcut, "hold-key");
+    }
+
+    #[test]
+    fn upgrades_snapshot_profiles_to_overrides() {
+        let dir = temp_dir("upgrade");
+        let mut snapshot = serde_json::to_value(Config::default()).unwrap();
+        snapshot["shortcut"] = json!("key-combination");
+        snapshot["voiceActivation"]["sensitivity"] = json!(70);
+        let profiles = json!({
+            "profiles": [{
+                "id": "work",
+                "name": "Work",
+                "description": null,
+                "config": snapshot,
+                "createdAt": 0,
+                "updatedAt": 0,
+                "isDefault": null,
+            }],
+            "activeProfileId": "work",
+        });
+        fs::write(dir.join("profiles.json"), profiles.to_string()).unwrap();
+
+        let store = open_store(&dir);
+        let work = &store.get_profiles()[0];
+        assert_eq!(
+            overrides(work),
+            json!({ "shortcut": "key-combination", "voiceActivation": { "sensitivity": 70 } })
+        );
+        assert_eq!(store.config().voice_activation.sensitivity, 70);
+
+        let saved = read_json(dir.join("profiles.json"));
+        assert_eq!(saved["version"], profiles::PROFILES_VERSION);
+        let backup = read_json(migrations::backup_path(&dir.join("profiles.json"), 0));
+        assert_eq!(backup, profiles);
+
+        // Already upgraded, a second load changes nothing
+        assert_eq!(open_store(&dir).get_profiles()[0].config, work.config);
+    }
+
+    fn write_bundle(dir: &Path, name: &str, shortcut: &str) -> PathBuf {
+        let path = dir.join(name);
+        let bundle = json!({
+            "format": bundle::BUNDLE_FORMAT,
+            "version": bundle::BUNDLE_VERSION,
+            "exportedAt": 0,
+            "profiles": [{ "id": "work", "name": "Work", "config": { "shortcut": shortcut } }],
+        });
+        fs::write(&path, bundle.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn imports_conflicting_profiles_by_renaming_replacing_or_skipping() {
+        let dir = temp_dir("import-conflicts");
+        let store = open_store(&dir);
+        let first = write_bundle(&dir, "first.json", "key-combination");
+        let second = write_bundle(&dir, "second.json", "ctrl-slash");
+
+        let report = store.import_profiles(&first, ImportConflict::Skip).unwrap();
+        assert_eq!(report.imported, ["work"]);
+        let shortcut = |store: &ConfigStore, id: &str| {
+            let profile = store.get_profiles().into_iter().find(|p| p.id == id).unwrap();
+            profile.config["shortcut"].clone()
+        };
+
+        let report = store.import_profiles(&second, ImportConflict::Skip).unwrap();
+        assert_eq!(report.skipped, ["work"]);
+        assert!(report.imported.is_empty());
+        assert_eq!(store.get_profiles().len(), 1);
+        assert_eq!(shortcut(&store, "work"), "key-combination");
+
+        let report = store.import_profiles(&second, ImportConflict::Replace).unwrap();
+        assert_eq!(report.replaced, ["work"]);
+        assert_eq!(store.get_profiles().len(), 1);
+        assert_eq!(shortcut(&store, "work"), "ctrl-slash");
+
+        let report = store.import_profiles(&first, ImportConflict::Rename).unwrap();
+        let (from, to) = &report.renamed[0];
+        assert_eq!(from, "work");
+        assert_ne!(to, "work");
+        assert_eq!(report.imported, std::slice::from_ref(to));
+        let renamed = store.get_profiles().into_iter().find(|p| &p.id == to).unwrap();
+        assert_eq!(renamed.name, "Work (imported)");
+        assert_eq!(shortcut(&store, "work"), "ctrl-slash");
+        assert_eq!(shortcut(&store, to), "key-combination");
+    }
+
+    #[test]
+    fn bundles_carry_api_keys_as_placeholders() {
+        let source_dir = temp_dir("export-source");
+        let source = open_store(&source_dir);
+        let mut config = source.get();
+        config["groqApiKey"] = json!("gq-source");
+        source.save(config).unwrap();
+        let profile = source.create_profile("Work".to_string(), None, None, None).unwrap();
+
+        let path = source_dir.join("bundle.toml");
+        let ids = [profile.id];
+        source.export_profiles(&ids, &path, SecretHandling::Template).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("${GROQ_API_KEY}"));
+        assert!(!content.contains("gq-source"));
+
+        let env = |name: &str| (name == "GROQ_API_KEY").then(|| "gq-from-env".to_string());
+        let target_dir = temp_dir("export-target");
+        let target = open_store(&target_dir);
+        target.import_profiles_with_env(&path, ImportConflict::Rename, env).unwrap();
+        assert_eq!(target.config().groq_api_key, "gq-from-env");
+        let imported = &target.get_profiles()[0];
+        assert!(imported.config.get("groqApiKey").is_none());
+
+        // A key the user already has is kept
+        let mut config = target.get();
+        config["groqApiKey"] = json!("gq-local");
+        target.save(config).unwrap();
+        target.import_profiles_with_env(&path, ImportConflict::Rename, env).unwrap();
+        assert_eq!(target.config().groq_api_key, "gq-local");
+
+        source.export_profiles(&ids, &path, SecretHandling::Strip).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("GROQ_API_KEY"));
+    }
+}
@@ -0,0 +1,215 @@
+use super::layers;
+use crate::types::SettingsProfile;
+use crate::vault::SECRET_KEYS;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// profiles.json version where profiles store overrides instead of snapshots
+pub const PROFILES_VERSION: u32 = 1;
+
+/// Provider settings shared by every profile. Like the API keys they live in
+/// config.json only, so switching profiles never loses a key or endpoint.
+const SHARED_KEYS: &[&str] = &["openaiBaseUrl", "groqBaseUrl", "geminiBaseUrl", "mcp"];
+
+/// Top-level keys a profile can't override
+pub fn global_keys() -> impl Iterator<Item = &'static str> {
+    SECRET_KEYS.iter().chain(SHARED_KEYS).copied()
+}
+
+/// Drop the global keys from a profile's overrides
+pub fn remove_global_keys(config: &mut Value) {
+    if let Value::Object(config) = config {
+        for key in global_keys() {
+            config.remove(key);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileEffectiveConfig {
+    pub profile_id: String,
+    /// Ancestors the profile inherits from, root first
+    pub inherits_from: Vec<String>,
+    /// The keys the profile itself sets
+    pub overrides: Value,
+    /// Config with the profile applied
+    pub effective: Value,
+    /// Every key the profile and its ancestors change compared to the base
+    pub changes: Vec<ConfigChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub path: String,
+    /// Value without any profile, `None` if the base doesn't have the key
+    pub base: Option<Value>,
+    pub value: Value,
+}
+
+/// The profile and its ancestors, root first. A missing parent ends the
+/// chain, and a cycle is cut where it would revisit a profile.
+pub fn chain<'a>(profiles: &'a [SettingsProfile], profile_id: &str) -> Vec<&'a SettingsProfile> {
+    let mut chain: Vec<&SettingsProfile> = Vec::new();
+    let mut next = Some(profile_id.to_string());
+
+    while let Some(id) = next {
+        let Some(profile) = profiles.iter().find(|p| p.id == id) else {
+            break;
+        };
+        if chain.iter().any(|p| p.id == profile.id) {
+            break;
+        }
+        chain.push(profile);
+        next = profile.parent_id.clone();
+    }
+
+    chain.reverse();
+    chain
+}
+
+/// Deep-merge the overrides of every profile in `chain`. Global keys left
+/// in older profiles are ignored.
+pub fn merge_chain(chain: &[&SettingsProfile]) -> Value {
+    let mut merged = Value::Object(Map::new());
+    for profile in chain {
+        if profile.config.is_object() {
+            layers::deep_merge(&mut merged, &profile.config);
+        }
+    }
+    remove_global_keys(&mut merged);
+    merged
+}
+
+/// Whether making `parent_id` the parent of `profile_id` would loop back
+pub fn creates_cycle(profiles: &[SettingsProfile], profile_id: &str, parent_id: &str) -> bool {
+    parent_id == profile_id || chain(profiles, parent_id).iter().any(|p| p.id == profile_id)
+}
+
+/// Leaf-level differences between a config with and without profiles
+pub fn changes(effective: &Value, base: &Value) -> Vec<ConfigChange> {
+    let changed = layers::diff(effective, base);
+    layers::leaf_paths(&changed)
+        .into_iter()
+        .map(|path| ConfigChange {
+            base: layers::get_path(base, &path).cloned(),
+            value: layers::get_path(effective, &path).cloned().unwrap_or(Value::Null),
+            path,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(id: &str, parent_id: Option<&str>, config: Value) -> SettingsProfile {
+        SettingsProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            config,
+            parent_id: parent_id.map(str::to_string),
+            activation: None,
+            created_at: 0,
+            updated_at: 0,
+            is_default: None,
+        }
+    }
+
+    fn ids(chain: &[&SettingsProfile]) -> Vec<String> {
+        chain.iter().map(|p| p.id.clone()).collect()
+    }
+
+    #[test]
+    fn chains_run_from_the_root() {
+        let profiles = vec![
+            profile("work", None, json!({})),
+            profile("meetings", Some("work"), json!({})),
+            profile("standup", Some("meetings"), json!({})),
+            profile("orphan", Some("deleted"), json!({})),
+        ];
+        assert_eq!(ids(&chain(&profiles, "standup")), ["work", "meetings", "standup"]);
+        assert_eq!(ids(&chain(&profiles, "work")), ["work"]);
+        assert_eq!(ids(&chain(&profiles, "orphan")), ["orphan"]);
+        assert!(chain(&profiles, "missing").is_empty());
+    }
+
+    #[test]
+    fn chains_stop_at_a_cycle() {
+        let profiles = vec![
+            profile("a", Some("c"), json!({})),
+            profile("b", Some("a"), json!({})),
+            profile("c", Some("b"), json!({})),
+            profile("self", Some("self"), json!({})),
+        ];
+        assert_eq!(ids(&chain(&profiles, "a")), ["b", "c", "a"]);
+        assert_eq!(ids(&chain(&profiles, "self")), ["self"]);
+    }
+
+    #[test]
+    fn children_override_their_parents() {
+        let profiles = vec![
+            profile(
+                "work",
+                None,
+                json!({
+                    "shortcut": "hold-key",
+                    "voiceActivation": { "enabled": true, "sensitivity": 40 },
+                    "openaiApiKey": "sk-old-snapshot",
+                    "openaiBaseUrl": "https://old.example.com/v1",
+                }),
+            ),
+            profile("meetings", Some("work"), json!({ "voiceActivation": { "sensitivity": 70 } })),
+            profile("broken", None, json!("not an object")),
+        ];
+        let merged = merge_chain(&chain(&profiles, "meetings"));
+        assert_eq!(
+            merged,
+            json!({
+                "shortcut": "hold-key",
+                "voiceActivation": { "enabled": true, "sensitivity": 70 },
+            })
+        );
+        assert_eq!(merge_chain(&chain(&profiles, "broken")), json!({}));
+    }
+
+    #[test]
+    fn detects_parent_cycles() {
+        let profiles = vec![
+            profile("work", None, json!({})),
+            profile("meetings", Some("work"), json!({})),
+            profile("standup", Some("meetings"), json!({})),
+            profile("home", None, json!({})),
+        ];
+        assert!(creates_cycle(&profiles, "work", "standup"));
+        assert!(creates_cycle(&profiles, "work", "work"));
+        assert!(creates_cycle(&profiles, "meetings", "standup"));
+        assert!(!creates_cycle(&profiles, "standup", "home"));
+        assert!(!creates_cycle(&profiles, "home", "standup"));
+    }
+
+    #[test]
+    fn lists_leaf_changes() {
+        let base = json!({ "shortcut": "hold-key", "voiceActivation": { "enabled": false, "sensitivity": 50 } });
+        let effective = json!({
+            "shortcut": "hold-key",
+            "voiceActivation": { "enabled": true, "sensitivity": 50 },
+            "extraKey": 1,
+        });
+        let changes = changes(&effective, &base);
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.base.clone(), c.value.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("extraKey", None, json!(1)),
+                ("voiceActivation.enabled", Some(json!(false)), json!(true)),
+            ]
+        );
+    }
+}
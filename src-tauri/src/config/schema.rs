@@ -0,0 +1,868 @@
+use super::migrations::CURRENT_CONFIG_VERSION;
+use crate::context::{ContextRule, DenyRule, TitlePattern};
+use crate::costs::{HardCapAction, ModelPrice};
+use crate::mcp::types::GlossaryEntry;
+use crate::types::AppRule;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const STT_PROVIDERS: &[&str] = &["openai", "groq"];
+pub const CHAT_PROVIDERS: &[&str] = &["openai", "groq", "gemini"];
+pub const SHORTCUT_OPTIONS: &[&str] = &[
+    "hold-key",
+    "key-combination",
+    "ctrl-slash",
+    "voice-activation",
+    "streaming-dictation",
+    "custom",
+    "disabled",
+];
+pub const APPLICATION_CONTEXTS: &[&str] = &[
+    "code-editor",
+    "terminal",
+    "email",
+    "chat",
+    "document",
+    "browser",
+    "notes",
+    "presentation",
+    "design",
+    "generic",
+];
+pub const FUSION_STRATEGIES: &[&str] = &[
+    "best-confidence",
+    "majority-vote",
+    "weighted-average",
+    "consensus",
+    "primary-fallback",
+];
+pub const DICTATION_MODES: &[&str] = &["auto", "manual", "disabled", "off"];
+pub const INSERT_MODES: &[&str] = &["replace", "append", "insert"];
+
+/// Typed view of config.json. Keys this build doesn't know about are kept in
+/// `extra` (at every level) so configs written by newer versions survive a save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    pub config_version: u32,
+    pub stt_provider_id: String,
+    pub openai_api_key: String,
+    pub groq_api_key: String,
+    pub gemini_api_key: String,
+    pub openai_base_url: String,
+    pub groq_base_url: String,
+    pub gemini_base_url: String,
+    /// Transcription model asked of each provider, and priced by cost tracking
+    pub openai_stt_model: String,
+    pub groq_stt_model: String,
+    pub recording_shortcut: String,
+    pub shortcut: String,
+    pub hold_key: String,
+    pub transcript_post_processing_enabled: bool,
+    pub transcript_post_processing_provider_id: String,
+    pub transcript_post_processing_prompt: String,
+    /// Type the transcript into the focused application, not just copy it
+    pub auto_insert: bool,
+    pub app_rules: Vec<AppRule>,
+    pub enable_app_rules: bool,
+    /// Terms the transcript should spell a particular way
+    pub glossary: Vec<GlossaryEntry>,
+    pub retention: RetentionConfig,
+    pub cost_tracking: CostTrackingConfig,
+    pub voice_activation: VoiceActivationConfig,
+    pub streaming_dictation: StreamingDictationConfig,
+    pub fusion_transcription: FusionTranscriptionConfig,
+    pub context_formatting: ContextFormattingConfig,
+    pub mcp: McpConfig,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_version: CURRENT_CONFIG_VERSION,
+            stt_provider_id: "openai".to_string(),
+            openai_api_key: String::new(),
+            groq_api_key: String::new(),
+            gemini_api_key: String::new(),
+            openai_base_url: "https://api.openai.com/v1".to_string(),
+            groq_base_url: "https://api.groq.com/openai/v1".to_string(),
+            gemini_base_url: "https://generativelanguage.googleapis.com".to_string(),
+            openai_stt_model: "whisper-1".to_string(),
+            groq_stt_model: "whisper-large-v3".to_string(),
+            recording_shortcut: "Control".to_string(),
+            shortcut: "hold-key".to_string(),
+            hold_key: "AltLeft+Space".to_string(),
+            transcript_post_processing_enabled: false,
+            transcript_post_processing_provider_id: "openai".to_string(),
+            transcript_post_processing_prompt: "Fix any grammar or spelling errors in the following text, but maintain the original meaning and tone:".to_string(),
+            auto_insert: true,
+            app_rules: vec![],
+            enable_app_rules: false,
+            glossary: vec![],
+            retention: RetentionConfig::default(),
+            cost_tracking: CostTrackingConfig::default(),
+            voice_activation: VoiceActivationConfig::default(),
+            streaming_dictation: StreamingDictationConfig::default(),
+            fusion_transcription: FusionTranscriptionConfig::default(),
+            context_formatting: ContextFormattingConfig::default(),
+            mcp: McpConfig::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    /// `null` keeps audio forever
+    pub audio_max_age_days: Option<u64>,
+    pub audio_max_total_gb: Option<f64>,
+    pub transcript_max_age_days: Option<u64>,
+    pub sweep_interval_minutes: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            audio_max_age_days: Some(30),
+            audio_max_total_gb: Some(2.0),
+            transcript_max_age_days: Some(365),
+            sweep_interval_minutes: 60,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CostTrackingConfig {
+    pub enabled: bool,
+    /// Overrides for the built-in price table, keyed by "provider/model"
+    pub prices: BTreeMap<String, ModelPrice>,
+    pub daily_soft_cap: Option<f64>,
+    pub daily_hard_cap: Option<f64>,
+    pub monthly_soft_cap: Option<f64>,
+    pub monthly_hard_cap: Option<f64>,
+    pub hard_cap_action: HardCapAction,
+    pub fallback_provider_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for CostTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            prices: BTreeMap::new(),
+            daily_soft_cap: None,
+            daily_hard_cap: None,
+            monthly_soft_cap: None,
+            monthly_hard_cap: None,
+            hard_cap_action: HardCapAction::Block,
+            fallback_provider_id: Some("groq".to_string()),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceActivationConfig {
+    pub enabled: bool,
+    pub sensitivity: u32,
+    pub silence_threshold: u64,
+    pub noise_gate: u32,
+    pub min_recording_duration: u64,
+    pub max_recording_duration: u64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for VoiceActivationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: 50,
+            silence_threshold: 1500,
+            noise_gate: 30,
+            min_recording_duration: 500,
+            max_recording_duration: 60000,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StreamingDictationConfig {
+    pub enabled: bool,
+    pub language: String,
+    pub continuous: bool,
+    pub interim_results: bool,
+    pub max_alternatives: u32,
+    pub sensitivity: u32,
+    pub punctuation_mode: String,
+    pub capitalization_mode: String,
+    pub pause_on_silence: u64,
+    pub insert_mode: String,
+    pub enable_voice_commands: bool,
+    pub context_formatting: bool,
+    /// The pass that applies the punctuation and capitalization modes
+    pub normalization: NormalizationConfig,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for StreamingDictationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            language: "en-US".to_string(),
+            continuous: true,
+            interim_results: true,
+            max_alternatives: 1,
+            sensitivity: 50,
+            punctuation_mode: "auto".to_string(),
+            capitalization_mode: "auto".to_string(),
+            pause_on_silence: 2000,
+            insert_mode: "insert".to_string(),
+            enable_voice_commands: false,
+            context_formatting: false,
+            normalization: NormalizationConfig::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NormalizationConfig {
+    pub enabled: bool,
+    pub remove_fillers: bool,
+    /// Write spoken numbers, amounts, dates and times the way they're read,
+    /// e.g. "three thirty pm" as "3:30 PM", before any formatting
+    pub normalize_numbers: bool,
+    /// Write spoken "new line" and "new paragraph" as line breaks in auto
+    /// punctuation mode too, where they'd otherwise be taken as words;
+    /// manual mode always does
+    pub spoken_layout: bool,
+    /// Rules tried before the built-in ones, keyed by language code
+    /// without the region, e.g. "en"
+    pub languages: BTreeMap<String, LanguageRules>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for NormalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            remove_fillers: true,
+            normalize_numbers: true,
+            spoken_layout: false,
+            languages: BTreeMap::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LanguageRules {
+    /// Spoken phrase to the mark it's written as, e.g. "full stop" -> "."
+    pub spoken_punctuation: BTreeMap<String, String>,
+    pub fillers: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FusionTranscriptionConfig {
+    pub enabled: bool,
+    pub strategy: String,
+    pub providers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_provider: Option<String>,
+    pub timeout_ms: u64,
+    pub min_providers_required: u32,
+    pub confidence_threshold: f64,
+    pub enable_parallel: bool,
+    pub provider_weights: BTreeMap<String, f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for FusionTranscriptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            strategy: "best-confidence".to_string(),
+            providers: vec!["openai".to_string(), "groq".to_string()],
+            primary_provider: None,
+            timeout_ms: 30000,
+            min_providers_required: 1,
+            confidence_threshold: 0.7,
+            enable_parallel: true,
+            provider_weights: BTreeMap::from([
+                ("openai".to_string(), 1.0),
+                ("groq".to_string(), 1.0),
+            ]),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContextFormattingConfig {
+    pub enabled: bool,
+    /// Format every transcript for this context instead of detecting one,
+    /// e.g. set by an app rule
+    pub context: Option<String>,
+    pub auto_detect_context: bool,
+    pub fallback_context: String,
+    pub enable_smart_formatting: bool,
+    pub preserve_original_on_error: bool,
+    /// Title formats to try before the built-in editor and browser parsers
+    pub title_patterns: Vec<TitlePattern>,
+    /// Scored alongside the built-in context table, winning ties with it
+    pub context_rules: Vec<ContextRule>,
+    /// Turn spoken constructs like "function foo taking x and y" into the
+    /// focused file's language in the code-editor context, rather than
+    /// only converting spoken symbols
+    pub code_dictation: bool,
+    /// Dictation in the terminal context
+    pub shell: ShellConfig,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for ContextFormattingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            context: None,
+            auto_detect_context: true,
+            fallback_context: "generic".to_string(),
+            enable_smart_formatting: true,
+            preserve_original_on_error: true,
+            title_patterns: vec![],
+            context_rules: vec![],
+            code_dictation: false,
+            shell: ShellConfig::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellConfig {
+    /// Spoken names for programs on top of the built-in ones,
+    /// e.g. "kube control" -> "kubectl"
+    pub commands: BTreeMap<String, String>,
+    /// Dictated commands matching any of these are shown for confirmation
+    /// instead of being typed
+    pub deny_list: Vec<DenyRule>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for ShellConfig {
+    fn default() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+            deny_list: crate::context::default_deny_list(),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct McpConfig {
+    /// Whispo as an MCP server other apps can connect to
+    pub server: McpServerSettings,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct McpServerSettings {
+    /// Read at startup; the server only listens on localhost
+    pub enabled: bool,
+    pub port: u16,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for McpServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 3000,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// Dotted path of the offending key, e.g. "voiceActivation.sensitivity"
+    pub path: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Returned by `ConfigStore::save` when a config fails validation
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationErrors(pub Vec<FieldError>);
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration: ")?;
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}: {}", error.path, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Error shape of the `save_config` command, with per-field details when available
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveConfigError {
+    pub message: String,
+    pub fields: Vec<FieldError>,
+}
+
+impl From<anyhow::Error> for SaveConfigError {
+    fn from(error: anyhow::Error) -> Self {
+        let fields = error
+            .downcast_ref::<ValidationErrors>()
+            .map(|errors| errors.0.clone())
+            .unwrap_or_default();
+
+        Self {
+            message: error.to_string(),
+            fields,
+        }
+    }
+}
+
+impl Config {
+    /// Parse and validate a config. Every problem is reported, not just the first.
+    pub fn parse(value: &Value) -> Result<Config, ValidationErrors> {
+        let (config, mut errors) = Self::from_value_lenient(value);
+        errors.extend(config.validate());
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ValidationErrors(errors))
+        }
+    }
+
+    /// Parse a config, falling back to defaults only for the fields that don't
+    /// deserialize. Used on load so one bad key doesn't cost the whole file.
+    pub fn from_value_lenient(value: &Value) -> (Config, Vec<FieldError>) {
+        if let Ok(config) = serde_json::from_value::<Config>(value.clone()) {
+            return (config, vec![]);
+        }
+
+        let Some(input) = value.as_object() else {
+            return (
+                Config::default(),
+                vec![FieldError::new("", "Config must be a JSON object")],
+            );
+        };
+
+        let mut merged = match serde_json::to_value(Config::default()) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        let mut errors = Vec::new();
+
+        for (key, input_value) in input {
+            let default_value = merged.get(key).cloned();
+            merged.insert(key.clone(), input_value.clone());
+            if try_parse(&merged).is_ok() {
+                continue;
+            }
+
+            match (default_value, input_value) {
+                // Keep the valid keys of a section and reset only the bad ones
+                (Some(Value::Object(default_section)), Value::Object(input_section)) => {
+                    let mut section = default_section;
+                    for (nested_key, nested_value) in input_section {
+                        let previous = section.insert(nested_key.clone(), nested_value.clone());
+                        merged.insert(key.clone(), Value::Object(section.clone()));
+
+                        if let Err(message) = try_parse(&merged) {
+                            errors.push(FieldError::new(format!("{}.{}", key, nested_key), message));
+                            match previous {
+                                Some(previous) => section.insert(nested_key.clone(), previous),
+                                None => section.remove(nested_key),
+                            };
+                            merged.insert(key.clone(), Value::Object(section.clone()));
+                        }
+                    }
+                }
+                // Keep the valid elements of a list, e.g. app rules
+                (Some(Value::Array(_)), Value::Array(input_items)) => {
+                    let mut items = Vec::new();
+                    for (index, item) in input_items.iter().enumerate() {
+                        items.push(item.clone());
+                        merged.insert(key.clone(), Value::Array(items.clone()));
+
+                        if let Err(message) = try_parse(&merged) {
+                            errors.push(FieldError::new(format!("{}[{}]", key, index), message));
+                            items.pop();
+                            merged.insert(key.clone(), Value::Array(items.clone()));
+                        }
+                    }
+                }
+                (default_value, _) => {
+                    let message = try_parse(&merged).err().unwrap_or_default();
+                    errors.push(FieldError::new(key.clone(), message));
+                    match default_value {
+                        Some(default_value) => merged.insert(key.clone(), default_value),
+                        None => merged.remove(key),
+                    };
+                }
+            }
+        }
+
+        let config = try_parse(&merged).unwrap_or_default();
+        (config, errors)
+    }
+
+    /// This config with `overrides` deep-merged on top. Overrides that don't
+    /// fit the schema are dropped.
+    pub fn merged_with(&self, overrides: &Value) -> Config {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        super::layers::deep_merge(&mut value, overrides);
+        Config::from_value_lenient(&value).0
+    }
+
+    pub fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let mut one_of = |path: &str, value: &str, allowed: &[&str]| {
+            if !allowed.contains(&value) {
+                errors.push(FieldError::new(
+                    path,
+                    format!("must be one of {}, got \"{}\"", allowed.join(", "), value),
+                ));
+            }
+        };
+
+        one_of("sttProviderId", &self.stt_provider_id, STT_PROVIDERS);
+        one_of(
+            "transcriptPostProcessingProviderId",
+            &self.transcript_post_processing_provider_id,
+            CHAT_PROVIDERS,
+        );
+        one_of("shortcut", &self.shortcut, SHORTCUT_OPTIONS);
+        one_of(
+            "streamingDictation.punctuationMode",
+            &self.streaming_dictation.punctuation_mode,
+            DICTATION_MODES,
+        );
+        one_of(
+            "streamingDictation.capitalizationMode",
+            &self.streaming_dictation.capitalization_mode,
+            DICTATION_MODES,
+        );
+        one_of("streamingDictation.insertMode", &self.streaming_dictation.insert_mode, INSERT_MODES);
+        one_of("fusionTranscription.strategy", &self.fusion_transcription.strategy, FUSION_STRATEGIES);
+        one_of(
+            "contextFormatting.fallbackContext",
+            &self.context_formatting.fallback_context,
+            APPLICATION_CONTEXTS,
+        );
+        if let Some(context) = &self.context_formatting.context {
+            one_of("contextFormatting.context", context, APPLICATION_CONTEXTS);
+        }
+        for (index, provider) in self.fusion_transcription.providers.iter().enumerate() {
+            one_of(&format!("fusionTranscription.providers[{}]", index), provider, STT_PROVIDERS);
+        }
+
+        for (path, url) in [
+            ("openaiBaseUrl", &self.openai_base_url),
+            ("groqBaseUrl", &self.groq_base_url),
+            ("geminiBaseUrl", &self.gemini_base_url),
+        ] {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                errors.push(FieldError::new(path, "must be an http(s) URL"));
+            }
+        }
+
+        let mut check = |ok: bool, path: &str, message: &str| {
+            if !ok {
+                errors.push(FieldError::new(path, message));
+            }
+        };
+
+        check(!self.openai_stt_model.is_empty(), "openaiSttModel", "must not be empty");
+        check(!self.groq_stt_model.is_empty(), "groqSttModel", "must not be empty");
+
+        let voice = &self.voice_activation;
+        check(voice.sensitivity <= 100, "voiceActivation.sensitivity", "must be between 0 and 100");
+        check(voice.noise_gate <= 100, "voiceActivation.noiseGate", "must be between 0 and 100");
+        check(voice.silence_threshold > 0, "voiceActivation.silenceThreshold", "must be greater than 0");
+        check(
+            voice.min_recording_duration <= voice.max_recording_duration,
+            "voiceActivation.minRecordingDuration",
+            "must not exceed maxRecordingDuration",
+        );
+
+        let streaming = &self.streaming_dictation;
+        check(!streaming.language.is_empty(), "streamingDictation.language", "must not be empty");
+        check(streaming.max_alternatives >= 1, "streamingDictation.maxAlternatives", "must be at least 1");
+        check(streaming.sensitivity <= 100, "streamingDictation.sensitivity", "must be between 0 and 100");
+
+        let fusion = &self.fusion_transcription;
+        check(!fusion.providers.is_empty(), "fusionTranscription.providers", "must not be empty");
+        check(
+            fusion.min_providers_required as usize <= fusion.providers.len().max(1),
+            "fusionTranscription.minProvidersRequired",
+            "must not exceed the number of providers",
+        );
+        check(
+            (0.0..=1.0).contains(&fusion.confidence_threshold),
+            "fusionTranscription.confidenceThreshold",
+            "must be between 0 and 1",
+        );
+        check(fusion.timeout_ms > 0, "fusionTranscription.timeoutMs", "must be greater than 0");
+        check(
+            fusion.provider_weights.values().all(|w| *w >= 0.0),
+            "fusionTranscription.providerWeights",
+            "weights must not be negative",
+        );
+
+        check(self.mcp.server.port > 0, "mcp.server.port", "must be greater than 0");
+
+        let retention = &self.retention;
+        check(
+            retention.audio_max_total_gb.map(|gb| gb >= 0.0).unwrap_or(true),
+            "retention.audioMaxTotalGb",
+            "must not be negative",
+        );
+
+        let costs = &self.cost_tracking;
+        for (path, cap) in [
+            ("costTracking.dailySoftCap", costs.daily_soft_cap),
+            ("costTracking.dailyHardCap", costs.daily_hard_cap),
+            ("costTracking.monthlySoftCap", costs.monthly_soft_cap),
+            ("costTracking.monthlyHardCap", costs.monthly_hard_cap),
+        ] {
+            check(cap.map(|c| c >= 0.0).unwrap_or(true), path, "must not be negative");
+        }
+        if let (Some(soft), Some(hard)) = (costs.daily_soft_cap, costs.daily_hard_cap) {
+            check(soft <= hard, "costTracking.dailySoftCap", "must not exceed dailyHardCap");
+        }
+        if let (Some(soft), Some(hard)) = (costs.monthly_soft_cap, costs.monthly_hard_cap) {
+            check(soft <= hard, "costTracking.monthlySoftCap", "must not exceed monthlyHardCap");
+        }
+
+        for (index, rule) in self.app_rules.iter().enumerate() {
+            if let Some(provider) = &rule.stt_provider_id {
+                check(
+                    STT_PROVIDERS.contains(&provider.as_str()),
+                    &format!("appRules[{}].sttProviderId", index),
+                    "unknown provider",
+                );
+            }
+            for (matcher_index, matcher) in rule.matchers.iter().enumerate() {
+                let compiled = crate::app_rules::check_pattern(matcher);
+                check(
+                    compiled.is_ok(),
+                    &format!("appRules[{}].matchers[{}].pattern", index, matcher_index),
+                    &compiled.err().unwrap_or_default(),
+                );
+            }
+        }
+
+        for (index, pattern) in self.context_formatting.title_patterns.iter().enumerate() {
+            let compiled = crate::context::check_title_pattern(pattern);
+            check(
+                compiled.is_ok(),
+                &format!("contextFormatting.titlePatterns[{}].pattern", index),
+                &compiled.err().unwrap_or_default(),
+            );
+        }
+
+        for (index, rule) in self.context_formatting.context_rules.iter().enumerate() {
+            check(
+                APPLICATION_CONTEXTS.contains(&rule.context.as_str()),
+                &format!("contextFormatting.contextRules[{}].context", index),
+                "unknown context",
+            );
+            let compiled = crate::context::check_rule(rule);
+            check(
+                compiled.is_ok(),
+                &format!("contextFormatting.contextRules[{}]", index),
+                &compiled.err().unwrap_or_default(),
+            );
+        }
+
+        for (index, rule) in self.context_formatting.shell.deny_list.iter().enumerate() {
+            let compiled = crate::context::check_deny_rule(rule);
+            check(
+                compiled.is_ok(),
+                &format!("contextFormatting.shell.denyList[{}].pattern", index),
+                &compiled.err().unwrap_or_default(),
+            );
+        }
+
+        for (index, entry) in self.glossary.iter().enumerate() {
+            check(
+                !entry.term.trim().is_empty(),
+                &format!("glossary[{}].term", index),
+                "must not be empty",
+            );
+        }
+
+        errors
+    }
+}
+
+fn try_parse(map: &Map<String, Value>) -> Result<Config, String> {
+    serde_json::from_value(Value::Object(map.clone())).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.path.as_str()).collect()
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(Config::default().validate(), vec![]);
+    }
+
+    #[test]
+    fn reports_every_invalid_field() {
+        let mut config = Config {
+            stt_provider_id: "whisper-local".to_string(),
+            groq_base_url: "ftp://example.com".to_string(),
+            openai_stt_model: String::new(),
+            ..Default::default()
+        };
+        config.voice_activation.sensitivity = 150;
+        config.voice_activation.min_recording_duration = 70_000;
+        config.cost_tracking.daily_soft_cap = Some(5.0);
+        config.cost_tracking.daily_hard_cap = Some(1.0);
+        config.glossary.push(GlossaryEntry {
+            term: " ".to_string(),
+            replacement: "x".to_string(),
+            context: None,
+        });
+
+        let errors = config.validate();
+        assert_eq!(
+            paths(&errors),
+            vec![
+                "sttProviderId",
+                "groqBaseUrl",
+                "openaiSttModel",
+                "voiceActivation.sensitivity",
+                "voiceActivation.minRecordingDuration",
+                "costTracking.dailySoftCap",
+                "glossary[0].term",
+            ]
+        );
+        assert_eq!(
+            errors[0].message,
+            format!("must be one of {}, got \"whisper-local\"", STT_PROVIDERS.join(", "))
+        );
+        assert_eq!(errors[5].message, "must not exceed dailyHardCap");
+    }
+
+    #[test]
+    fn parse_reports_type_and_value_errors_together() {
+        let errors = Config::parse(&json!({ "autoInsert": "yes", "shortcut": "triple-tap" }))
+            .unwrap_err()
+            .0;
+        assert_eq!(paths(&errors), vec!["autoInsert", "shortcut"]);
+    }
+
+    #[test]
+    fn lenient_keeps_valid_keys_and_resets_bad_ones() {
+        let (config, errors) = Config::from_value_lenient(&json!({
+            "sttProviderId": "groq",
+            "autoInsert": "yes",
+            "futureKey": [1, 2],
+        }));
+
+        assert_eq!(paths(&errors), vec!["autoInsert"]);
+        assert_eq!(config.stt_provider_id, "groq");
+        assert!(config.auto_insert);
+        assert_eq!(config.extra["futureKey"], json!([1, 2]));
+    }
+
+    #[test]
+    fn lenient_resets_only_the_bad_keys_of_a_section() {
+        let (config, errors) = Config::from_value_lenient(&json!({
+            "voiceActivation": { "sensitivity": "high", "noiseGate": 10 },
+        }));
+
+        assert_eq!(paths(&errors), vec!["voiceActivation.sensitivity"]);
+        assert_eq!(config.voice_activation.sensitivity, 50);
+        assert_eq!(config.voice_activation.noise_gate, 10);
+    }
+
+    #[test]
+    fn lenient_drops_only_the_bad_list_elements() {
+        let (config, errors) = Config::from_value_lenient(&json!({
+            "glossary": [
+                { "term": "tauri", "replacement": "Tauri" },
+                { "term": 5 },
+                { "term": "rust", "replacement": "Rust" },
+            ],
+        }));
+
+        assert_eq!(paths(&errors), vec!["glossary[1]"]);
+        let terms: Vec<&str> = config.glossary.iter().map(|entry| entry.term.as_str()).collect();
+        assert_eq!(terms, vec!["tauri", "rust"]);
+    }
+
+    #[test]
+    fn lenient_falls_back_to_defaults_for_a_non_object() {
+        let (config, errors) = Config::from_value_lenient(&json!([1, 2, 3]));
+        assert_eq!(config, Config::default());
+        assert_eq!(paths(&errors), vec![""]);
+
+        let (config, errors) = Config::from_value_lenient(&json!({ "holdKey": "AltLeft+KeyD" }));
+        assert_eq!(config.hold_key, "AltLeft+KeyD");
+        assert!(errors.is_empty());
+    }
+}
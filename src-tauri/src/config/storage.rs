@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const LOCK_FILE: &str = ".config.lock";
+
+/// Exclusive lock shared by every Whispo process that reads or writes
+/// config.json and profiles.json. Released when dropped.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    pub fn acquire(lock_path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+        file.lock_exclusive()
+            .with_context(|| format!("Failed to lock {}", lock_path.display()))?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// Content hash of a file as we last read or wrote it. `None` means the file
+/// didn't exist.
+pub type FileStamp = Option<String>;
+
+pub fn stamp(path: &Path) -> Result<FileStamp> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(stamp_bytes(&data))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+pub fn stamp_bytes(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Write to a temp file next to `path`, fsync it, then rename it over `path`.
+/// Readers see either the old or the new file, never a truncated one.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let temp_path = temp_path(path);
+
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        sync_parent_dir(path);
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.tmp-{}", name, std::process::id()))
+}
+
+/// Persist the rename itself. Not possible on Windows, where the rename is
+/// already durable once MoveFileEx returns.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) {
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) {}
+
+/// A file changed on disk since we last read it, e.g. edited by hand or by a
+/// second instance. Saving would silently discard those edits.
+#[derive(Debug, Clone)]
+pub struct ExternalEditError {
+    pub path: PathBuf,
+}
+
+impl fmt::Display for ExternalEditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} was changed outside Whispo; reload it before saving",
+            self.path.file_name().unwrap_or_default().to_string_lossy()
+        )
+    }
+}
+
+impl std::error::Error for ExternalEditError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("whispo-storage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("config.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        let names: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, vec!["config.json"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn write_atomic_leaves_the_file_alone_on_failure() {
+        let dir = temp_dir("atomic-failure");
+        let path = dir.join("config.json");
+        write_atomic(&path, b"kept").unwrap();
+
+        // The temp file can't be renamed over a directory
+        let blocked = dir.join("blocked");
+        fs::create_dir(&blocked).unwrap();
+        fs::write(blocked.join("inside"), b"").unwrap();
+        assert!(write_atomic(&blocked, b"lost").is_err());
+        assert!(!temp_path(&blocked).exists());
+        assert_eq!(fs::read(&path).unwrap(), b"kept");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn stamps_follow_the_content() {
+        let dir = temp_dir("stamp");
+        let path = dir.join("profiles.json");
+        assert_eq!(stamp(&path).unwrap(), None);
+
+        write_atomic(&path, b"{}").unwrap();
+        assert_eq!(stamp(&path).unwrap(), Some(stamp_bytes(b"{}")));
+        fs::write(&path, b"{ }").unwrap();
+        assert_ne!(stamp(&path).unwrap(), Some(stamp_bytes(b"{}")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_lock_is_exclusive_until_dropped() {
+        let dir = temp_dir("lock");
+        let lock_path = dir.join(LOCK_FILE);
+        let other = OpenOptions::new().create(true).truncate(false).write(true).open(&lock_path).unwrap();
+
+        let lock = FileLock::acquire(&lock_path).unwrap();
+        assert!(other.try_lock_exclusive().is_err());
+        drop(lock);
+        other.try_lock_exclusive().unwrap();
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
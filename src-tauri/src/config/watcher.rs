@@ -0,0 +1,143 @@
+use anyhow::Result;
+use notify::{Event, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Editors and dotfile tools often touch a file several times per save
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+const WATCHED_FILES: &[&str] = &["config.json", "profiles.json"];
+
+/// Turns a stream of filesystem events into one change per burst
+#[derive(Debug, Default)]
+pub struct ChangeDebouncer {
+    last_event: Option<Instant>,
+}
+
+impl ChangeDebouncer {
+    pub fn event(&mut self, now: Instant) {
+        self.last_event = Some(now);
+    }
+
+    /// How long to wait for further events before the burst is over, or
+    /// `None` while there is no burst
+    pub fn wait(&self, now: Instant) -> Option<Duration> {
+        self.last_event
+            .map(|last| DEBOUNCE.saturating_sub(now.duration_since(last)))
+    }
+
+    /// Whether a burst has just ended. Each burst is reported once.
+    pub fn settled(&mut self, now: Instant) -> bool {
+        match self.last_event {
+            Some(last) if now.duration_since(last) >= DEBOUNCE => {
+                self.last_event = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Call `on_change` once per burst of filesystem events on config.json or
+/// profiles.json. The directory is watched rather than the files, so saves
+/// that rename a temp file over the original are still seen.
+///
+/// Our own writes trigger this too; callers should compare against
+/// `ConfigStore::external_changes` before reloading.
+pub fn watch(app_data_dir: PathBuf, on_change: impl Fn() + Send + 'static) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result {
+            if event.paths.iter().any(|path| is_watched(path)) {
+                let _ = tx.send(());
+            }
+        }
+    })?;
+    watcher.watch(&app_data_dir, RecursiveMode::NonRecursive)?;
+
+    thread::spawn(move || {
+        // The watcher stops when dropped, so it lives as long as this thread
+        let _watcher = watcher;
+
+        let mut debouncer = ChangeDebouncer::default();
+        loop {
+            let received = match debouncer.wait(Instant::now()) {
+                Some(wait) => rx.recv_timeout(wait),
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(()) => debouncer.event(Instant::now()),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if debouncer.settled(Instant::now()) {
+                on_change();
+            }
+        }
+    });
+
+    Ok(())
+}
+
+fn is_watched(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| WATCHED_FILES.contains(&name))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn reports_a_burst_once_it_goes_quiet() {
+        let start = Instant::now();
+        let mut debouncer = ChangeDebouncer::default();
+        assert_eq!(debouncer.wait(start), None);
+        assert!(!debouncer.settled(start));
+
+        debouncer.event(start);
+        debouncer.event(ms(start, 100));
+        debouncer.event(ms(start, 250));
+        assert_eq!(
+            debouncer.wait(ms(start, 300)),
+            Some(Duration::from_millis(250))
+        );
+        assert!(!debouncer.settled(ms(start, 500)));
+        assert!(debouncer.settled(ms(start, 550)));
+
+        // Reported once, then idle again
+        assert!(!debouncer.settled(ms(start, 900)));
+        assert_eq!(debouncer.wait(ms(start, 900)), None);
+    }
+
+    #[test]
+    fn each_event_restarts_the_wait() {
+        let start = Instant::now();
+        let mut debouncer = ChangeDebouncer::default();
+
+        for step in 0..5 {
+            debouncer.event(ms(start, step * 200));
+            assert!(!debouncer.settled(ms(start, step * 200 + 250)));
+        }
+        assert_eq!(debouncer.wait(ms(start, 2_000)), Some(Duration::ZERO));
+        assert!(debouncer.settled(ms(start, 2_000)));
+    }
+
+    #[test]
+    fn watches_only_config_files() {
+        assert!(is_watched(Path::new("/data/config.json")));
+        assert!(is_watched(Path::new("/data/profiles.json")));
+        assert!(!is_watched(Path::new("/data/.config.json.tmp-42")));
+        assert!(!is_watched(Path::new("/data/recordings/history.json")));
+        assert!(!is_watched(Path::new("/")));
+    }
+}
@@ -0,0 +1,555 @@
+use crate::types::ActiveApplication;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Points each matching criterion adds to a rule's score, so a rule that
+/// pins down both the executable and the title beats one that only names
+/// the executable
+const EXECUTABLE_SCORE: i32 = 10;
+const TITLE_SCORE: i32 = 8;
+const APP_NAME_SCORE: i32 = 5;
+
+/// One row of the context table. Every criterion that is set has to match;
+/// a rule with none set never does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextRule {
+    pub id: String,
+    /// One of `APPLICATION_CONTEXTS`
+    pub context: String,
+    /// Executable file names, compared whole and case-insensitively with
+    /// any `.exe` dropped
+    #[serde(default)]
+    pub executables: Vec<String>,
+    /// Case-insensitive regex for the application name
+    #[serde(default)]
+    pub app_name: Option<String>,
+    /// Case-insensitive regex for the window title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Added to the score; use it to settle rules that match equally well
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextScore {
+    pub rule_id: String,
+    pub context: String,
+    pub score: i32,
+}
+
+/// The context picked for an application and the rule behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextDetection {
+    pub context: String,
+    /// `None` when no rule matched and the fallback context applies
+    pub rule: Option<ContextRule>,
+    pub reason: String,
+    /// Every rule that matched, best first
+    pub scores: Vec<ContextScore>,
+}
+
+/// Compile the rule's patterns, for validating it before it's saved
+pub fn check_rule(rule: &ContextRule) -> Result<(), String> {
+    if rule.executables.is_empty() && rule.app_name.is_none() && rule.title.is_none() {
+        return Err("must set executables, appName or title".to_string());
+    }
+    for pattern in rule.app_name.iter().chain(rule.title.iter()) {
+        regex(pattern)?;
+    }
+    Ok(())
+}
+
+fn regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// A rule with its patterns compiled
+struct CompiledRule {
+    rule: ContextRule,
+    app_name: Option<Regex>,
+    title: Option<Regex>,
+}
+
+impl CompiledRule {
+    /// `None` if a pattern doesn't compile; validation reports it on save
+    fn new(rule: ContextRule) -> Option<Self> {
+        Some(Self {
+            app_name: rule.app_name.as_deref().map(regex).transpose().ok()?,
+            title: rule.title.as_deref().map(regex).transpose().ok()?,
+            rule,
+        })
+    }
+}
+
+fn compiled_builtin_rules() -> &'static [CompiledRule] {
+    static BUILTIN: OnceLock<Vec<CompiledRule>> = OnceLock::new();
+    BUILTIN.get_or_init(|| builtin_rules().into_iter().filter_map(CompiledRule::new).collect())
+}
+
+/// User rules as written and as compiled
+type UserRuleCache = Option<(Vec<ContextRule>, Arc<Vec<CompiledRule>>)>;
+
+/// The user's rules compiled, reusing the last compilation until the rules
+/// in the config change
+fn compiled_user_rules(rules: &[ContextRule]) -> Arc<Vec<CompiledRule>> {
+    static CACHE: Mutex<UserRuleCache> = Mutex::new(None);
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached, compiled)) = cache.as_ref() {
+        if cached == rules {
+            return compiled.clone();
+        }
+    }
+    let compiled = Arc::new(rules.iter().cloned().filter_map(CompiledRule::new).collect::<Vec<_>>());
+    *cache = Some((rules.to_vec(), compiled.clone()));
+    compiled
+}
+
+/// The rule's score for `app` and why it matched, or `None` if it doesn't
+fn score(compiled: &CompiledRule, app: &ActiveApplication) -> Option<(i32, Vec<String>)> {
+    let rule = &compiled.rule;
+    let mut score = rule.priority;
+    let mut reasons = Vec::new();
+
+    if !rule.executables.is_empty() {
+        let executable = app.executable.to_lowercase();
+        let executable = executable.strip_suffix(".exe").unwrap_or(&executable);
+        if !rule.executables.iter().any(|e| e.eq_ignore_ascii_case(executable)) {
+            return None;
+        }
+        score += EXECUTABLE_SCORE;
+        reasons.push(format!("executable is {}", app.executable));
+    }
+
+    for (pattern, value, points, field) in [
+        (&compiled.app_name, &app.name, APP_NAME_SCORE, "app name"),
+        (&compiled.title, &app.title, TITLE_SCORE, "window title"),
+    ] {
+        let Some(pattern) = pattern else {
+            continue;
+        };
+        if !pattern.is_match(value) {
+            return None;
+        }
+        score += points;
+        reasons.push(format!("{} matches \"{}\"", field, pattern.as_str()));
+    }
+
+    (!reasons.is_empty()).then_some((score, reasons))
+}
+
+/// Score every rule against `app`, user rules first so they win ties with
+/// the built-in ones, and pick the best. `fallback` applies when none match.
+pub fn classify(user_rules: &[ContextRule], app: &ActiveApplication, fallback: &str) -> ContextDetection {
+    let user_rules = compiled_user_rules(user_rules);
+    let mut matched: Vec<(&ContextRule, i32, Vec<String>)> = user_rules
+        .iter()
+        .chain(compiled_builtin_rules())
+        .filter_map(|compiled| score(compiled, app).map(|(score, reasons)| (&compiled.rule, score, reasons)))
+        .collect();
+    // Stable, so equal scores keep user rules ahead
+    matched.sort_by_key(|(_, score, _)| std::cmp::Reverse(*score));
+
+    let scores = matched
+        .iter()
+        .map(|(rule, score, _)| ContextScore {
+            rule_id: rule.id.clone(),
+            context: rule.context.clone(),
+            score: *score,
+        })
+        .collect();
+
+    match matched.into_iter().next() {
+        Some((rule, score, reasons)) => ContextDetection {
+            context: rule.context.clone(),
+            reason: format!("{} (rule {}, score {})", reasons.join(" and "), rule.id, score),
+            rule: Some(rule.clone()),
+            scores,
+        },
+        None => ContextDetection {
+            context: fallback.to_string(),
+            rule: None,
+            reason: format!("no rule matches {}; using the fallback context", app.name),
+            scores,
+        },
+    }
+}
+
+const BROWSERS: &[&str] = &[
+    "chrome",
+    "google-chrome",
+    "google chrome",
+    "chromium",
+    "chromium-browser",
+    "firefox",
+    "firefox-esr",
+    "safari",
+    "msedge",
+    "microsoft-edge",
+    "microsoft edge",
+    "brave",
+    "brave-browser",
+    "brave browser",
+    "vivaldi",
+    "vivaldi-bin",
+    "opera",
+    "arc",
+];
+
+const TERMINALS: &[&str] = &[
+    "gnome-terminal-server",
+    "kgx",
+    "konsole",
+    "alacritty",
+    "kitty",
+    "wezterm-gui",
+    "foot",
+    "xterm",
+    "urxvt",
+    "terminator",
+    "tilix",
+    "ghostty",
+    "warp",
+    "terminal",
+    "iterm2",
+    "windowsterminal",
+    "cmd",
+    "powershell",
+    "pwsh",
+];
+
+/// The built-in table. Browser rows with a title pattern outscore the plain
+/// browser row, so webmail counts as email rather than browsing.
+pub fn builtin_rules() -> Vec<ContextRule> {
+    let rule = |id: &str, context: &str, executables: &[&str], title: Option<&str>| ContextRule {
+        id: format!("builtin:{}", id),
+        context: context.to_string(),
+        executables: executables.iter().map(|e| e.to_string()).collect(),
+        app_name: None,
+        title: title.map(str::to_string),
+        priority: 0,
+    };
+
+    vec![
+        rule(
+            "code-editor",
+            "code-editor",
+            &[
+                "code",
+                "code-oss",
+                "codium",
+                "cursor",
+                "windsurf",
+                "zed",
+                "zeditor",
+                "sublime_text",
+                "subl",
+                "idea",
+                "idea64",
+                "pycharm",
+                "pycharm64",
+                "webstorm",
+                "webstorm64",
+                "goland",
+                "goland64",
+                "clion",
+                "clion64",
+                "rustrover",
+                "rustrover64",
+                "phpstorm",
+                "rider",
+                "studio",
+                "studio64",
+                "xcode",
+                "emacs",
+                "gvim",
+                "nvim-qt",
+                "neovide",
+                "kate",
+                "helix",
+            ],
+            None,
+        ),
+        ContextRule {
+            app_name: Some(
+                r"^(visual studio code|android studio|xcode|intellij idea|pycharm|webstorm|goland|clion|rustrover)\b"
+                    .to_string(),
+            ),
+            ..rule("code-editor-app", "code-editor", &[], None)
+        },
+        rule("terminal", "terminal", TERMINALS, None),
+        rule(
+            "terminal-editor",
+            "code-editor",
+            TERMINALS,
+            Some(r" - N?VIM$|\bn?vim\b|\bhx\b|\bemacs\b"),
+        ),
+        rule(
+            "email",
+            "email",
+            &[
+                "thunderbird",
+                "outlook",
+                "olk",
+                "mail",
+                "evolution",
+                "geary",
+                "mailspring",
+                "betterbird",
+            ],
+            None,
+        ),
+        rule(
+            "email-web",
+            "email",
+            BROWSERS,
+            Some(r"\b(gmail|outlook|proton mail|fastmail|yahoo mail)\b"),
+        ),
+        rule(
+            "chat",
+            "chat",
+            &[
+                "slack",
+                "discord",
+                "teams",
+                "ms-teams",
+                "telegram",
+                "telegram-desktop",
+                "signal",
+                "signal-desktop",
+                "element",
+                "whatsapp",
+                "messages",
+                "zulip",
+                "mattermost",
+            ],
+            None,
+        ),
+        rule(
+            "chat-web",
+            "chat",
+            BROWSERS,
+            Some(r"\b(slack|discord|microsoft teams|whatsapp|messenger)\b"),
+        ),
+        rule(
+            "document",
+            "document",
+            &[
+                "winword",
+                "microsoft word",
+                "soffice",
+                "libreoffice",
+                "swriter",
+                "pages",
+                "abiword",
+            ],
+            None,
+        ),
+        rule(
+            "document-web",
+            "document",
+            BROWSERS,
+            Some(r"google docs|word online|\.docx?\b"),
+        ),
+        rule(
+            "notes",
+            "notes",
+            &[
+                "obsidian",
+                "notion",
+                "logseq",
+                "joplin",
+                "bear",
+                "notes",
+                "onenote",
+                "standard notes",
+                "simplenote",
+            ],
+            None,
+        ),
+        rule(
+            "notes-web",
+            "notes",
+            BROWSERS,
+            Some(r"\b(notion|obsidian|onenote|google keep)\b"),
+        ),
+        rule(
+            "presentation",
+            "presentation",
+            &["powerpnt", "microsoft powerpoint", "keynote", "simpress"],
+            None,
+        ),
+        rule(
+            "presentation-web",
+            "presentation",
+            BROWSERS,
+            Some(r"google slides|\.pptx?\b"),
+        ),
+        rule(
+            "design",
+            "design",
+            &["figma", "figma-linux", "sketch", "inkscape", "gimp", "krita"],
+            None,
+        ),
+        rule("design-web", "design", BROWSERS, Some(r"\bfigma\b")),
+        rule("browser", "browser", BROWSERS, None),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, executable: &str, title: &str) -> ActiveApplication {
+        ActiveApplication {
+            name: name.to_string(),
+            executable: executable.to_string(),
+            executable_path: None,
+            title: title.to_string(),
+            last_updated: 0,
+        }
+    }
+
+    fn context_of(app: &ActiveApplication) -> (String, Option<String>) {
+        let detection = classify(&[], app, "generic");
+        (detection.context, detection.rule.map(|rule| rule.id))
+    }
+
+    fn user_rule(id: &str, context: &str) -> ContextRule {
+        ContextRule {
+            id: id.to_string(),
+            context: context.to_string(),
+            executables: vec![],
+            app_name: None,
+            title: None,
+            priority: 0,
+        }
+    }
+
+    #[test]
+    fn studio_means_android_studio_only() {
+        for android_studio in [
+            app("Android Studio", "studio64.exe", "whispo – MainActivity.kt"),
+            app("Android Studio", "java", "whispo – MainActivity.kt"),
+        ] {
+            assert_eq!(context_of(&android_studio).0, "code-editor", "{:?}", android_studio);
+        }
+        assert_eq!(
+            context_of(&app("OBS Studio", "obs64.exe", "OBS 30.0.2 - Profile: Untitled")),
+            ("generic".to_string(), None)
+        );
+        assert_eq!(context_of(&app("RStudio", "rstudio", "analysis.R")).1, None);
+    }
+
+    #[test]
+    fn cmd_means_the_command_prompt_only() {
+        assert_eq!(
+            context_of(&app("Command Prompt", "cmd.exe", "C:\\Windows\\system32\\cmd.exe")),
+            ("terminal".to_string(), Some("builtin:terminal".to_string()))
+        );
+        assert_eq!(context_of(&app("Cmder", "cmder.exe", "Cmder")).1, None);
+        assert_eq!(context_of(&app("CmdLauncher", "cmdlauncher", "")).1, None);
+    }
+
+    #[test]
+    fn title_rules_outscore_plain_executable_rules() {
+        assert_eq!(
+            context_of(&app(
+                "Google Chrome",
+                "chrome.exe",
+                "Inbox (3) - me@example.com - Gmail - Google Chrome"
+            )),
+            ("email".to_string(), Some("builtin:email-web".to_string()))
+        );
+        assert_eq!(
+            context_of(&app("Google Chrome", "chrome.exe", "Rust - Google Chrome")),
+            ("browser".to_string(), Some("builtin:browser".to_string()))
+        );
+        assert_eq!(
+            context_of(&app("kitty", "kitty", "main.rs (~/code/whispo/src) - NVIM")),
+            ("code-editor".to_string(), Some("builtin:terminal-editor".to_string()))
+        );
+        assert_eq!(context_of(&app("kitty", "kitty", "~/code/whispo")).0, "terminal");
+    }
+
+    #[test]
+    fn every_criterion_must_match() {
+        let rule = ContextRule {
+            executables: vec!["firefox".to_string()],
+            title: Some(r"\bjira\b".to_string()),
+            ..user_rule("jira", "document")
+        };
+        let compiled = CompiledRule::new(rule).unwrap();
+
+        let (points, reasons) = score(&compiled, &app("Firefox", "firefox", "PROJ-12 - Jira")).unwrap();
+        assert_eq!(points, EXECUTABLE_SCORE + TITLE_SCORE);
+        assert_eq!(reasons.len(), 2);
+        assert!(score(&compiled, &app("Firefox", "firefox", "GitHub")).is_none());
+        assert!(score(&compiled, &app("Chrome", "chrome", "PROJ-12 - Jira")).is_none());
+
+        let empty = CompiledRule::new(user_rule("empty", "chat")).unwrap();
+        assert!(score(&empty, &app("Slack", "slack", "")).is_none());
+    }
+
+    #[test]
+    fn user_rules_win_ties_and_priority_settles_them() {
+        let slack = app("Slack", "slack", "general - Acme - Slack");
+        let notes = ContextRule {
+            executables: vec!["slack".to_string()],
+            ..user_rule("slack-notes", "notes")
+        };
+
+        let detection = classify(std::slice::from_ref(&notes), &slack, "generic");
+        assert_eq!(detection.context, "notes");
+        assert_eq!(detection.rule.unwrap().id, "slack-notes");
+        let ids: Vec<_> = detection.scores.iter().map(|s| s.rule_id.as_str()).collect();
+        assert_eq!(ids, ["slack-notes", "builtin:chat"]);
+
+        let outranked = ContextRule { priority: -1, ..notes };
+        assert_eq!(classify(&[outranked], &slack, "generic").context, "chat");
+    }
+
+    #[test]
+    fn changed_user_rules_are_recompiled() {
+        let app = app("Acme CRM", "acmecrm", "Leads");
+        let rule = ContextRule {
+            app_name: Some("^acme".to_string()),
+            ..user_rule("acme", "document")
+        };
+        assert_eq!(
+            classify(std::slice::from_ref(&rule), &app, "generic").context,
+            "document"
+        );
+
+        let changed = ContextRule {
+            context: "email".to_string(),
+            ..rule
+        };
+        assert_eq!(
+            classify(std::slice::from_ref(&changed), &app, "generic").context,
+            "email"
+        );
+        assert_eq!(classify(&[], &app, "generic").context, "generic");
+    }
+
+    #[test]
+    fn invalid_rules_never_match() {
+        let broken = ContextRule {
+            title: Some("(".to_string()),
+            ..user_rule("broken", "chat")
+        };
+        assert!(check_rule(&broken).is_err());
+        assert!(check_rule(&user_rule("empty", "chat")).is_err());
+        assert_eq!(
+            classify(&[broken], &app("Anything", "anything", "("), "generic").rule,
+            None
+        );
+    }
+}
@@ -0,0 +1,603 @@
+use super::format::{
+    apply_casing, bare, casing_at, format_code, join, phrase_at, tokenize, Casing, Glue, Token, CODE_SYMBOLS,
+};
+
+/// How one language writes the constructs a developer can dictate. Templates
+/// fill in `{name}`, `{params}`, `{value}`, `{condition}`, `{item}` and `{items}`.
+struct Language {
+    names: &'static [&'static str],
+    function: &'static str,
+    class: &'static str,
+    variable: &'static str,
+    constant: &'static str,
+    condition: &'static str,
+    else_if: &'static str,
+    else_: &'static str,
+    while_loop: &'static str,
+    for_each: &'static str,
+    print: &'static str,
+    comment: &'static str,
+    /// Written after a construct that opens a block
+    block_open: &'static str,
+    /// Written on its own line by "end block"; `None` where indentation
+    /// alone closes the block
+    block_close: Option<&'static str>,
+    /// Ends variable, constant, return and print statements
+    statement_end: &'static str,
+    indent: &'static str,
+    identifier: Casing,
+    and: &'static str,
+    or: &'static str,
+    not: &'static str,
+    true_: &'static str,
+    false_: &'static str,
+    null: &'static str,
+}
+
+const RUST: Language = Language {
+    names: &["rust"],
+    function: "fn {name}({params})",
+    class: "struct {name}",
+    variable: "let {name} = {value}",
+    constant: "const {name} = {value}",
+    condition: "if {condition}",
+    else_if: "else if {condition}",
+    else_: "else",
+    while_loop: "while {condition}",
+    for_each: "for {item} in {items}",
+    print: "println!(\"{}\", {value})",
+    comment: "//",
+    block_open: " {",
+    block_close: Some("}"),
+    statement_end: ";",
+    indent: "    ",
+    identifier: Casing::Snake,
+    and: "&&",
+    or: "||",
+    not: "!",
+    true_: "true",
+    false_: "false",
+    null: "None",
+};
+
+const PYTHON: Language = Language {
+    names: &["python"],
+    function: "def {name}({params})",
+    class: "class {name}",
+    variable: "{name} = {value}",
+    constant: "{name} = {value}",
+    condition: "if {condition}",
+    else_if: "elif {condition}",
+    else_: "else",
+    while_loop: "while {condition}",
+    for_each: "for {item} in {items}",
+    print: "print({value})",
+    comment: "#",
+    block_open: ":",
+    block_close: None,
+    statement_end: "",
+    indent: "    ",
+    identifier: Casing::Snake,
+    and: "and",
+    or: "or",
+    not: "not",
+    true_: "True",
+    false_: "False",
+    null: "None",
+};
+
+const JAVASCRIPT: Language = Language {
+    names: &[
+        "javascript",
+        "javascriptreact",
+        "typescript",
+        "typescriptreact",
+        "vue",
+        "svelte",
+    ],
+    function: "function {name}({params})",
+    class: "class {name}",
+    variable: "let {name} = {value}",
+    constant: "const {name} = {value}",
+    condition: "if ({condition})",
+    else_if: "else if ({condition})",
+    else_: "else",
+    while_loop: "while ({condition})",
+    for_each: "for (const {item} of {items})",
+    print: "console.log({value})",
+    comment: "//",
+    block_open: " {",
+    block_close: Some("}"),
+    statement_end: ";",
+    indent: "  ",
+    identifier: Casing::Camel,
+    and: "&&",
+    or: "||",
+    not: "!",
+    true_: "true",
+    false_: "false",
+    null: "null",
+};
+
+const GO: Language = Language {
+    names: &["go"],
+    function: "func {name}({params})",
+    class: "type {name} struct",
+    variable: "{name} := {value}",
+    constant: "const {name} = {value}",
+    condition: "if {condition}",
+    else_if: "else if {condition}",
+    else_: "else",
+    while_loop: "for {condition}",
+    for_each: "for _, {item} := range {items}",
+    print: "fmt.Println({value})",
+    comment: "//",
+    block_open: " {",
+    block_close: Some("}"),
+    statement_end: "",
+    indent: "\t",
+    identifier: Casing::Camel,
+    and: "&&",
+    or: "||",
+    not: "!",
+    true_: "true",
+    false_: "false",
+    null: "nil",
+};
+
+const LANGUAGES: &[&Language] = &[&RUST, &PYTHON, &JAVASCRIPT, &GO];
+
+/// The table for a language, JavaScript's when no file is focused, or
+/// `None` for a language without one
+fn language(name: Option<&str>) -> Option<&'static Language> {
+    let Some(name) = name else {
+        return Some(&JAVASCRIPT);
+    };
+    LANGUAGES
+        .iter()
+        .find(|language| language.names.contains(&name.to_lowercase().as_str()))
+        .copied()
+}
+
+/// Spoken between a function's name and its parameters
+const PARAMETER_MARKERS: &[&str] = &["taking", "that takes", "with parameters", "with arguments"];
+
+/// Words that are never folded into a multi-word identifier
+const RESERVED: &[&str] = &[
+    "in", "is", "as", "of", "new", "await", "async", "self", "this", "mut", "and", "or", "not", "typeof",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineCommand {
+    NewLine,
+    Indent,
+    Dedent,
+    EndBlock,
+}
+
+const LINE_COMMANDS: &[(&str, LineCommand)] = &[
+    ("new line indent", LineCommand::Indent),
+    ("newline indent", LineCommand::Indent),
+    ("new line dedent", LineCommand::Dedent),
+    ("newline dedent", LineCommand::Dedent),
+    ("new line outdent", LineCommand::Dedent),
+    ("newline outdent", LineCommand::Dedent),
+    ("new line", LineCommand::NewLine),
+    ("newline", LineCommand::NewLine),
+    ("end block", LineCommand::EndBlock),
+    ("close block", LineCommand::EndBlock),
+];
+
+fn line_command_at(words: &[String], index: usize) -> Option<(LineCommand, usize)> {
+    LINE_COMMANDS
+        .iter()
+        .filter_map(|(phrase, command)| phrase_at(words, index, phrase).map(|len| (*command, len)))
+        .max_by_key(|(_, len)| *len)
+}
+
+/// A stretch of one line's words, spoken and bare side by side
+#[derive(Clone, Copy)]
+struct Words<'a> {
+    spoken: &'a [&'a str],
+    bare: &'a [String],
+}
+
+impl<'a> Words<'a> {
+    fn len(&self) -> usize {
+        self.bare.len()
+    }
+
+    fn slice(&self, range: std::ops::Range<usize>) -> Self {
+        Self {
+            spoken: &self.spoken[range.clone()],
+            bare: &self.bare[range],
+        }
+    }
+
+    /// The words after `phrase` if they start with it
+    fn after(&self, phrase: &str) -> Option<Self> {
+        phrase_at(self.bare, 0, phrase).map(|len| self.slice(len..self.len()))
+    }
+
+    /// The words before and after the first `phrase`
+    fn split(&self, phrase: &str) -> Option<(Self, Self)> {
+        (0..self.len()).find_map(|index| {
+            phrase_at(self.bare, index, phrase).map(|len| (self.slice(0..index), self.slice(index + len..self.len())))
+        })
+    }
+
+    fn text(&self) -> String {
+        self.spoken.join(" ")
+    }
+}
+
+/// A name in the language's casing, or in the casing the user asked for
+fn identifier(words: Words, casing: Casing) -> String {
+    let (casing, start) = casing_at(words.bare, 0).unwrap_or((casing, 0));
+    let parts: Vec<String> = words.bare[start..]
+        .iter()
+        .map(|word| word.replace(['\'', '"'], ""))
+        .filter(|word| !word.is_empty())
+        .collect();
+    apply_casing(casing, &parts)
+}
+
+fn parameters(language: &Language, words: Words) -> String {
+    let mut parameters = Vec::new();
+    let mut start = 0;
+    for index in 0..=words.len() {
+        let separator = index == words.len() || matches!(words.bare[index].as_str(), "and" | "comma");
+        if separator {
+            if index > start {
+                parameters.push(identifier(words.slice(start..index), language.identifier));
+            }
+            start = index + 1;
+        }
+    }
+    parameters.join(", ")
+}
+
+fn is_plain_word(token: &Token) -> bool {
+    token.is_word && token.text.chars().all(|c| c.is_ascii_lowercase()) && !RESERVED.contains(&token.text.as_str())
+}
+
+/// Symbols, literals and logic in the language's spelling; runs of plain
+/// words fold into one identifier and "quote ... quote" is a string
+fn expression(language: &Language, words: Words) -> String {
+    let mut tokens = Vec::new();
+    let mut rest = words;
+    while rest.len() > 0 {
+        let (code, string) = match rest.split("quote") {
+            Some((code, after)) => match after.split("quote") {
+                Some((string, tail)) => {
+                    rest = tail;
+                    (code, Some(string))
+                }
+                None => {
+                    rest = after.slice(0..0);
+                    (code, Some(after))
+                }
+            },
+            None => {
+                let code = rest;
+                rest = rest.slice(0..0);
+                (code, None)
+            }
+        };
+        tokens.extend(code_tokens(language, code));
+        if let Some(string) = string {
+            tokens.push(Token::word(format!("\"{}\"", string.text())));
+        }
+    }
+    join(&tokens)
+}
+
+fn code_tokens(language: &Language, words: Words) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    for mut token in tokenize(&words.text(), CODE_SYMBOLS, true) {
+        if token.is_word {
+            let spelling = match token.text.to_lowercase().as_str() {
+                "and" => Some(language.and),
+                "or" => Some(language.or),
+                "true" => Some(language.true_),
+                "false" => Some(language.false_),
+                "null" | "none" | "nil" => Some(language.null),
+                _ => None,
+            };
+            if token.text.eq_ignore_ascii_case("not") && language.not == "!" {
+                token = Token {
+                    text: "!".to_string(),
+                    left: Glue::Space,
+                    right: Glue::Attach,
+                    is_word: false,
+                };
+            } else if let Some(spelling) = spelling {
+                token.text = spelling.to_string();
+            } else {
+                token.text = token.text.replace(['\'', '"'], "");
+                // Undo the sentence case speech-to-text adds, not a casing command's
+                let rest = token.text.chars().skip(1);
+                if !rest.clone().any(char::is_uppercase) {
+                    token.text = token.text.to_lowercase();
+                }
+            }
+        }
+
+        match tokens.last_mut() {
+            Some(previous) if is_plain_word(previous) && is_plain_word(&token) => {
+                let parts: Vec<String> = previous
+                    .text
+                    .split(['_', ' '])
+                    .chain(std::iter::once(token.text.as_str()))
+                    .map(str::to_string)
+                    .collect();
+                previous.text = apply_casing(language.identifier, &parts);
+            }
+            _ => tokens.push(token),
+        }
+    }
+    tokens
+}
+
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    values.iter().fold(template.to_string(), |text, (key, value)| {
+        text.replace(&format!("{{{}}}", key), value)
+    })
+}
+
+/// A line's construct in the language's syntax, and whether it opens a block
+fn render(language: &Language, words: Words) -> (String, bool) {
+    let statement = |text: String| (format!("{}{}", text, language.statement_end), false);
+    let block = |text: String| (format!("{}{}", text, language.block_open), true);
+    let expr = |words: Words| expression(language, words);
+
+    if let Some(rest) = ["function", "method"].iter().find_map(|p| words.after(p)) {
+        let (name, params) = PARAMETER_MARKERS
+            .iter()
+            .find_map(|marker| rest.split(marker))
+            .unwrap_or((rest, rest.slice(0..0)));
+        return block(fill(
+            language.function,
+            &[
+                ("name", identifier(name, language.identifier)),
+                ("params", parameters(language, params)),
+            ],
+        ));
+    }
+    if let Some(name) = ["class", "struct"].iter().find_map(|p| words.after(p)) {
+        return block(fill(language.class, &[("name", identifier(name, Casing::Pascal))]));
+    }
+    for (phrases, template, casing) in [
+        (&["constant"][..], language.constant, Casing::Constant),
+        (&["variable", "let", "var"][..], language.variable, language.identifier),
+    ] {
+        if let Some((name, value)) = phrases
+            .iter()
+            .find_map(|p| words.after(p))
+            .and_then(|rest| rest.split("equals"))
+        {
+            return statement(fill(
+                template,
+                &[("name", identifier(name, casing)), ("value", expr(value))],
+            ));
+        }
+    }
+    if let Some(condition) = ["else if", "otherwise if"].iter().find_map(|p| words.after(p)) {
+        return block(fill(language.else_if, &[("condition", expr(condition))]));
+    }
+    if matches!(words.bare, [word] if word == "else" || word == "otherwise") {
+        return block(language.else_.to_string());
+    }
+    if let Some(condition) = words.after("if") {
+        return block(fill(language.condition, &[("condition", expr(condition))]));
+    }
+    if let Some(condition) = words.after("while") {
+        return block(fill(language.while_loop, &[("condition", expr(condition))]));
+    }
+    if let Some((item, items)) = ["for each", "for"]
+        .iter()
+        .find_map(|p| words.after(p))
+        .and_then(|rest| rest.split("in"))
+    {
+        return block(fill(
+            language.for_each,
+            &[("item", identifier(item, language.identifier)), ("items", expr(items))],
+        ));
+    }
+    if let Some(value) = words.after("return") {
+        let value = expr(value);
+        return statement(if value.is_empty() {
+            "return".to_string()
+        } else {
+            format!("return {}", value)
+        });
+    }
+    if let Some(value) = words.after("print") {
+        return statement(fill(language.print, &[("value", expr(value))]));
+    }
+    if let Some(text) = words.after("comment") {
+        let text = text.text();
+        return (format!("{} {}", language.comment, text.trim_end_matches('.')), false);
+    }
+    (expr(words), false)
+}
+
+/// Write dictated code in `language`'s syntax. Each line is a construct
+/// ("function foo taking x and y", "if x equals equals 5", "return x") or a
+/// plain expression; "new line", "new line indent", "new line dedent" and
+/// "end block" move between lines. Languages without a table only get
+/// their spoken symbols converted, rather than another language's syntax.
+pub fn dictate_code(text: &str, language_name: Option<&str>) -> String {
+    let Some(language) = language(language_name) else {
+        return format_code(text);
+    };
+    let spoken: Vec<&str> = text.split_whitespace().collect();
+    let bare_words: Vec<String> = spoken.iter().map(|w| bare(w)).collect();
+    let words = Words {
+        spoken: &spoken,
+        bare: &bare_words,
+    };
+
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut level = 0usize;
+    let mut start = 0;
+    let mut index = 0;
+    while index <= words.len() {
+        let command = line_command_at(words.bare, index);
+        if command.is_none() && index < words.len() {
+            index += 1;
+            continue;
+        }
+
+        let line = words.slice(start..index);
+        if line.len() > 0 {
+            let (text, _) = render(language, line);
+            // "} else {" rather than an else on the line after the brace
+            let is_else = line.after("else").is_some() || line.after("otherwise").is_some();
+            match lines.last_mut() {
+                Some((last_level, last))
+                    if is_else && *last_level == level && Some(last.as_str()) == language.block_close =>
+                {
+                    *last = format!("{} {}", last, text);
+                }
+                _ => lines.push((level, text)),
+            }
+        }
+
+        let Some((command, len)) = command else {
+            break;
+        };
+        match command {
+            LineCommand::NewLine => {}
+            LineCommand::Indent => level += 1,
+            LineCommand::Dedent => level = level.saturating_sub(1),
+            LineCommand::EndBlock => {
+                level = level.saturating_sub(1);
+                if let Some(close) = language.block_close {
+                    lines.push((level, close.to_string()));
+                }
+            }
+        }
+        index += len;
+        start = index;
+    }
+
+    lines
+        .iter()
+        .map(|(level, text)| format!("{}{}", language.indent.repeat(*level), text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dictate_code;
+
+    /// Language, what was said and the code it should produce
+    const GOLDEN: &[(&str, &str, &str)] = &[
+        (
+            "rust",
+            "function get user taking user id and name",
+            "fn get_user(user_id, name) {",
+        ),
+        (
+            "python",
+            "function get user taking user id and name",
+            "def get_user(user_id, name):",
+        ),
+        (
+            "typescript",
+            "function get user taking user id and name",
+            "function getUser(userId, name) {",
+        ),
+        (
+            "go",
+            "function get user taking user id and name",
+            "func getUser(userId, name) {",
+        ),
+        ("rust", "Function main.", "fn main() {"),
+        ("python", "snake case user id", "user_id"),
+        ("javascript", "snake case user id", "user_id"),
+        ("rust", "if x equals equals 5", "if x == 5 {"),
+        ("python", "if x equals equals 5", "if x == 5:"),
+        ("javascript", "if x equals equals 5", "if (x == 5) {"),
+        ("python", "if ready and not done", "if ready and not done:"),
+        ("rust", "if ready and not done", "if ready && !done {"),
+        (
+            "rust",
+            "variable user name equals quote Hello world quote",
+            "let user_name = \"Hello world\";",
+        ),
+        (
+            "python",
+            "variable user name equals quote Hello world quote",
+            "user_name = \"Hello world\"",
+        ),
+        ("typescript", "constant max retries equals 3", "const MAX_RETRIES = 3;"),
+        ("go", "variable user name equals null", "userName := nil"),
+        ("python", "return null", "return None"),
+        ("python", "return true", "return True"),
+        ("rust", "for each item in items", "for item in items {"),
+        ("python", "for item in items", "for item in items:"),
+        ("javascript", "for each item in items", "for (const item of items) {"),
+        ("go", "for each item in items", "for _, item := range items {"),
+        ("go", "while count less than 10", "for count < 10 {"),
+        ("rust", "print total", "println!(\"{}\", total);"),
+        ("python", "print total", "print(total)"),
+        ("javascript", "print total", "console.log(total);"),
+        ("rust", "comment fix this later.", "// fix this later"),
+        ("python", "comment fix this later.", "# fix this later"),
+        ("rust", "class user account", "struct UserAccount {"),
+        ("go", "class user account", "type UserAccount struct {"),
+        ("rust", "total plus equals item dot price", "total += item.price"),
+        (
+            "rust",
+            "function add taking a and b new line indent return a plus b new line end block",
+            "fn add(a, b) {\n    return a + b;\n}",
+        ),
+        (
+            "python",
+            "function add taking a and b new line indent return a plus b",
+            "def add(a, b):\n    return a + b",
+        ),
+        (
+            "rust",
+            "if ready new line indent return true end block else new line indent return false end block",
+            "if ready {\n    return true;\n} else {\n    return false;\n}",
+        ),
+        (
+            "python",
+            "if ready new line indent return true new line dedent else new line indent return false",
+            "if ready:\n    return True\nelse:\n    return False",
+        ),
+        (
+            "go",
+            "function main new line indent print message end block",
+            "func main() {\n\tfmt.Println(message)\n}",
+        ),
+        ("", "variable count equals 0", "let count = 0;"),
+    ];
+
+    #[test]
+    fn golden() {
+        for (language, spoken, expected) in GOLDEN {
+            let language = Some(*language).filter(|l| !l.is_empty());
+            assert_eq!(
+                dictate_code(spoken, language),
+                *expected,
+                "{:?} in {:?}",
+                spoken,
+                language
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_languages_only_get_symbols() {
+        assert_eq!(
+            dictate_code("function get user taking user id", Some("kotlin")),
+            "function get user taking user id"
+        );
+        assert_eq!(dictate_code("if x equals equals 5", Some("haskell")), "if x == 5");
+    }
+}
@@ -0,0 +1,659 @@
+use super::code::dictate_code;
+use super::shell::dictate_command;
+use crate::config::{ContextFormattingConfig, StreamingDictationConfig};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Whether a symbol sits against its neighbour or is spaced from it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Glue {
+    Space,
+    Attach,
+    /// Attach after an identifier, e.g. the "(" of a call, but not after a
+    /// keyword or operator
+    AfterWord,
+}
+
+/// A spoken phrase and what it's written as
+pub(super) struct Symbol {
+    phrase: &'static str,
+    text: &'static str,
+    left: Glue,
+    right: Glue,
+}
+
+pub(super) const fn symbol(phrase: &'static str, text: &'static str, left: Glue, right: Glue) -> Symbol {
+    Symbol {
+        phrase,
+        text,
+        left,
+        right,
+    }
+}
+
+use Glue::{AfterWord, Attach, Space};
+
+pub(super) const CODE_SYMBOLS: &[Symbol] = &[
+    symbol("new line", "\n", Attach, Attach),
+    symbol("newline", "\n", Attach, Attach),
+    symbol("open paren", "(", AfterWord, Attach),
+    symbol("open parenthesis", "(", AfterWord, Attach),
+    symbol("left paren", "(", AfterWord, Attach),
+    symbol("close paren", ")", Attach, Space),
+    symbol("close parenthesis", ")", Attach, Space),
+    symbol("right paren", ")", Attach, Space),
+    symbol("open bracket", "[", Attach, Attach),
+    symbol("close bracket", "]", Attach, Space),
+    symbol("open brace", "{", Space, Space),
+    symbol("open curly", "{", Space, Space),
+    symbol("close brace", "}", Space, Space),
+    symbol("close curly", "}", Space, Space),
+    symbol("fat arrow", "=>", Space, Space),
+    symbol("arrow", "->", Space, Space),
+    symbol("triple equals", "===", Space, Space),
+    symbol("double equals", "==", Space, Space),
+    symbol("equals equals", "==", Space, Space),
+    symbol("not equals", "!=", Space, Space),
+    symbol("plus equals", "+=", Space, Space),
+    symbol("minus equals", "-=", Space, Space),
+    symbol("less than or equal to", "<=", Space, Space),
+    symbol("less than or equals", "<=", Space, Space),
+    symbol("greater than or equal to", ">=", Space, Space),
+    symbol("greater than or equals", ">=", Space, Space),
+    symbol("less than", "<", Space, Space),
+    symbol("greater than", ">", Space, Space),
+    symbol("equals", "=", Space, Space),
+    symbol("plus plus", "++", Attach, Space),
+    symbol("minus minus", "--", Attach, Space),
+    symbol("plus", "+", Space, Space),
+    symbol("minus", "-", Space, Space),
+    symbol("times", "*", Space, Space),
+    symbol("divided by", "/", Space, Space),
+    symbol("modulo", "%", Space, Space),
+    symbol("double ampersand", "&&", Space, Space),
+    symbol("logical and", "&&", Space, Space),
+    symbol("double pipe", "||", Space, Space),
+    symbol("logical or", "||", Space, Space),
+    symbol("pipe", "|", Space, Space),
+    symbol("ampersand", "&", Space, Attach),
+    symbol("bang", "!", AfterWord, Attach),
+    symbol("double colon", "::", Attach, Attach),
+    symbol("colon", ":", Attach, Space),
+    symbol("semicolon", ";", Attach, Space),
+    symbol("comma", ",", Attach, Space),
+    symbol("dot", ".", Attach, Attach),
+    symbol("underscore", "_", Attach, Attach),
+    symbol("dash", "-", Attach, Attach),
+    symbol("slash", "/", Attach, Attach),
+    symbol("backslash", "\\", Attach, Attach),
+    symbol("hash", "#", Space, Attach),
+    symbol("dollar sign", "$", Space, Attach),
+    symbol("at sign", "@", Space, Attach),
+    symbol("tilde", "~", Space, Attach),
+    symbol("caret", "^", Space, Space),
+    symbol("question mark", "?", Attach, Space),
+    symbol("backtick", "`", Space, Attach),
+];
+
+/// Words that take a space before "(" instead of a call's attached one
+const KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "match", "catch", "return", "in", "and", "or", "not",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Casing {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+    Constant,
+    Upper,
+    Lower,
+}
+
+const CASINGS: &[(&str, Casing)] = &[
+    ("camel case", Casing::Camel),
+    ("pascal case", Casing::Pascal),
+    ("snake case", Casing::Snake),
+    ("kebab case", Casing::Kebab),
+    ("screaming snake case", Casing::Constant),
+    ("constant case", Casing::Constant),
+    ("all caps", Casing::Upper),
+    ("upper case", Casing::Upper),
+    ("lower case", Casing::Lower),
+];
+
+pub(super) fn apply_casing(casing: Casing, words: &[String]) -> String {
+    let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+    let capitalized = |w: &String| capitalize(w);
+    match casing {
+        Casing::Camel => lower
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.clone() } else { capitalized(w) })
+            .collect(),
+        Casing::Pascal => lower.iter().map(capitalized).collect(),
+        Casing::Snake => lower.join("_"),
+        Casing::Kebab => lower.join("-"),
+        Casing::Constant => lower.join("_").to_uppercase(),
+        Casing::Upper => lower.join(" ").to_uppercase(),
+        Casing::Lower => lower.join(" "),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token {
+    pub(super) text: String,
+    pub(super) left: Glue,
+    pub(super) right: Glue,
+    pub(super) is_word: bool,
+}
+
+impl Token {
+    pub(super) fn word(text: String) -> Self {
+        Self {
+            text,
+            left: Space,
+            right: Space,
+            is_word: true,
+        }
+    }
+}
+
+/// A spoken word with the punctuation speech-to-text added around it removed
+pub(super) fn bare(word: &str) -> String {
+    word.trim_matches([',', '.', '!', '?', ';', ':', '"']).to_lowercase()
+}
+
+/// Length in words of `phrase` if the words at `index` say it
+pub(super) fn phrase_at(words: &[String], index: usize, phrase: &str) -> Option<usize> {
+    let parts: Vec<&str> = phrase.split(' ').collect();
+    let spoken = words.get(index..index + parts.len())?;
+    spoken
+        .iter()
+        .zip(&parts)
+        .all(|(word, part)| word == part)
+        .then_some(parts.len())
+}
+
+fn symbol_at<'a>(symbols: &'a [Symbol], words: &[String], index: usize) -> Option<(&'a Symbol, usize)> {
+    // Longest phrase first, so "double equals" wins over "equals"
+    symbols
+        .iter()
+        .filter_map(|s| phrase_at(words, index, s.phrase).map(|len| (s, len)))
+        .max_by_key(|(_, len)| *len)
+}
+
+pub(super) fn casing_at(words: &[String], index: usize) -> Option<(Casing, usize)> {
+    CASINGS
+        .iter()
+        .filter_map(|(phrase, casing)| phrase_at(words, index, phrase).map(|len| (*casing, len)))
+        .max_by_key(|(_, len)| *len)
+}
+
+/// Turn spoken words into tokens: symbol phrases become symbols and a casing
+/// command joins the plain words after it, up to the next symbol or command
+pub(super) fn tokenize(text: &str, symbols: &[Symbol], casing_commands: bool) -> Vec<Token> {
+    let spoken: Vec<&str> = text.split_whitespace().collect();
+    let words: Vec<String> = spoken.iter().map(|w| bare(w)).collect();
+    let is_command = |index: usize| {
+        symbol_at(symbols, &words, index).is_some() || (casing_commands && casing_at(&words, index).is_some())
+    };
+
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < words.len() {
+        if casing_commands {
+            if let Some((casing, len)) = casing_at(&words, index) {
+                let start = index + len;
+                let mut end = start;
+                while end < words.len() && !is_command(end) {
+                    end += 1;
+                }
+                if end > start {
+                    let parts: Vec<String> = words[start..end].to_vec();
+                    tokens.push(Token::word(apply_casing(casing, &parts)));
+                    index = end;
+                    continue;
+                }
+            }
+        }
+
+        if let Some((symbol, len)) = symbol_at(symbols, &words, index) {
+            tokens.push(Token {
+                text: symbol.text.to_string(),
+                left: symbol.left,
+                right: symbol.right,
+                is_word: false,
+            });
+            index += len;
+            continue;
+        }
+
+        let word = spoken[index].trim_matches([',', '.', '!', '?', ';', ':']);
+        if !word.is_empty() {
+            tokens.push(Token::word(word.to_string()));
+        }
+        index += 1;
+    }
+    tokens
+}
+
+/// Join tokens, spacing them by their glue
+pub(super) fn join(tokens: &[Token]) -> String {
+    let mut output = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if let Some(previous) = index.checked_sub(1).map(|i| &tokens[i]) {
+            let attach_left = match token.left {
+                Attach => true,
+                Space => false,
+                AfterWord => previous.is_word && !KEYWORDS.contains(&previous.text.to_lowercase().as_str()),
+            };
+            if !(attach_left || previous.right == Attach) {
+                output.push(' ');
+            }
+        }
+        output.push_str(&token.text);
+    }
+    output.lines().map(str::trim_end).collect::<Vec<_>>().join("\n")
+}
+
+fn regex(cache: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cache.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Drop hesitation sounds along with the comma speech-to-text puts after them
+pub fn remove_fillers(text: &str) -> String {
+    static FILLERS: OnceLock<Regex> = OnceLock::new();
+    let fillers = regex(&FILLERS, r"(?i)\b(?:u+m+|u+h+|e+r+m*|h+m+)\b[,.]?\s*");
+    collapse_whitespace(&fillers.replace_all(text, ""))
+}
+
+/// Single spaces within lines, no blank space at either end
+fn collapse_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Capitalize the first letter of the text and of every sentence. A
+/// sentence starts after a new line, or after ".", "!" or "?" and a space,
+/// so "example.com" stays as it is.
+fn capitalize_sentences(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut start_of_sentence = true;
+    let mut after_terminator = false;
+    for c in text.chars() {
+        if start_of_sentence && c.is_alphabetic() {
+            output.extend(c.to_uppercase());
+            start_of_sentence = false;
+            after_terminator = false;
+            continue;
+        }
+        if c == '\n' || (after_terminator && c.is_whitespace()) {
+            start_of_sentence = true;
+        } else if !c.is_whitespace() {
+            start_of_sentence = false;
+        }
+        // Closing quotes and brackets belong to the sentence they end
+        after_terminator = matches!(c, '.' | '!' | '?') || (after_terminator && matches!(c, '"' | '\'' | ')'));
+        output.push(c);
+    }
+    output
+}
+
+fn ensure_final_punctuation(text: &str) -> String {
+    match text.chars().last() {
+        None => String::new(),
+        Some('.' | '!' | '?' | ':' | ';') => text.to_string(),
+        Some(_) => format!("{}.", text.trim_end_matches(',')),
+    }
+}
+
+/// The cleanup `streamingDictation` asks for, which the formatters for
+/// prose follow rather than redoing it their own way: fillers go only if
+/// `normalization.removeFillers` is on, and sentences are capitalized and
+/// closed only in the auto capitalization and punctuation modes
+#[derive(Debug, Clone, Copy)]
+struct Cleanup {
+    remove_fillers: bool,
+    capitalize: bool,
+    punctuate: bool,
+}
+
+impl Cleanup {
+    fn new(dictation: &StreamingDictationConfig) -> Self {
+        Self {
+            remove_fillers: dictation.normalization.remove_fillers,
+            capitalize: dictation.capitalization_mode == "auto",
+            punctuate: dictation.punctuation_mode == "auto",
+        }
+    }
+
+    fn fillers(&self, text: &str) -> String {
+        if self.remove_fillers {
+            remove_fillers(text)
+        } else {
+            collapse_whitespace(text)
+        }
+    }
+
+    fn sentences(&self, text: &str) -> String {
+        let text = if self.capitalize {
+            capitalize_sentences(text)
+        } else {
+            text.to_string()
+        };
+        if self.punctuate {
+            ensure_final_punctuation(&text)
+        } else {
+            text
+        }
+    }
+
+    fn capitalize(&self, word: &str) -> String {
+        if self.capitalize {
+            capitalize(word)
+        } else {
+            word.to_string()
+        }
+    }
+
+    /// Title-case a name, e.g. "sarah jones" -> "Sarah Jones"
+    fn name(&self, name: &str) -> String {
+        name.split_whitespace()
+            .map(|word| self.capitalize(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn format_generic(text: &str, cleanup: Cleanup) -> String {
+    cleanup.sentences(&cleanup.fillers(text))
+}
+
+pub(super) fn format_code(text: &str) -> String {
+    join(&tokenize(text, CODE_SYMBOLS, true))
+}
+
+/// Words that follow a sign-off without being the sender's name, as in
+/// "thanks for everything" or "cheers everyone"
+const NOT_NAMES: &[&str] = &[
+    "again",
+    "so",
+    "for",
+    "a",
+    "very",
+    "all",
+    "everyone",
+    "everybody",
+    "in",
+    "to",
+    "you",
+    "and",
+];
+
+/// An opening greeting on its own line, the body as sentences and a
+/// sign-off with the sender's name below it. A greeting only takes a name of
+/// one or two words followed by a comma. The sign-off has to start the
+/// text or follow the end of a sentence or clause, so "I'll try my best"
+/// keeps its "best".
+fn format_email(text: &str, cleanup: Cleanup) -> String {
+    static GREETING: OnceLock<Regex> = OnceLock::new();
+    static SIGN_OFF: OnceLock<Regex> = OnceLock::new();
+    let greeting_pattern = regex(
+        &GREETING,
+        r"(?i)^(?P<greeting>hi|hello|hey|dear|good morning|good afternoon|good evening)\b(?:\s+(?P<name>[\w'-]+(?:\s+[\w'-]+)?)\s*,|\s*[,.!:])\s*",
+    );
+    let sign_off_pattern = regex(
+        &SIGN_OFF,
+        r"(?i)(?:^|[.!?,;]\s*)(?P<sign_off>thanks again|thank you again|thanks so much|thank you so much|thanks|thank you|many thanks|best regards|kind regards|warm regards|regards|best|cheers|sincerely|talk soon)\s*[,.!]?(?:\s+(?P<name>[\w'-]+(?:\s+[\w'-]+)?))?[.!]?$",
+    );
+
+    let mut body = cleanup.fillers(text);
+    let mut greeting = None;
+    if let Some(captures) = greeting_pattern.captures(&body) {
+        let name = captures
+            .name("name")
+            .map(|n| format!(" {}", cleanup.name(n.as_str())))
+            .unwrap_or_default();
+        greeting = Some(format!(
+            "{}{},",
+            cleanup.capitalize(&captures["greeting"].to_lowercase()),
+            name
+        ));
+        body = body[captures.get(0).unwrap().end()..].to_string();
+    }
+
+    let mut sign_off = None;
+    let captures = sign_off_pattern.captures(&body).filter(|captures| {
+        let first_word = captures.name("name").and_then(|n| n.as_str().split_whitespace().next());
+        !first_word.is_some_and(|word| NOT_NAMES.contains(&word.to_lowercase().as_str()))
+    });
+    if let Some(captures) = captures {
+        let mut lines = vec![format!("{},", cleanup.capitalize(&captures["sign_off"].to_lowercase()))];
+        lines.extend(captures.name("name").map(|n| cleanup.name(n.as_str())));
+        sign_off = Some(lines.join("\n"));
+        // Keep the "?" or "!" that ended the body
+        body = body[..captures.name("sign_off").unwrap().start()].to_string();
+    }
+
+    let body = cleanup.sentences(body.trim());
+    [greeting, Some(body).filter(|b| !b.is_empty()), sign_off]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Chat stays casual: just the hesitations and stray spacing go
+fn format_chat(text: &str, cleanup: Cleanup) -> String {
+    cleanup.fillers(text)
+}
+
+fn bullet(item: &str, cleanup: Cleanup) -> Option<String> {
+    let item = item.trim().trim_matches([',', '.', ';', ':']).trim();
+    (!item.is_empty()).then(|| format!("- {}", cleanup.capitalize(item)))
+}
+
+/// Spoken "bullet" or "next item" markers, or a "heading: a, b and c"
+/// list, become a bullet list; anything else is tidied like prose
+fn format_notes(text: &str, cleanup: Cleanup) -> String {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    static SERIES: OnceLock<Regex> = OnceLock::new();
+    let marker = regex(
+        &MARKER,
+        r"(?i)[,.]?\s*\b(?:bullet point|bullet|next item|new item|next point)\b[,.:]?\s*",
+    );
+    let series_separator = regex(&SERIES, r"(?i),\s*(?:and\s+)?|\s+and\s+");
+
+    let text = cleanup.fillers(text);
+    if marker.is_match(&text) {
+        let mut parts = marker.split(&text);
+        let heading = parts.next().map(str::trim).filter(|h| !h.is_empty());
+        let items: Vec<String> = parts.filter_map(|item| bullet(item, cleanup)).collect();
+        if !items.is_empty() {
+            let heading = heading.map(|h| cleanup.capitalize(h.trim_end_matches([',', '.', ':'])) + ":");
+            return heading.into_iter().chain(items).collect::<Vec<_>>().join("\n");
+        }
+    }
+
+    if let Some((heading, list)) = text.split_once(':') {
+        let items: Vec<String> = series_separator
+            .split(list)
+            .filter_map(|item| bullet(item, cleanup))
+            .collect();
+        if items.len() >= 3 && !heading.trim().is_empty() {
+            let heading = format!("{}:", cleanup.capitalize(heading.trim()));
+            return std::iter::once(heading).chain(items).collect::<Vec<_>>().join("\n");
+        }
+    }
+
+    format_generic(&text, cleanup)
+}
+
+/// Format a transcript for an application context without calling a model.
+/// Contexts without a formatter of their own get generic sentence cleanup,
+/// as far as `dictation`'s normalization settings allow. `language` is the
+/// focused file's, for code dictation.
+pub fn format_for_context(
+    context: &str,
+    text: &str,
+    formatting: &ContextFormattingConfig,
+    dictation: &StreamingDictationConfig,
+    language: Option<&str>,
+) -> String {
+    let cleanup = Cleanup::new(dictation);
+    match context {
+        "code-editor" if formatting.code_dictation => dictate_code(text, language),
+        "code-editor" => format_code(text),
+        "terminal" => dictate_command(text, &formatting.shell),
+        "email" => format_email(text, cleanup),
+        "chat" => format_chat(text, cleanup),
+        "notes" => format_notes(text, cleanup),
+        _ => format_generic(text, cleanup),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What was said in each context and how it should be written
+    const CASES: &[(&str, &str, &str)] = &[
+        (
+            "generic",
+            "um so the meeting is at three. see you there",
+            "So the meeting is at three. See you there.",
+        ),
+        (
+            "generic",
+            "check example.com for details",
+            "Check example.com for details.",
+        ),
+        ("generic", "is it ready? yes! great", "Is it ready? Yes! Great."),
+        ("generic", "he said \"stop.\" then left", "He said \"stop.\" Then left."),
+        ("document", "uh the draft is done", "The draft is done."),
+        ("email", "hi john, I'll try my best.", "Hi John,\n\nI'll try my best."),
+        // Only one or two words before a comma are a name
+        ("email", "hey can you help. thanks", "Hey can you help.\n\nThanks,"),
+        ("email", "hello, can you help", "Hello,\n\nCan you help."),
+        ("email", "dear mary jane, welcome", "Dear Mary Jane,\n\nWelcome."),
+        ("email", "please do your best work", "Please do your best work."),
+        (
+            "email",
+            "talk to you soon. Thanks again",
+            "Talk to you soon.\n\nThanks again,",
+        ),
+        (
+            "email",
+            "hi sarah, the report is attached. best regards, tom",
+            "Hi Sarah,\n\nThe report is attached.\n\nBest regards,\nTom",
+        ),
+        (
+            "email",
+            "can you check the numbers? thanks",
+            "Can you check the numbers?\n\nThanks,",
+        ),
+        (
+            "email",
+            "it all worked out, thanks for everything",
+            "It all worked out, thanks for everything.",
+        ),
+        (
+            "email",
+            "dear team, see example.com. cheers",
+            "Dear Team,\n\nSee example.com.\n\nCheers,",
+        ),
+        ("chat", "um hey are you free   later", "hey are you free later"),
+        (
+            "notes",
+            "groceries bullet milk bullet eggs",
+            "Groceries:\n- Milk\n- Eggs",
+        ),
+        (
+            "notes",
+            "todo: call bob, email alice and fix the bug",
+            "Todo:\n- Call bob\n- Email alice\n- Fix the bug",
+        ),
+        ("notes", "remember to water the plants", "Remember to water the plants."),
+        ("code-editor", "open paren x comma y close paren", "(x, y)"),
+        ("code-editor", "snake case user id equals 5", "user_id = 5"),
+        (
+            "terminal",
+            "git status pipe grep modified",
+            "git status | grep modified",
+        ),
+    ];
+
+    #[test]
+    fn formats_each_context() {
+        let formatting = ContextFormattingConfig::default();
+        let dictation = StreamingDictationConfig::default();
+        for (context, spoken, expected) in CASES {
+            assert_eq!(
+                format_for_context(context, spoken, &formatting, &dictation, None),
+                *expected,
+                "{} {:?}",
+                context,
+                spoken
+            );
+        }
+    }
+
+    #[test]
+    fn follows_normalization_settings() {
+        let formatting = ContextFormattingConfig::default();
+        let mut dictation = StreamingDictationConfig {
+            punctuation_mode: "off".to_string(),
+            capitalization_mode: "off".to_string(),
+            ..Default::default()
+        };
+        dictation.normalization.remove_fillers = false;
+        let format = |context: &str, text: &str, dictation: &StreamingDictationConfig| {
+            format_for_context(context, text, &formatting, dictation, None)
+        };
+
+        assert_eq!(
+            format("generic", "um so the meeting is at three", &dictation),
+            "um so the meeting is at three"
+        );
+        assert_eq!(format("chat", "uh hey   there", &dictation), "uh hey there");
+        assert_eq!(
+            format("notes", "groceries bullet milk bullet eggs", &dictation),
+            "groceries:\n- milk\n- eggs"
+        );
+
+        dictation.punctuation_mode = "manual".to_string();
+        dictation.capitalization_mode = "manual".to_string();
+        assert_eq!(
+            format("email", "hi john, the report is attached. thanks", &dictation),
+            "hi john,\n\nthe report is attached.\n\nthanks,"
+        );
+        assert_eq!(
+            format("generic", "meet London at NASA", &dictation),
+            "meet London at NASA"
+        );
+    }
+
+    #[test]
+    fn sentences_start_after_a_terminator_and_a_space() {
+        assert_eq!(
+            capitalize_sentences("visit example.com. then v1.2 ships"),
+            "Visit example.com. Then v1.2 ships"
+        );
+        assert_eq!(capitalize_sentences("one\ntwo?three"), "One\nTwo?three");
+        assert_eq!(capitalize_sentences("(really?) yes"), "(really?) Yes");
+    }
+}
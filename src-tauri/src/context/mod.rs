@@ -0,0 +1,12 @@
+// Working out what the user is dictating into from the focused window
+
+mod classifier;
+mod code;
+mod format;
+mod shell;
+mod title;
+
+pub use classifier::{check_rule, classify, ContextDetection, ContextRule};
+pub use format::format_for_context;
+pub use shell::{check_deny_rule, dangerous_command, default_deny_list, DenyRule};
+pub use title::{check_title_pattern, TitleContext, TitleParserRegistry, TitlePattern};
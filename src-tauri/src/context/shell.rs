@@ -0,0 +1,369 @@
+use super::format::{bare, join, phrase_at, remove_fillers, symbol, tokenize, Glue, Symbol, Token};
+use crate::config::ShellConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use Glue::{Attach, Space};
+
+const SHELL_SYMBOLS: &[Symbol] = &[
+    symbol("dash dash", "--", Space, Attach),
+    symbol("double dash", "--", Space, Attach),
+    symbol("dash", "-", Space, Attach),
+    symbol("slash", "/", Attach, Attach),
+    symbol("dot", ".", Attach, Attach),
+    symbol("tilde", "~", Space, Attach),
+    symbol("pipe", "|", Space, Space),
+    symbol("and and", "&&", Space, Space),
+    symbol("double ampersand", "&&", Space, Space),
+    symbol("redirect to", ">", Space, Space),
+    symbol("append to", ">>", Space, Space),
+    symbol("greater than", ">", Space, Space),
+    symbol("less than", "<", Space, Space),
+    symbol("equals", "=", Attach, Attach),
+    symbol("dollar sign", "$", Space, Attach),
+    symbol("dollar", "$", Space, Attach),
+    symbol("star", "*", Space, Attach),
+    symbol("asterisk", "*", Space, Attach),
+    symbol("colon", ":", Attach, Attach),
+    symbol("at sign", "@", Attach, Attach),
+    symbol("underscore", "_", Attach, Attach),
+    symbol("semicolon", ";", Attach, Space),
+];
+
+/// Spoken commands for common shell programs
+const SHELL_COMMANDS: &[(&str, &str)] = &[
+    ("change directory to", "cd"),
+    ("change directory", "cd"),
+    ("list directory", "ls"),
+    ("list files", "ls"),
+    ("make directory", "mkdir"),
+    ("remove file", "rm"),
+    ("copy file", "cp"),
+    ("move file", "mv"),
+    ("print working directory", "pwd"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    Double,
+    Single,
+}
+
+const QUOTE_OPENERS: &[(&str, Quote)] = &[
+    ("single quote", Quote::Single),
+    ("double quote", Quote::Double),
+    ("open quote", Quote::Double),
+    ("quote", Quote::Double),
+];
+
+const QUOTE_CLOSERS: &[(&str, Quote)] = &[
+    ("end single quote", Quote::Single),
+    ("single quote", Quote::Single),
+    ("double quote", Quote::Double),
+    ("end quote", Quote::Double),
+    ("end quote", Quote::Single),
+    ("close quote", Quote::Double),
+    ("close quote", Quote::Single),
+    ("unquote", Quote::Double),
+    ("unquote", Quote::Single),
+    ("quote", Quote::Double),
+    ("quote", Quote::Single),
+];
+
+fn quote_at<T: Copy>(
+    table: &[(&str, T)],
+    words: &[String],
+    index: usize,
+    matches: impl Fn(T) -> bool,
+) -> Option<(T, usize)> {
+    table
+        .iter()
+        .filter(|(_, value)| matches(*value))
+        .filter_map(|(phrase, value)| phrase_at(words, index, phrase).map(|len| (*value, len)))
+        .max_by_key(|(_, len)| *len)
+}
+
+/// One argument as the shell should see it, whatever it contains
+fn quoted(text: &str, quote: Quote) -> String {
+    match quote {
+        Quote::Double => {
+            let escaped = text
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "\\$")
+                .replace('`', "\\`");
+            format!("\"{}\"", escaped)
+        }
+        Quote::Single => format!("'{}'", text.replace('\'', r"'\''")),
+    }
+}
+
+/// Typographic characters speech-to-text likes to produce, in their ASCII
+/// form, and control characters like a newline, which would make the
+/// terminal run the line early, as spaces
+fn plain_characters(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201c}' | '\u{201d}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Patterns compiled from the config, kept until the part of the config
+/// they came from changes
+struct CompiledCache<K, V>(Mutex<Option<(K, Arc<V>)>>);
+
+impl<K, V> CompiledCache<K, V> {
+    const fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    fn get<Q>(&self, source: &Q, compile: impl FnOnce(&Q) -> V) -> Arc<V>
+    where
+        Q: ToOwned<Owned = K> + PartialEq + ?Sized,
+        K: Borrow<Q>,
+    {
+        let mut cache = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached, compiled)) = cache.as_ref() {
+            if cached.borrow() == source {
+                return compiled.clone();
+            }
+        }
+        let compiled = Arc::new(compile(source));
+        *cache = Some((source.to_owned(), compiled.clone()));
+        compiled
+    }
+}
+
+/// Spoken program names, matched as whole words, and what they're written as
+type CommandPatterns = Vec<(Regex, String)>;
+
+fn command_patterns(commands: impl IntoIterator<Item = (impl AsRef<str>, impl AsRef<str>)>) -> CommandPatterns {
+    commands
+        .into_iter()
+        .map(|(phrase, command)| {
+            let pattern = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(phrase.as_ref()))).unwrap();
+            (pattern, command.as_ref().to_string())
+        })
+        .collect()
+}
+
+/// A single command line: spoken program names, flags and operators become
+/// the real thing, "quote ... quote" becomes one quoted argument and the
+/// sentence case speech-to-text adds is undone
+pub fn dictate_command(text: &str, shell: &ShellConfig) -> String {
+    static BUILTIN: OnceLock<CommandPatterns> = OnceLock::new();
+    static CONFIGURED: CompiledCache<BTreeMap<String, String>, CommandPatterns> = CompiledCache::new();
+    let builtin = BUILTIN.get_or_init(|| command_patterns(SHELL_COMMANDS.iter().copied()));
+    let configured = CONFIGURED.get(&shell.commands, |commands| command_patterns(commands));
+
+    let mut text = remove_fillers(&plain_characters(text));
+    for (pattern, command) in configured.iter().chain(builtin) {
+        text = pattern.replace_all(&text, regex::NoExpand(command)).to_string();
+    }
+
+    let spoken: Vec<&str> = text.split_whitespace().collect();
+    let words: Vec<String> = spoken.iter().map(|w| bare(w)).collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut start = 0;
+    let mut index = 0;
+    while index <= words.len() {
+        let opener = quote_at(QUOTE_OPENERS, &words, index, |_| true);
+        if opener.is_none() && index < words.len() {
+            index += 1;
+            continue;
+        }
+        tokens.extend(tokenize(&spoken[start..index].join(" "), SHELL_SYMBOLS, false));
+        let Some((quote, len)) = opener else {
+            break;
+        };
+
+        // Everything up to the closing quote, or the end, is taken as said
+        let content_start = index + len;
+        let mut content_end = content_start;
+        let mut closer_len = 0;
+        while content_end < words.len() {
+            if let Some((_, len)) = quote_at(QUOTE_CLOSERS, &words, content_end, |q| q == quote) {
+                closer_len = len;
+                break;
+            }
+            content_end += 1;
+        }
+        let content = spoken[content_start..content_end].join(" ");
+        tokens.push(Token::word(quoted(content.trim_matches([',', ';']), quote)));
+        index = content_end + closer_len;
+        start = index;
+    }
+
+    if let Some(first) = tokens.first_mut() {
+        let rest = &first.text[first.text.chars().next().map(char::len_utf8).unwrap_or(0)..];
+        if first.is_word && !rest.chars().any(char::is_uppercase) {
+            first.text = first.text.to_lowercase();
+        }
+    }
+
+    join(&tokens)
+}
+
+/// A kind of command that's shown for confirmation rather than typed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DenyRule {
+    /// Regex searched for anywhere in the command line
+    pub pattern: String,
+    /// Why the command needs a second look, shown when asking
+    pub description: String,
+}
+
+/// Compile the rule's pattern, for validating it before it's saved
+pub fn check_deny_rule(rule: &DenyRule) -> Result<(), String> {
+    Regex::new(&rule.pattern).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// The first rule `command` matches, if it looks destructive. Rules that
+/// don't compile never match; validation reports them on save.
+pub fn dangerous_command<'a>(command: &str, deny_list: &'a [DenyRule]) -> Option<&'a DenyRule> {
+    static COMPILED: CompiledCache<Vec<DenyRule>, Vec<Option<Regex>>> = CompiledCache::new();
+    let patterns = COMPILED.get(deny_list, |rules| {
+        rules.iter().map(|rule| Regex::new(&rule.pattern).ok()).collect()
+    });
+    deny_list
+        .iter()
+        .zip(patterns.iter())
+        .find(|(_, pattern)| pattern.as_ref().is_some_and(|pattern| pattern.is_match(command)))
+        .map(|(rule, _)| rule)
+}
+
+/// The deny-list new configs start with
+pub fn default_deny_list() -> Vec<DenyRule> {
+    let rule = |pattern: &str, description: &str| DenyRule {
+        pattern: pattern.to_string(),
+        description: description.to_string(),
+    };
+    vec![
+        rule(
+            r"\brm\s+(?:[^;&|]*\s)?(?:-[a-zA-Z]*[rRf][a-zA-Z]*|--recursive|--force)\b",
+            "Deletes files recursively or without asking",
+        ),
+        rule(
+            r"(?:^|[;&|]\s*|\bsudo\s+)dd\s",
+            "Copies raw data and can overwrite a disk",
+        ),
+        rule(
+            r"\bgit\s+push\b[^;&|]*\s(?:--force(?:-with-lease)?\b|-[a-zA-Z]*f\b|\+\S)",
+            "Force-pushes, rewriting the remote branch",
+        ),
+        rule(
+            r"\bgit\s+push\b[^;&|]*\s(?:--delete\b|-d\b|:\S)",
+            "Deletes a branch on the remote",
+        ),
+        rule(r"\bgit\s+reset\s+[^;&|]*--hard\b", "Discards uncommitted changes"),
+        rule(r"\bgit\s+clean\s+[^;&|]*-[a-zA-Z]*f", "Deletes untracked files"),
+        rule(r"\bfind\s[^;&|]*\s-delete\b", "Deletes every file find matches"),
+        rule(r"\bmkfs(?:\.\w+)?\s", "Formats a filesystem"),
+        rule(r">\s*/dev/(?:sd|hd|nvme|mmcblk|disk)", "Writes straight to a disk"),
+        rule(r":\(\)\s*\{", "Looks like a fork bomb"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell() -> ShellConfig {
+        ShellConfig::default()
+    }
+
+    #[test]
+    fn dictates_commands() {
+        let cases = [
+            ("git commit dash m quote fix bug quote", r#"git commit -m "fix bug""#),
+            (
+                "Git commit dash m quote, fix the $HOME bug, quote.",
+                r#"git commit -m "fix the \$HOME bug""#,
+            ),
+            ("echo single quote it's done single quote", r#"echo 'it'\''s done'"#),
+            ("grep dash r quote to do end quote src", r#"grep -r "to do" src"#),
+            (
+                "git log dash dash author equals quote Sam Lee quote",
+                r#"git log --author="Sam Lee""#,
+            ),
+            ("List files dash la pipe grep star dot rs", "ls -la | grep *.rs"),
+            ("change directory to tilde slash projects", "cd ~/projects"),
+            ("echo quote unfinished", r#"echo "unfinished""#),
+            ("echo \u{201c}hi\u{201d}\nrm", "echo \"hi\" rm"),
+        ];
+        for (spoken, expected) in cases {
+            assert_eq!(dictate_command(spoken, &shell()), expected, "{:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn uses_configured_commands() {
+        let shell = ShellConfig {
+            commands: BTreeMap::from([("kube control".to_string(), "kubectl".to_string())]),
+            ..shell()
+        };
+        assert_eq!(dictate_command("kube control get pods", &shell), "kubectl get pods");
+    }
+
+    #[test]
+    fn flags_destructive_commands() {
+        let deny_list = default_deny_list();
+        for command in [
+            "rm -rf /",
+            "rm -r build",
+            "sudo rm notes.txt --force",
+            "dd if=/dev/zero of=/dev/sda",
+            "cd /tmp && sudo dd if=image.iso of=/dev/sdb",
+            "git push --force",
+            "git push -f origin main",
+            "git push origin +main",
+            "git push --force-with-lease",
+            "git push origin :main",
+            "git push origin --delete feature",
+            "git push -d origin feature",
+            "find / -delete",
+            "find . -name '*.log' -delete",
+            "git reset --hard HEAD~1",
+            "git clean -fdx",
+            "mkfs.ext4 /dev/sdb1",
+            "cat image > /dev/sda",
+        ] {
+            assert!(dangerous_command(command, &deny_list).is_some(), "{:?}", command);
+        }
+        for command in [
+            "rm notes.txt",
+            "git add dd.txt",
+            "git push origin main",
+            "git push origin main:main",
+            "find . -name '*.log'",
+            "git reset HEAD~1",
+            "ls -rf",
+        ] {
+            assert!(dangerous_command(command, &deny_list).is_none(), "{:?}", command);
+        }
+    }
+
+    #[test]
+    fn uses_configured_deny_list() {
+        let deny_list = vec![DenyRule {
+            pattern: r"\bkubectl\s+delete\b".to_string(),
+            description: "Deletes cluster resources".to_string(),
+        }];
+        assert!(dangerous_command("kubectl delete pod web", &deny_list).is_some());
+        assert!(dangerous_command("rm -rf /", &deny_list).is_none());
+        assert!(check_deny_rule(&DenyRule {
+            pattern: "(".to_string(),
+            description: String::new(),
+        })
+        .is_err());
+    }
+}
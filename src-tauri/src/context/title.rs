@@ -0,0 +1,753 @@
+use crate::mcp::types::{FileContext, ProjectContext};
+use crate::types::ActiveApplication;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What a window title says about the document in front of the user
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitleContext {
+    /// Name of the parser that understood the title
+    pub parser: String,
+    pub file: Option<FileContext>,
+    pub project: Option<ProjectContext>,
+    /// Site name a browser title ends with, e.g. "GitHub"
+    pub site: Option<String>,
+    pub domain: Option<String>,
+}
+
+/// A user-defined title format: a regex whose named groups `file`, `project`,
+/// `root`, `site` and `domain` say what each part of the title is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TitlePattern {
+    pub name: String,
+    /// Only apply to applications whose name contains this
+    #[serde(default)]
+    pub app_name: Option<String>,
+    pub pattern: String,
+}
+
+const PATTERN_GROUPS: &[&str] = &["file", "project", "root", "site", "domain"];
+
+/// Compile the pattern, for validating it before it's saved
+pub fn check_title_pattern(pattern: &TitlePattern) -> Result<(), String> {
+    let regex = Regex::new(&pattern.pattern).map_err(|e| e.to_string())?;
+    if !regex
+        .capture_names()
+        .flatten()
+        .any(|group| PATTERN_GROUPS.contains(&group))
+    {
+        return Err(format!("must capture at least one of {}", PATTERN_GROUPS.join(", ")));
+    }
+    Ok(())
+}
+
+/// Extracts context from the titles of one family of applications
+pub trait TitleParser: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Whether titles of `app` are in the format this parser reads
+    fn applies_to(&self, app: &ActiveApplication) -> bool;
+
+    fn parse(&self, title: &str) -> Option<TitleContext>;
+}
+
+/// Title parsers in the order they're tried. Parsers registered later are
+/// tried before the built-in ones, so they can override them.
+pub struct TitleParserRegistry {
+    parsers: Vec<Box<dyn TitleParser>>,
+}
+
+impl Default for TitleParserRegistry {
+    fn default() -> Self {
+        Self {
+            // Xcode before VS Code, whose "code" would match it
+            parsers: vec![
+                Box::new(Xcode),
+                Box::new(VsCode),
+                Box::new(JetBrains),
+                Box::new(SublimeText),
+                Box::new(Vim),
+                Box::new(Browser),
+            ],
+        }
+    }
+}
+
+impl TitleParserRegistry {
+    /// The built-in parsers with the user's patterns in front. Patterns that
+    /// don't compile are skipped; validation reports them on save.
+    pub fn with_patterns(patterns: &[TitlePattern]) -> Self {
+        let mut registry = Self::default();
+        for pattern in patterns.iter().rev() {
+            if let Ok(regex) = Regex::new(&pattern.pattern) {
+                registry.register(Box::new(PatternParser {
+                    name: pattern.name.clone(),
+                    app_name: pattern.app_name.as_ref().map(|name| name.to_lowercase()),
+                    regex,
+                }));
+            }
+        }
+        registry
+    }
+
+    pub fn register(&mut self, parser: Box<dyn TitleParser>) {
+        self.parsers.insert(0, parser);
+    }
+
+    /// Context from the title of `app`, from the first parser that applies
+    /// and understands it
+    pub fn parse(&self, app: &ActiveApplication) -> Option<TitleContext> {
+        let title = app.title.trim();
+        if title.is_empty() {
+            return None;
+        }
+        self.parsers
+            .iter()
+            .filter(|parser| parser.applies_to(app))
+            .find_map(|parser| parser.parse(title))
+    }
+}
+
+/// Whether `app` is one of `names`, by its executable exactly or by whole
+/// words of its name, so "arc" doesn't take "Archive Manager" nor "code"
+/// "Decoder"
+fn is_app(app: &ActiveApplication, names: &[&str]) -> bool {
+    let executable = app.executable.to_lowercase();
+    let executable = executable.trim_end_matches(".exe");
+    let app_words = words(&app.name);
+    names.iter().any(|n| {
+        let name_words = words(n);
+        executable == *n
+            || app_words
+                .windows(name_words.len())
+                .any(|window| window == name_words.as_slice())
+    })
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Title parts split on `separator`, without empty ones
+fn parts<'a>(title: &'a str, separator: &str) -> Vec<&'a str> {
+    title
+        .split(separator)
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Language id, as editors name them, for a file name
+pub fn language_for_file(name: &str) -> Option<String> {
+    let file_name = Path::new(name).file_name()?.to_str()?.to_lowercase();
+    let by_name = match file_name.as_str() {
+        "dockerfile" => Some("dockerfile"),
+        "makefile" | "gnumakefile" => Some("makefile"),
+        "cargo.toml" | "pyproject.toml" => Some("toml"),
+        _ => None,
+    };
+    if by_name.is_some() {
+        return by_name.map(str::to_string);
+    }
+
+    let language = match Path::new(&file_name).extension()?.to_str()? {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "md" | "markdown" => "markdown",
+        "sh" | "bash" | "zsh" => "shellscript",
+        "sql" => "sql",
+        "lua" => "lua",
+        "dart" => "dart",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "ex" | "exs" => "elixir",
+        "hs" => "haskell",
+        "scala" => "scala",
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+fn file_context(path: &str) -> FileContext {
+    let name = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    FileContext {
+        path: path.to_string(),
+        language: language_for_file(&name),
+        name,
+        cursor_position: None,
+        selected_text: None,
+    }
+}
+
+/// Project context from a title; the root path is empty when the title
+/// doesn't show it
+fn project_context(name: &str, root_path: Option<&str>, file: Option<&FileContext>) -> ProjectContext {
+    ProjectContext {
+        name: name.to_string(),
+        root_path: root_path.unwrap_or_default().to_string(),
+        language: file.and_then(|f| f.language.clone()),
+        framework: None,
+    }
+}
+
+/// Whether a title part names a file rather than, say, "Welcome" or "Settings"
+fn looks_like_file(part: &str) -> bool {
+    language_for_file(part).is_some() || Path::new(part).extension().is_some_and(|ext| ext.len() <= 5)
+}
+
+/// Strip a trailing `[...]` annotation, returning it separately
+fn split_bracket(part: &str) -> (&str, Option<&str>) {
+    match part.strip_suffix(']').and_then(|rest| rest.rsplit_once(" [")) {
+        Some((before, inside)) => (before.trim(), Some(inside)),
+        None => (part, None),
+    }
+}
+
+struct PatternParser {
+    name: String,
+    app_name: Option<String>,
+    regex: Regex,
+}
+
+impl TitleParser for PatternParser {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        self.app_name
+            .as_ref()
+            .map(|name| app.name.to_lowercase().contains(name))
+            .unwrap_or(true)
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        let captures = self.regex.captures(title)?;
+        let group = |name: &str| {
+            captures
+                .name(name)
+                .map(|m| m.as_str().trim())
+                .filter(|value| !value.is_empty())
+        };
+
+        let file = group("file").map(file_context);
+        Some(TitleContext {
+            parser: self.name.clone(),
+            project: group("project").map(|name| project_context(name, group("root"), file.as_ref())),
+            file,
+            site: group("site").map(str::to_string),
+            domain: group("domain").map(|domain| domain.to_lowercase()),
+        })
+    }
+}
+
+/// VS Code and its forks: "● main.rs — whispo — Visual Studio Code", with
+/// " - " separators on Windows
+struct VsCode;
+
+impl TitleParser for VsCode {
+    fn name(&self) -> &str {
+        "vscode"
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        is_app(
+            app,
+            &[
+                "visual studio code",
+                "code",
+                "code-oss",
+                "codium",
+                "vscodium",
+                "cursor",
+                "windsurf",
+            ],
+        )
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        let title = title.trim_start_matches('●').trim();
+        let separator = if title.contains(" — ") { " — " } else { " - " };
+        let mut parts = parts(title, separator);
+
+        let app_names = ["Visual Studio Code", "Code - OSS", "VSCodium", "Cursor", "Windsurf"];
+        if parts
+            .last()
+            .is_some_and(|last| app_names.iter().any(|n| last.ends_with(n)))
+        {
+            parts.pop();
+        }
+
+        // A remote window adds e.g. "[WSL: Ubuntu]" to the folder name
+        let (file, project) = match parts.as_slice() {
+            [] => return None,
+            [only] if looks_like_file(only) => (Some(file_context(only)), None),
+            [only] => (None, Some(split_bracket(only).0)),
+            [file, project, ..] if looks_like_file(file) => (Some(file_context(file)), Some(split_bracket(project).0)),
+            [_, project, ..] => (None, Some(split_bracket(project).0)),
+        };
+
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            project: project.map(|name| project_context(name, None, file.as_ref())),
+            file,
+            ..Default::default()
+        })
+    }
+}
+
+/// IntelliJ-based IDEs: "whispo – main.rs" in recent versions, "whispo
+/// [~/code/whispo] – …/src/main.rs [whispo] - IntelliJ IDEA" in older ones
+struct JetBrains;
+
+impl TitleParser for JetBrains {
+    fn name(&self) -> &str {
+        "jetbrains"
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        is_app(
+            app,
+            &[
+                "intellij",
+                "idea",
+                "pycharm",
+                "webstorm",
+                "goland",
+                "clion",
+                "rustrover",
+                "phpstorm",
+                "rubymine",
+                "rider",
+                "datagrip",
+                "android studio",
+                "studio64",
+            ],
+        )
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        if title.starts_with("Welcome to ") {
+            return None;
+        }
+        let parts = parts(title, " – ");
+        let (project, root_path) = split_bracket(parts.first()?);
+        let file = parts.get(1).map(|part| {
+            // Drop the IDE name older versions append, then the module name
+            let part = part.rsplit_once(" - ").map(|(before, _)| before).unwrap_or(part);
+            let path = split_bracket(part).0.trim_start_matches('…').trim_start_matches("...");
+            let mut file = file_context(path.trim_start_matches('/'));
+            if let Some(root) = root_path {
+                file.path = format!("{}/{}", root.trim_end_matches('/'), file.path);
+            }
+            file
+        });
+
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            project: Some(project_context(project, root_path, file.as_ref())),
+            file,
+            ..Default::default()
+        })
+    }
+}
+
+/// Sublime Text: "~/code/whispo/src/main.rs (whispo) - Sublime Text"
+struct SublimeText;
+
+impl TitleParser for SublimeText {
+    fn name(&self) -> &str {
+        "sublime-text"
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        is_app(app, &["sublime text", "sublime_text"])
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        let title = title.strip_suffix(" - Sublime Text").unwrap_or(title);
+        let title = title.trim_end_matches(" •").trim_start_matches("• ");
+        let (path, project) = match title.strip_suffix(')').and_then(|rest| rest.rsplit_once(" (")) {
+            Some((path, project)) => (path.trim(), Some(project)),
+            None => (title.trim(), None),
+        };
+        if path.is_empty() || path == "untitled" {
+            return None;
+        }
+
+        let file = file_context(path);
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            project: project.map(|name| project_context(name, None, Some(&file))),
+            file: Some(file),
+            ..Default::default()
+        })
+    }
+}
+
+/// Vim and Neovim set the terminal title to "main.rs + (~/code/whispo/src) - NVIM",
+/// so this applies to any application
+struct Vim;
+
+impl TitleParser for Vim {
+    fn name(&self) -> &str {
+        "vim"
+    }
+
+    fn applies_to(&self, _app: &ActiveApplication) -> bool {
+        true
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        let pattern = PATTERN.get_or_init(|| {
+            Regex::new(r"^(?P<file>\S.*?)(?: [+=-]+)? \((?P<dir>[^)]*)\) - (?:N?VIM|VIM\d*)$").unwrap()
+        });
+        let captures = pattern.captures(title)?;
+        let name = &captures["file"];
+        let dir = &captures["dir"];
+
+        let mut file = file_context(name);
+        file.path = format!("{}/{}", dir.trim_end_matches('/'), name);
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            file: Some(file),
+            ..Default::default()
+        })
+    }
+}
+
+/// Xcode: "whispo — AppDelegate.swift"
+struct Xcode;
+
+impl TitleParser for Xcode {
+    fn name(&self) -> &str {
+        "xcode"
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        is_app(app, &["xcode"])
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        let parts = parts(title, " — ");
+        let project = *parts.first()?;
+        let file = parts
+            .get(1)
+            .filter(|part| looks_like_file(part))
+            .map(|part| file_context(part));
+
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            project: Some(project_context(project, None, file.as_ref())),
+            file,
+            ..Default::default()
+        })
+    }
+}
+
+/// Browsers: "Pull requests · whispo/whispo - GitHub - Google Chrome". The
+/// site is the last part once the browser name is gone; the domain comes
+/// from a host name in the title, or from the site if it's a well-known one.
+struct Browser;
+
+const BROWSER_NAMES: &[&str] = &[
+    "Google Chrome",
+    "Chromium",
+    "Mozilla Firefox",
+    "Firefox",
+    "Microsoft Edge",
+    "Microsoft\u{200b} Edge",
+    "Brave",
+    "Vivaldi",
+    "Opera",
+    "Safari",
+    "Arc",
+];
+
+const KNOWN_SITES: &[(&str, &str)] = &[
+    ("GitHub", "github.com"),
+    ("GitLab", "gitlab.com"),
+    ("Stack Overflow", "stackoverflow.com"),
+    ("Gmail", "mail.google.com"),
+    ("Google Docs", "docs.google.com"),
+    ("Google Sheets", "docs.google.com"),
+    ("Google Search", "google.com"),
+    ("YouTube", "youtube.com"),
+    ("Notion", "notion.so"),
+    ("Slack", "app.slack.com"),
+    ("Jira", "atlassian.net"),
+    ("Confluence", "atlassian.net"),
+    ("Linear", "linear.app"),
+    ("Outlook", "outlook.office.com"),
+    ("Wikipedia", "wikipedia.org"),
+    ("X", "x.com"),
+    ("Reddit", "reddit.com"),
+];
+
+impl TitleParser for Browser {
+    fn name(&self) -> &str {
+        "browser"
+    }
+
+    fn applies_to(&self, app: &ActiveApplication) -> bool {
+        is_app(
+            app,
+            &[
+                "chrome", "chromium", "firefox", "safari", "msedge", "edge", "brave", "vivaldi", "opera", "arc",
+            ],
+        )
+    }
+
+    fn parse(&self, title: &str) -> Option<TitleContext> {
+        let mut title = title;
+        for browser in BROWSER_NAMES {
+            if let Some(rest) = [" - ", " — ", " – "]
+                .iter()
+                .find_map(|separator| title.strip_suffix(&format!("{}{}", separator, browser)))
+            {
+                title = rest;
+                break;
+            }
+        }
+        let title = title.trim();
+        if title.is_empty() {
+            return None;
+        }
+
+        let site = [" - ", " | ", " · ", " — ", " – "]
+            .iter()
+            .filter_map(|separator| title.rsplit_once(separator))
+            .map(|(_, site)| site.trim())
+            .min_by_key(|site| site.len())
+            .filter(|site| !site.is_empty() && site.len() < title.len())
+            .map(str::to_string);
+
+        static HOST: OnceLock<Regex> = OnceLock::new();
+        let host = HOST.get_or_init(|| {
+            Regex::new(r"(?i)\b(?:[a-z0-9-]+\.)+(?:com|org|net|io|dev|app|so|co|ai|edu|gov|[a-z]{2})\b").unwrap()
+        });
+        let domain = host
+            .find(title)
+            .map(|m| m.as_str().to_lowercase())
+            .filter(|host| language_for_file(host).is_none())
+            .or_else(|| {
+                let site = site.as_deref()?;
+                KNOWN_SITES
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(site))
+                    .map(|(_, domain)| domain.to_string())
+            });
+
+        Some(TitleContext {
+            parser: self.name().to_string(),
+            site,
+            domain,
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(name: &str, executable: &str, title: &str) -> ActiveApplication {
+        ActiveApplication {
+            name: name.to_string(),
+            executable: executable.to_string(),
+            executable_path: None,
+            title: title.to_string(),
+            last_updated: 0,
+        }
+    }
+
+    fn parse(name: &str, executable: &str, title: &str) -> TitleContext {
+        TitleParserRegistry::default()
+            .parse(&app(name, executable, title))
+            .unwrap_or_else(|| panic!("no context from {:?}", title))
+    }
+
+    fn file(context: &TitleContext) -> (&str, &str, Option<&str>) {
+        let file = context.file.as_ref().expect("a file");
+        (file.path.as_str(), file.name.as_str(), file.language.as_deref())
+    }
+
+    fn project(context: &TitleContext) -> (&str, &str) {
+        let project = context.project.as_ref().expect("a project");
+        (project.name.as_str(), project.root_path.as_str())
+    }
+
+    #[test]
+    fn matches_whole_app_names_and_exact_executables() {
+        let browsers = &["chrome", "edge", "opera", "arc"];
+        assert!(is_app(&app("Microsoft Edge", "msedge.exe", ""), browsers));
+        assert!(is_app(&app("Arc", "Arc", ""), browsers));
+        assert!(is_app(&app("Opera GX", "opera.exe", ""), browsers));
+        assert!(is_app(&app("chromium", "chrome", ""), browsers));
+        assert!(!is_app(&app("Archive Manager", "file-roller", ""), browsers));
+        assert!(!is_app(&app("Search", "searchhost.exe", ""), browsers));
+        assert!(!is_app(&app("Knowledge Base", "knowledge", ""), browsers));
+
+        let editors = &["visual studio code", "code"];
+        assert!(is_app(&app("Visual Studio Code", "Electron", ""), editors));
+        assert!(is_app(&app("Code", "code", ""), editors));
+        assert!(!is_app(&app("Decoder", "decoder", ""), editors));
+        assert!(!is_app(&app("Visual Studio", "devenv.exe", ""), editors));
+    }
+
+    #[test]
+    fn vscode_titles() {
+        let context = parse("Code", "code", "● main.rs — whispo — Visual Studio Code");
+        assert_eq!(context.parser, "vscode");
+        assert_eq!(file(&context), ("main.rs", "main.rs", Some("rust")));
+        assert_eq!(project(&context), ("whispo", ""));
+
+        let remote = parse(
+            "Visual Studio Code",
+            "Code.exe",
+            "app.py - api [WSL: Ubuntu] - Visual Studio Code",
+        );
+        assert_eq!(file(&remote), ("app.py", "app.py", Some("python")));
+        assert_eq!(project(&remote), ("api", ""));
+
+        let welcome = parse("Cursor", "cursor", "Welcome - whispo - Cursor");
+        assert!(welcome.file.is_none());
+        assert_eq!(project(&welcome), ("whispo", ""));
+    }
+
+    #[test]
+    fn jetbrains_titles() {
+        let context = parse("RustRover", "rustrover", "whispo – main.rs");
+        assert_eq!(context.parser, "jetbrains");
+        assert_eq!(file(&context), ("main.rs", "main.rs", Some("rust")));
+        assert_eq!(project(&context), ("whispo", ""));
+
+        let older = parse(
+            "IntelliJ IDEA",
+            "idea64.exe",
+            "whispo [~/code/whispo] – …/src/Main.kt [whispo] - IntelliJ IDEA",
+        );
+        assert_eq!(file(&older), ("~/code/whispo/src/Main.kt", "Main.kt", Some("kotlin")));
+        assert_eq!(project(&older), ("whispo", "~/code/whispo"));
+
+        assert!(TitleParserRegistry::default()
+            .parse(&app("PyCharm", "pycharm", "Welcome to PyCharm"))
+            .is_none());
+    }
+
+    #[test]
+    fn sublime_text_titles() {
+        let context = parse(
+            "Sublime Text",
+            "sublime_text",
+            "~/code/whispo/src/main.rs (whispo) - Sublime Text",
+        );
+        assert_eq!(context.parser, "sublime-text");
+        assert_eq!(file(&context), ("~/code/whispo/src/main.rs", "main.rs", Some("rust")));
+        assert_eq!(project(&context), ("whispo", ""));
+
+        assert!(TitleParserRegistry::default()
+            .parse(&app("Sublime Text", "sublime_text", "untitled - Sublime Text"))
+            .is_none());
+    }
+
+    #[test]
+    fn vim_titles_in_any_terminal() {
+        let context = parse("kitty", "kitty", "main.rs + (~/code/whispo/src) - NVIM");
+        assert_eq!(context.parser, "vim");
+        assert_eq!(file(&context), ("~/code/whispo/src/main.rs", "main.rs", Some("rust")));
+
+        let vim = parse("Terminal", "Terminal", "notes.md (~/docs/) - VIM");
+        assert_eq!(file(&vim), ("~/docs/notes.md", "notes.md", Some("markdown")));
+    }
+
+    #[test]
+    fn xcode_titles() {
+        let context = parse("Xcode", "Xcode", "whispo — AppDelegate.swift");
+        assert_eq!(context.parser, "xcode");
+        assert_eq!(
+            file(&context),
+            ("AppDelegate.swift", "AppDelegate.swift", Some("swift"))
+        );
+        assert_eq!(project(&context), ("whispo", ""));
+
+        let no_file = parse("Xcode", "Xcode", "whispo — Build Succeeded");
+        assert!(no_file.file.is_none());
+    }
+
+    #[test]
+    fn browser_titles() {
+        let github = parse(
+            "Google Chrome",
+            "google-chrome",
+            "Pull requests · whispo/whispo - GitHub - Google Chrome",
+        );
+        assert_eq!(github.parser, "browser");
+        assert_eq!(github.site.as_deref(), Some("GitHub"));
+        assert_eq!(github.domain.as_deref(), Some("github.com"));
+
+        let host = parse("Firefox", "firefox", "Pricing | Acme — acme.io — Mozilla Firefox");
+        assert_eq!(host.domain.as_deref(), Some("acme.io"));
+
+        // An archive manager isn't a browser, so its title says nothing
+        assert!(TitleParserRegistry::default()
+            .parse(&app("Archive Manager", "file-roller", "backup.zip - Arc"))
+            .is_none());
+    }
+
+    #[test]
+    fn user_patterns_come_first() {
+        let patterns = [TitlePattern {
+            name: "helix".to_string(),
+            app_name: Some("Alacritty".to_string()),
+            pattern: r"^hx (?P<file>\S+) in (?P<project>\S+)$".to_string(),
+        }];
+        let registry = TitleParserRegistry::with_patterns(&patterns);
+
+        let context = registry
+            .parse(&app("Alacritty", "alacritty", "hx lib.rs in whispo"))
+            .unwrap();
+        assert_eq!(context.parser, "helix");
+        assert_eq!(file(&context), ("lib.rs", "lib.rs", Some("rust")));
+        assert_eq!(project(&context), ("whispo", ""));
+
+        assert!(registry.parse(&app("kitty", "kitty", "hx lib.rs in whispo")).is_none());
+        assert!(check_title_pattern(&patterns[0]).is_ok());
+        let no_groups = TitlePattern {
+            pattern: "^hx .*$".to_string(),
+            ..patterns[0].clone()
+        };
+        assert!(check_title_pattern(&no_groups).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+use crate::config::{quarantine, write_atomic, Config, FieldError};
+use anyhow::Result;
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const LEDGER_FILE: &str = "costs.json";
+
+/// Price of one provider/model pair in USD
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    pub per_minute: Option<f64>,
+}
+
+/// Built-in prices, keyed by "provider/model". Users override them through
+/// `costTracking.prices` in the config.
+pub fn default_prices() -> HashMap<String, ModelPrice> {
+    let per_minute = |price: f64| ModelPrice {
+        per_minute: Some(price),
+    };
+
+    HashMap::from([
+        ("openai/whisper-1".to_string(), per_minute(0.006)),
+        ("openai/gpt-4o-mini-transcribe".to_string(), per_minute(0.003)),
+        ("groq/whisper-large-v3".to_string(), per_minute(0.111 / 60.0)),
+        ("groq/whisper-large-v3-turbo".to_string(), per_minute(0.04 / 60.0)),
+    ])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HardCapAction {
+    Block,
+    Fallback,
+}
+
+/// Settings from the `costTracking` config section
+#[derive(Debug, Clone)]
+pub struct CostPolicy {
+    pub enabled: bool,
+    pub prices: HashMap<String, ModelPrice>,
+    pub daily_soft_cap: Option<f64>,
+    pub daily_hard_cap: Option<f64>,
+    pub monthly_soft_cap: Option<f64>,
+    pub monthly_hard_cap: Option<f64>,
+    pub hard_cap_action: HardCapAction,
+    pub fallback_provider_id: Option<String>,
+}
+
+impl CostPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        let section = &config.cost_tracking;
+
+        let mut prices = default_prices();
+        for (key, price) in &section.prices {
+            prices.insert(key.clone(), price.clone());
+        }
+
+        Self {
+            enabled: section.enabled,
+            prices,
+            daily_soft_cap: section.daily_soft_cap,
+            daily_hard_cap: section.daily_hard_cap,
+            monthly_soft_cap: section.monthly_soft_cap,
+            monthly_hard_cap: section.monthly_hard_cap,
+            hard_cap_action: section.hard_cap_action,
+            fallback_provider_id: section.fallback_provider_id.clone().filter(|s| !s.is_empty()),
+        }
+    }
+
+    pub fn price_for(&self, provider_id: &str, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(&format!("{}/{}", provider_id, model))
+    }
+
+    /// Cost of transcribing `duration_ms` of audio, if the model has a per-minute price
+    pub fn transcription_cost(&self, provider_id: &str, model: &str, duration_ms: f64) -> Option<f64> {
+        self.price_for(provider_id, model)
+            .and_then(|p| p.per_minute)
+            .map(|per_minute| per_minute * duration_ms / 60_000.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CostKind {
+    Transcription,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEntry {
+    pub timestamp: i64,
+    pub recording_id: Option<String>,
+    pub provider_id: String,
+    pub model: String,
+    pub kind: CostKind,
+    pub cost_usd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CapDecision {
+    Allow,
+    Warn { message: String },
+    Block { message: String },
+    Fallback { provider_id: String, message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostSummary {
+    pub today_usd: f64,
+    pub month_usd: f64,
+    /// Month-to-date spend per provider
+    pub month_by_provider: HashMap<String, f64>,
+    pub daily_soft_cap: Option<f64>,
+    pub daily_hard_cap: Option<f64>,
+    pub monthly_soft_cap: Option<f64>,
+    pub monthly_hard_cap: Option<f64>,
+}
+
+/// Spend ledger kept in `costs.json`, independent of history so that
+/// retention sweeps don't erase spending data
+pub struct CostLedger {
+    path: PathBuf,
+    entries: Mutex<Vec<CostEntry>>,
+    load_error: Option<FieldError>,
+}
+
+impl CostLedger {
+    pub fn new(app_data_dir: &Path) -> Result<Self> {
+        let path = app_data_dir.join(LEDGER_FILE);
+        let mut load_error = None;
+        let entries = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            match serde_json::from_str(&content) {
+                Ok(entries) => entries,
+                Err(_) => {
+                    load_error = Some(quarantine(&path)?);
+                    vec![]
+                }
+            }
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+            load_error,
+        })
+    }
+
+    /// Set when costs.json couldn't be parsed and was moved aside
+    pub fn load_error(&self) -> Option<FieldError> {
+        self.load_error.clone()
+    }
+
+    pub fn record(&self, entry: CostEntry) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(entry);
+        write_atomic(&self.path, serde_json::to_string(&*entries)?.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Vec<CostEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn summary(&self, policy: &CostPolicy, now: i64) -> CostSummary {
+        let entries = self.entries.lock().unwrap();
+        let (day_start, month_start) = period_starts(now);
+
+        let mut summary = CostSummary {
+            today_usd: 0.0,
+            month_usd: 0.0,
+            month_by_provider: HashMap::new(),
+            daily_soft_cap: policy.daily_soft_cap,
+            daily_hard_cap: policy.daily_hard_cap,
+            monthly_soft_cap: policy.monthly_soft_cap,
+            monthly_hard_cap: policy.monthly_hard_cap,
+        };
+
+        for entry in entries.iter().filter(|e| e.timestamp >= month_start) {
+            summary.month_usd += entry.cost_usd;
+            *summary.month_by_provider.entry(entry.provider_id.clone()).or_insert(0.0) += entry.cost_usd;
+            if entry.timestamp >= day_start {
+                summary.today_usd += entry.cost_usd;
+            }
+        }
+
+        summary
+    }
+
+    /// Decide whether a call to `provider_id` may go ahead given current spend
+    pub fn check_caps(&self, policy: &CostPolicy, provider_id: &str, now: i64) -> CapDecision {
+        if !policy.enabled {
+            return CapDecision::Allow;
+        }
+
+        let summary = self.summary(policy, now);
+        let exceeded = |spent: f64, cap: Option<f64>| cap.map(|c| spent >= c).unwrap_or(false);
+
+        let hard = if exceeded(summary.today_usd, policy.daily_hard_cap) {
+            Some(format!("Daily spending cap of ${:.2} reached", policy.daily_hard_cap.unwrap()))
+        } else if exceeded(summary.month_usd, policy.monthly_hard_cap) {
+            Some(format!("Monthly spending cap of ${:.2} reached", policy.monthly_hard_cap.unwrap()))
+        } else {
+            None
+        };
+
+        if let Some(message) = hard {
+            return match (&policy.hard_cap_action, &policy.fallback_provider_id) {
+                (HardCapAction::Fallback, Some(fallback)) if fallback != provider_id => CapDecision::Fallback {
+                    provider_id: fallback.clone(),
+                    message,
+                },
+                _ => CapDecision::Block { message },
+            };
+        }
+
+        if exceeded(summary.today_usd, policy.daily_soft_cap) {
+            return CapDecision::Warn {
+                message: format!("Daily spending of ${:.2} is over the soft cap", summary.today_usd),
+            };
+        }
+        if exceeded(summary.month_usd, policy.monthly_soft_cap) {
+            return CapDecision::Warn {
+                message: format!("Monthly spending of ${:.2} is over the soft cap", summary.month_usd),
+            };
+        }
+
+        CapDecision::Allow
+    }
+}
+
+/// Local midnight of today and of the first day of this month, in ms
+fn period_starts(now: i64) -> (i64, i64) {
+    let Some(now) = Local.timestamp_millis_opt(now).single() else {
+        return (now, now);
+    };
+    let date = now.date_naive();
+    let to_millis = |date: chrono::NaiveDate| {
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+            .map(|dt| dt.timestamp_millis())
+            .unwrap_or(0)
+    };
+
+    (to_millis(date), to_millis(date.with_day(1).unwrap_or(date)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn ledger(name: &str) -> CostLedger {
+        let dir = std::env::temp_dir().join(format!("whispo-costs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        CostLedger::new(&dir).unwrap()
+    }
+
+    fn policy(configure: impl FnOnce(&mut Config)) -> CostPolicy {
+        let mut config = Config::default();
+        config.cost_tracking.enabled = true;
+        configure(&mut config);
+        CostPolicy::from_config(&config)
+    }
+
+    fn spend(ledger: &CostLedger, timestamp: i64, cost_usd: f64) {
+        ledger
+            .record(CostEntry {
+                timestamp,
+                recording_id: None,
+                provider_id: "openai".to_string(),
+                model: "whisper-1".to_string(),
+                kind: CostKind::Transcription,
+                cost_usd,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn prices_transcriptions_per_minute() {
+        let policy = policy(|config| {
+            config.cost_tracking.prices.insert(
+                "groq/whisper-large-v3".to_string(),
+                ModelPrice { per_minute: Some(0.01) },
+            );
+        });
+
+        let cost = policy.transcription_cost("openai", "whisper-1", 90_000.0).unwrap();
+        assert!((cost - 0.009).abs() < 1e-9);
+        let overridden = policy.transcription_cost("groq", "whisper-large-v3", 60_000.0).unwrap();
+        assert!((overridden - 0.01).abs() < 1e-9);
+        assert_eq!(policy.transcription_cost("openai", "unknown-model", 60_000.0), None);
+    }
+
+    #[test]
+    fn soft_caps_warn_and_hard_caps_block() {
+        let now = Local::now().timestamp_millis();
+        let ledger = ledger("caps");
+        spend(&ledger, now, 1.5);
+
+        let disabled = policy(|config| {
+            config.cost_tracking.enabled = false;
+            config.cost_tracking.daily_hard_cap = Some(1.0);
+        });
+        assert_eq!(ledger.check_caps(&disabled, "openai", now), CapDecision::Allow);
+
+        let under = policy(|config| config.cost_tracking.daily_soft_cap = Some(2.0));
+        assert_eq!(ledger.check_caps(&under, "openai", now), CapDecision::Allow);
+
+        let soft = policy(|config| {
+            config.cost_tracking.daily_soft_cap = Some(1.0);
+            config.cost_tracking.daily_hard_cap = Some(2.0);
+        });
+        assert!(matches!(ledger.check_caps(&soft, "openai", now), CapDecision::Warn { .. }));
+
+        let monthly_soft = policy(|config| config.cost_tracking.monthly_soft_cap = Some(1.0));
+        assert!(matches!(ledger.check_caps(&monthly_soft, "openai", now), CapDecision::Warn { .. }));
+
+        let hard = policy(|config| {
+            config.cost_tracking.daily_soft_cap = Some(1.0);
+            config.cost_tracking.monthly_hard_cap = Some(1.5);
+        });
+        assert!(matches!(ledger.check_caps(&hard, "openai", now), CapDecision::Block { .. }));
+    }
+
+    #[test]
+    fn hard_caps_fall_back_to_another_provider() {
+        let now = Local::now().timestamp_millis();
+        let ledger = ledger("fallback");
+        spend(&ledger, now, 1.0);
+
+        let fallback = policy(|config| {
+            config.cost_tracking.daily_hard_cap = Some(1.0);
+            config.cost_tracking.hard_cap_action = HardCapAction::Fallback;
+        });
+        match ledger.check_caps(&fallback, "openai", now) {
+            CapDecision::Fallback { provider_id, .. } => assert_eq!(provider_id, "groq"),
+            decision => panic!("expected a fallback, got {:?}", decision),
+        }
+        // Already on the fallback provider, or without one, there's nowhere left to go
+        assert!(matches!(ledger.check_caps(&fallback, "groq", now), CapDecision::Block { .. }));
+        let no_fallback = policy(|config| {
+            config.cost_tracking.daily_hard_cap = Some(1.0);
+            config.cost_tracking.hard_cap_action = HardCapAction::Fallback;
+            config.cost_tracking.fallback_provider_id = Some(String::new());
+        });
+        assert!(matches!(ledger.check_caps(&no_fallback, "openai", now), CapDecision::Block { .. }));
+    }
+
+    #[test]
+    fn summarizes_today_and_this_month() {
+        let now = Local::now().timestamp_millis();
+        let (day_start, month_start) = period_starts(now);
+        let ledger = ledger("summary");
+        spend(&ledger, now, 1.0);
+        spend(&ledger, day_start - 1, 2.0);
+        spend(&ledger, month_start - 1, 4.0);
+
+        let summary = ledger.summary(&policy(|_| {}), now);
+        assert!((summary.today_usd - 1.0).abs() < 1e-9);
+        // The entry just before midnight counts toward the month unless today is the 1st
+        let month = if day_start == month_start { 1.0 } else { 3.0 };
+        assert!((summary.month_usd - month).abs() < 1e-9);
+        assert!((summary.month_by_provider["openai"] - month).abs() < 1e-9);
+    }
+
+    #[test]
+    fn quarantines_a_corrupt_ledger() {
+        let dir = std::env::temp_dir().join(format!("whispo-costs-corrupt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(LEDGER_FILE), "{not json").unwrap();
+
+        let ledger = CostLedger::new(&dir).unwrap();
+        assert!(ledger.entries().is_empty());
+        assert!(ledger.load_error().is_some());
+        assert!(!dir.join(LEDGER_FILE).exists());
+        let moved = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("costs.json.corrupt-"));
+        assert!(moved);
+
+        spend(&ledger, 0, 1.0);
+        assert_eq!(CostLedger::new(&dir).unwrap().entries().len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::types::RecordingHistoryItem;
+use crate::vault::Vault;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub const RECORDINGS_DIR: &str = "recordings";
+pub const HISTORY_FILE: &str = "history.json";
+
+/// Recording history and audio files stored under `app_data_dir/recordings`.
+/// When the vault is enabled both are encrypted on disk and decrypted on read.
+pub struct HistoryStore {
+    recordings_folder: PathBuf,
+    vault: Arc<Vault>,
+    /// Held for every load-modify-save of history.json, so the retention
+    /// sweeper and the commands don't overwrite each other's changes
+    lock: Mutex<()>,
+}
+
+impl HistoryStore {
+    pub fn new(app_data_dir: &Path, vault: Arc<Vault>) -> Self {
+        Self {
+            recordings_folder: app_data_dir.join(RECORDINGS_DIR),
+            vault,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn recordings_folder(&self) -> &Path {
+        &self.recordings_folder
+    }
+
+    pub fn history_path(&self) -> PathBuf {
+        self.recordings_folder.join(HISTORY_FILE)
+    }
+
+    pub fn audio_path(&self, id: &str) -> PathBuf {
+        self.recordings_folder.join(format!("{}.webm", id))
+    }
+
+    pub fn load(&self) -> Result<Vec<RecordingHistoryItem>> {
+        self.read_history_at(&self.history_path())
+    }
+
+    pub fn save(&self, history: &[RecordingHistoryItem]) -> Result<()> {
+        fs::create_dir_all(&self.recordings_folder)
+            .context("Failed to create recordings folder")?;
+        self.write_history_at(&self.history_path(), history)
+    }
+
+    /// Load the history, let `change` modify it and save it if it did, with
+    /// no other change to history.json in between
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Vec<RecordingHistoryItem>) -> Result<T>,
+    ) -> Result<T> {
+        let _lock = self.lock.lock().unwrap();
+        let mut history = self.load()?;
+        let before = history.clone();
+        let result = change(&mut history)?;
+        if history != before {
+            self.save(&history)?;
+        }
+        Ok(result)
+    }
+
+    /// Store a new recording. While the vault is locked nothing can be
+    /// encrypted, so the recording is refused rather than written in the clear.
+    pub fn add(&self, item: RecordingHistoryItem, audio: &[u8]) -> Result<()> {
+        if self.is_locked() {
+            anyhow::bail!("The vault is locked, so the recording wasn't saved to history");
+        }
+
+        self.update(|history| {
+            self.save_audio(&item.id, audio)?;
+            history.push(item);
+            Ok(())
+        })
+    }
+
+    /// Run `change` with no other change to history.json or the audio files
+    /// in between, for changes that move the recordings folder as a whole
+    pub fn exclusive<T>(&self, change: impl FnOnce() -> Result<T>) -> Result<T> {
+        let _lock = self.lock.lock().unwrap();
+        change()
+    }
+
+    /// Whether history is sealed by a vault that isn't unlocked
+    pub fn is_locked(&self) -> bool {
+        self.vault.is_locked()
+    }
+
+    pub fn save_audio(&self, id: &str, data: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.recordings_folder)
+            .context("Failed to create recordings folder")?;
+        fs::write(self.audio_path(id), self.seal(data)?)?;
+        Ok(())
+    }
+
+    pub fn read_audio(&self, id: &str) -> Result<Vec<u8>> {
+        let data = fs::read(self.audio_path(id))?;
+        self.vault.decrypt_bytes(&data)
+    }
+
+    /// Read any history.json (ours, a trash entry or one from another install),
+    /// decrypting it if needed
+    pub fn read_history_at(&self, path: &Path) -> Result<Vec<RecordingHistoryItem>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let data = self.vault.decrypt_bytes(&fs::read(path)?)?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Failed to parse history file {}", path.display()))
+    }
+
+    pub fn write_history_at(&self, path: &Path, history: &[RecordingHistoryItem]) -> Result<()> {
+        let content = serde_json::to_vec(history)?;
+        fs::write(path, self.seal(&content)?)?;
+        Ok(())
+    }
+
+    /// Rewrite history and audio so they match the vault state, after it was
+    /// enabled (`encrypt = true`) or before it gets disabled
+    pub fn rewrite_all(&self, encrypt: bool) -> Result<()> {
+        let _lock = self.lock.lock().unwrap();
+        let history = self.load()?;
+
+        for item in &history {
+            let path = self.audio_path(&item.id);
+            if !path.exists() {
+                continue;
+            }
+            let data = self.vault.decrypt_bytes(&fs::read(&path)?)?;
+            let data = if encrypt { self.vault.encrypt_bytes(&data)? } else { data };
+            fs::write(&path, data)?;
+        }
+
+        if self.history_path().exists() {
+            let content = serde_json::to_vec(&history)?;
+            let content = if encrypt { self.vault.encrypt_bytes(&content)? } else { content };
+            fs::write(self.history_path(), content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Merge items from another history into this one, skipping ids that
+    /// already exist. Audio files next to `source_folder` are copied along.
+    /// Returns the number of items added.
+    pub fn import(
+        &self,
+        incoming: Vec<RecordingHistoryItem>,
+        source_folder: Option<&Path>,
+    ) -> Result<usize> {
+        self.update(|history| {
+            let mut added = 0;
+
+            for item in incoming {
+                if history.iter().any(|existing| existing.id == item.id) {
+                    continue;
+                }
+
+                if let Some(folder) = source_folder {
+                    let source_audio = folder.join(format!("{}.webm", item.id));
+                    let target_audio = self.audio_path(&item.id);
+                    if source_audio.exists() && !target_audio.exists() {
+                        let data = self.vault.decrypt_bytes(&fs::read(&source_audio)?)
+                            .with_context(|| format!("Failed to read audio for recording {}", item.id))?;
+                        self.save_audio(&item.id, &data)?;
+                    }
+                }
+
+                history.push(item);
+                added += 1;
+            }
+
+            Ok(added)
+        })
+    }
+
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if self.vault.is_enabled() {
+            self.vault.encrypt_bytes(data)
+        } else {
+            Ok(data.to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = std::env::temp_dir().join(format!("whispo-history-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let vault = Arc::new(Vault::new(&dir));
+        let store = Arc::new(HistoryStore::new(&dir, vault));
+
+        let threads: Vec<_> = (0..8)
+            .map(|n| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    store
+                        .update(|history| {
+                            history.push(RecordingHistoryItem {
+                                id: n.to_string(),
+                                created_at: n,
+                                duration: 1.0,
+                                transcript: String::new(),
+                                original_transcript: None,
+                                is_original_shown: None,
+                                pinned: None,
+                                provider_id: None,
+                                latency_ms: None,
+                                app_name: None,
+                                cost_usd: None,
+                            });
+                            Ok(())
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(store.load().unwrap().len(), 8);
+    }
+}
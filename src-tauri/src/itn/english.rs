@@ -0,0 +1,718 @@
+use super::{join, Normalizer, Token};
+
+pub struct English;
+
+impl Normalizer for English {
+    fn code(&self) -> &'static str {
+        "en"
+    }
+
+    /// Dates, times, amounts, versions, measurements and percentages first,
+    /// then any number left over. Single numbers below ten stay words, as
+    /// style guides prefer, unless a unit or symbol follows.
+    fn normalize(&self, text: &str) -> String {
+        let tokens = Token::split(text);
+
+        let mut output: Vec<Token> = Vec::with_capacity(tokens.len());
+        let mut index = 0;
+        while index < tokens.len() {
+            if let Some(written) = RULES.iter().find_map(|rule| rule(&tokens, index)) {
+                // "on the third of March" reads "on March 3"
+                if written.drops_article && output.last().is_some_and(|t| t.is_open() && t.lower() == "the") {
+                    output.pop();
+                }
+                index += written.len;
+                let trailing = written.trailing.unwrap_or_else(|| tokens[index - 1].trailing.clone());
+                output.push(Token {
+                    word: written.text,
+                    trailing,
+                });
+                continue;
+            }
+            output.push(tokens[index].clone());
+            index += 1;
+        }
+        join(&output)
+    }
+}
+
+/// Reads the words at an index, returning what to write in their place
+type Rule = fn(&[Token], usize) -> Option<Written>;
+
+/// Tried in order at each word; the most specific come first
+const RULES: &[Rule] = &[date, day_of_month, time, currency, version, measurement, plain_number];
+
+/// What a rule writes in place of `len` spoken words
+struct Written {
+    text: String,
+    len: usize,
+    drops_article: bool,
+    /// Punctuation to write after it, when not the last spoken word's
+    trailing: Option<String>,
+}
+
+impl Written {
+    fn new(text: String, len: usize) -> Self {
+        Self {
+            text,
+            len,
+            drops_article: false,
+            trailing: None,
+        }
+    }
+}
+
+const UNITS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const UNIT_ORDINALS: &[&str] = &[
+    "zeroth",
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+];
+
+const TENS: &[(&str, &str, u64)] = &[
+    ("twenty", "twentieth", 20),
+    ("thirty", "thirtieth", 30),
+    ("forty", "fortieth", 40),
+    ("fifty", "fiftieth", 50),
+    ("sixty", "sixtieth", 60),
+    ("seventy", "seventieth", 70),
+    ("eighty", "eightieth", 80),
+    ("ninety", "ninetieth", 90),
+];
+
+const SCALES: &[(&str, u64)] = &[
+    ("hundred", 100),
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+];
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Spoken unit, how it's written and whether a space separates it from
+/// the number. Plural forms match too.
+const MEASUREMENTS: &[(&str, &str, bool)] = &[
+    ("percent", "%", false),
+    ("per cent", "%", false),
+    ("kilometers per hour", "km/h", true),
+    ("kilometres per hour", "km/h", true),
+    ("miles per hour", "mph", true),
+    ("degrees celsius", "°C", false),
+    ("degrees fahrenheit", "°F", false),
+    ("degree", "°", false),
+    ("kilometer", "km", true),
+    ("kilometre", "km", true),
+    ("centimeter", "cm", true),
+    ("centimetre", "cm", true),
+    ("millimeter", "mm", true),
+    ("millimetre", "mm", true),
+    ("kilogram", "kg", true),
+    ("gram", "g", true),
+    ("milligram", "mg", true),
+    ("millisecond", "ms", true),
+    ("kilobyte", "KB", true),
+    ("megabyte", "MB", true),
+    ("gigabyte", "GB", true),
+    ("terabyte", "TB", true),
+];
+
+/// Currency word and symbol, written before the amount
+const CURRENCIES: &[(&str, &str)] = &[("dollar", "$"), ("euro", "€")];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberWord {
+    Unit(u64),
+    Ten(u64),
+    Scale(u64),
+}
+
+/// A number word and whether it's the ordinal form
+fn number_word(word: &str) -> Option<(NumberWord, bool)> {
+    let word = word.to_lowercase();
+    if let Some(n) = UNITS.iter().position(|w| *w == word) {
+        return Some((NumberWord::Unit(n as u64), false));
+    }
+    if let Some(n) = UNIT_ORDINALS.iter().position(|w| *w == word) {
+        return Some((NumberWord::Unit(n as u64), true));
+    }
+    for (cardinal, ordinal, value) in TENS {
+        if word == *cardinal || word == *ordinal {
+            return Some((NumberWord::Ten(*value), word == *ordinal));
+        }
+    }
+    SCALES
+        .iter()
+        .find(|(w, _)| *w == word)
+        .map(|(_, value)| (NumberWord::Scale(*value), false))
+}
+
+/// A number read from the words at `index`, as many as form one number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SpokenNumber {
+    value: u64,
+    len: usize,
+    ordinal: bool,
+}
+
+fn word_at(tokens: &[Token], index: usize) -> Option<String> {
+    tokens.get(index).map(Token::lower)
+}
+
+/// Whether the words from `index` say `phrase`, with nothing but the last
+/// followed by punctuation
+fn phrase_at(tokens: &[Token], index: usize, phrase: &str) -> Option<usize> {
+    let parts: Vec<&str> = phrase.split(' ').collect();
+    let spoken = tokens.get(index..index + parts.len())?;
+    let matches = spoken.iter().zip(&parts).all(|(token, part)| token.lower() == *part);
+    let open = spoken[..parts.len() - 1].iter().all(Token::is_open);
+    (matches && open).then_some(parts.len())
+}
+
+fn number_at(tokens: &[Token], index: usize) -> Option<SpokenNumber> {
+    // Speech-to-text sometimes writes part of a phrase in digits already
+    let first = tokens.get(index)?;
+    if !first.word.is_empty() && first.word.chars().all(|c| c.is_ascii_digit()) {
+        return first.word.parse().ok().map(|value| SpokenNumber {
+            value,
+            len: 1,
+            ordinal: false,
+        });
+    }
+
+    let mut total = 0;
+    let mut current = 0;
+    let mut previous: Option<NumberWord> = None;
+    let mut len = 0;
+    let mut ordinal = false;
+
+    while let Some(token) = tokens.get(index + len) {
+        if ordinal || (len > 0 && !tokens[index + len - 1].is_open()) {
+            break;
+        }
+        // "one hundred and five"
+        if token.lower() == "and" {
+            let after_scale = matches!(previous, Some(NumberWord::Scale(_)));
+            let next = tokens.get(index + len + 1).and_then(|t| number_word(&t.word));
+            if after_scale && token.is_open() && matches!(next, Some((NumberWord::Unit(_) | NumberWord::Ten(_), _))) {
+                len += 1;
+                continue;
+            }
+            break;
+        }
+        let Some((kind, is_ordinal)) = number_word(&token.word) else {
+            break;
+        };
+        match (previous, kind) {
+            (None | Some(NumberWord::Scale(_)), NumberWord::Unit(n) | NumberWord::Ten(n)) => current += n,
+            (Some(NumberWord::Ten(_)), NumberWord::Unit(n)) if (1..10).contains(&n) => current += n,
+            (Some(NumberWord::Unit(_) | NumberWord::Ten(_)), NumberWord::Scale(100)) if current < 100 => {
+                current *= 100;
+            }
+            // "two hundred thousand", "nine hundred ninety nine billion"
+            (Some(NumberWord::Unit(_) | NumberWord::Ten(_) | NumberWord::Scale(100)), NumberWord::Scale(scale))
+                if scale > 100 && (total == 0 || total % (scale * 1000) == 0) =>
+            {
+                total += current * scale;
+                current = 0;
+            }
+            _ => break,
+        }
+        previous = Some(kind);
+        ordinal = is_ordinal;
+        len += 1;
+    }
+
+    (len > 0).then_some(SpokenNumber {
+        value: total + current,
+        len,
+        ordinal,
+    })
+}
+
+/// A year said as two pairs, e.g. "nineteen ninety nine", "twenty twenty
+/// four" or "twenty oh five"
+fn year_at(tokens: &[Token], index: usize) -> Option<SpokenNumber> {
+    let century = number_at(tokens, index).filter(|n| (10..=20).contains(&n.value) && n.len == 1)?;
+    if !tokens[index].is_open() {
+        return None;
+    }
+    let rest = index + century.len;
+    let (value, len) = match word_at(tokens, rest).as_deref() {
+        Some("oh" | "o") if tokens[rest].is_open() => {
+            let digit = number_at(tokens, rest + 1).filter(|n| n.value < 10 && n.len == 1)?;
+            (digit.value, 1 + digit.len)
+        }
+        _ => {
+            let pair = number_at(tokens, rest).filter(|n| (10..100).contains(&n.value) && !n.ordinal)?;
+            (pair.value, pair.len)
+        }
+    };
+    Some(SpokenNumber {
+        value: century.value * 100 + value,
+        len: century.len + len,
+        ordinal: false,
+    })
+}
+
+/// Digits with thousands separated once a number is long enough to need it
+fn digits(value: u64) -> String {
+    let plain = value.to_string();
+    if value < 10_000 {
+        return plain;
+    }
+    let mut output = String::new();
+    for (index, c) in plain.chars().enumerate() {
+        if index > 0 && (plain.len() - index).is_multiple_of(3) {
+            output.push(',');
+        }
+        output.push(c);
+    }
+    output
+}
+
+fn ordinal(value: u64) -> String {
+    let suffix = match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", value, suffix)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn month_at(tokens: &[Token], index: usize) -> Option<&'static str> {
+    let word = word_at(tokens, index)?;
+    MONTHS.iter().find(|month| **month == word).copied()
+}
+
+fn day_at(tokens: &[Token], index: usize) -> Option<SpokenNumber> {
+    number_at(tokens, index).filter(|day| (1..=31).contains(&day.value))
+}
+
+/// ", 2024" when a year follows the day at `index`; a comma after the day
+/// is the only punctuation allowed between them
+fn year_after(tokens: &[Token], day_end: usize) -> Option<(String, usize)> {
+    let separator = &tokens[day_end - 1].trailing;
+    if !(separator.is_empty() || separator == ",") {
+        return None;
+    }
+    let year = year_at(tokens, day_end).or_else(|| number_at(tokens, day_end).filter(|n| n.value >= 1000))?;
+    Some((format!(", {}", year.value), year.len))
+}
+
+/// Months that are also everyday words, as in "you may one day" or "we
+/// march two miles"
+const AMBIGUOUS_MONTHS: &[&str] = &["may", "march"];
+
+/// Whether the word at `index` is capitalized where a sentence doesn't
+/// start, so is meant as a name
+fn is_proper_noun(tokens: &[Token], index: usize) -> bool {
+    let starts_sentence = index == 0 || tokens[index - 1].trailing.contains(['.', '!', '?']);
+    !starts_sentence && tokens[index].word.starts_with(|c: char| c.is_uppercase())
+}
+
+/// "march third" and "march third twenty twenty four" as "March 3" and
+/// "March 3, 2024". "May" and "March" with a plain number after them only
+/// count when capitalized or followed by a year.
+fn date(tokens: &[Token], index: usize) -> Option<Written> {
+    let month = month_at(tokens, index).filter(|_| tokens[index].is_open())?;
+    let day = day_at(tokens, index + 1)?;
+    let day_end = index + 1 + day.len;
+    let year_after = year_after(tokens, day_end);
+    if !day.ordinal && year_after.is_none() && AMBIGUOUS_MONTHS.contains(&month) && !is_proper_noun(tokens, index) {
+        return None;
+    }
+    let (year, year_len) = year_after.unwrap_or_default();
+    Some(Written::new(
+        format!("{} {}{}", capitalize(month), day.value, year),
+        1 + day.len + year_len,
+    ))
+}
+
+/// "the third of march" as "March 3". Without "the" before it, a year
+/// after it or a capitalized month, "a second of march" is left alone.
+fn day_of_month(tokens: &[Token], index: usize) -> Option<Written> {
+    let day = day_at(tokens, index).filter(|day| day.ordinal)?;
+    let of = index + day.len;
+    if word_at(tokens, of).as_deref() != Some("of") || !tokens[of - 1].is_open() || !tokens[of].is_open() {
+        return None;
+    }
+    let month = month_at(tokens, of + 1)?;
+    let year_after = year_after(tokens, of + 2);
+    let after_the = index > 0 && tokens[index - 1].is_open() && tokens[index - 1].lower() == "the";
+    if !after_the && year_after.is_none() && !is_proper_noun(tokens, of + 1) {
+        return None;
+    }
+    let (year, year_len) = year_after.unwrap_or_default();
+    Some(Written {
+        text: format!("{} {}{}", capitalize(month), day.value, year),
+        len: day.len + 2 + year_len,
+        drops_article: true,
+        trailing: None,
+    })
+}
+
+fn meridiem_at(tokens: &[Token], index: usize) -> Option<(&'static str, usize)> {
+    match word_at(tokens, index)?.as_str() {
+        "am" | "a.m" => Some(("AM", 1)),
+        "pm" | "p.m" => Some(("PM", 1)),
+        "a" | "p" if phrase_at(tokens, index + 1, "m").is_some() && tokens[index].is_open() => {
+            Some((if tokens[index].lower() == "a" { "AM" } else { "PM" }, 2))
+        }
+        _ => None,
+    }
+}
+
+/// "three thirty pm" as "3:30 PM" and "at three fifteen" as "at 3:15".
+/// Without "am", "pm" or a preceding "at", "three thirty" isn't
+/// certainly a time, so it's left alone.
+fn time(tokens: &[Token], index: usize) -> Option<Written> {
+    let hour = number_at(tokens, index).filter(|n| (1..=12).contains(&n.value) && n.len == 1 && !n.ordinal)?;
+    let mut len = hour.len;
+    let mut minutes = None;
+    if tokens[index + len - 1].is_open() {
+        match word_at(tokens, index + len).as_deref() {
+            Some("oh" | "o") if tokens[index + len].is_open() => {
+                if let Some(n) = number_at(tokens, index + len + 1).filter(|n| n.value < 10 && n.len == 1) {
+                    minutes = Some(n.value);
+                    len += 1 + n.len;
+                }
+            }
+            _ => {
+                if let Some(n) = number_at(tokens, index + len).filter(|n| (10..60).contains(&n.value) && !n.ordinal) {
+                    minutes = Some(n.value);
+                    len += n.len;
+                }
+            }
+        }
+    }
+
+    let meridiem = if tokens[index + len - 1].is_open() {
+        meridiem_at(tokens, index + len)
+    } else {
+        None
+    };
+    let after_at = index > 0 && tokens[index - 1].is_open() && tokens[index - 1].lower() == "at";
+    if meridiem.is_none() && !(after_at && minutes.is_some()) {
+        return None;
+    }
+
+    let mut text = hour.value.to_string();
+    if let Some(minutes) = minutes {
+        text = format!("{}:{:02}", text, minutes);
+    }
+    let mut trailing = None;
+    if let Some((meridiem, meridiem_len)) = meridiem {
+        text = format!("{} {}", text, meridiem);
+        len += meridiem_len;
+        // The dot of "p.m." ends the sentence only if a new one follows
+        let last = &tokens[index + len - 1];
+        let sentence_continues = tokens
+            .get(index + len)
+            .is_some_and(|next| next.word.starts_with(char::is_lowercase));
+        if last.word.contains('.') && sentence_continues {
+            trailing = last.trailing.strip_prefix('.').map(str::to_string);
+        }
+    }
+    Some(Written {
+        trailing,
+        ..Written::new(text, len)
+    })
+}
+
+/// A number with an optional fractional part, "two point five" as "2.5",
+/// and the number of words it took
+fn decimal_at(tokens: &[Token], index: usize) -> Option<(String, usize)> {
+    let whole = year_at(tokens, index).or_else(|| number_at(tokens, index))?;
+    if whole.ordinal {
+        return None;
+    }
+    let mut text = whole.value.to_string();
+    let mut len = whole.len;
+    if let Some((fraction, fraction_len)) = fraction_at(tokens, index + len) {
+        text = format!("{}.{}", text, fraction);
+        len += fraction_len;
+    }
+    Some((text, len))
+}
+
+/// The part after "point": digit by digit ("three point one four"), or
+/// one number ("two point twenty five")
+fn fraction_at(tokens: &[Token], point: usize) -> Option<(String, usize)> {
+    if !tokens[point - 1].is_open() || phrase_at(tokens, point, "point").is_none() || !tokens[point].is_open() {
+        return None;
+    }
+    let start = point + 1;
+    if let Some(number) = number_at(tokens, start).filter(|n| (10..100).contains(&n.value) && !n.ordinal) {
+        return Some((number.value.to_string(), 1 + number.len));
+    }
+    let mut fraction = String::new();
+    let mut len = 0;
+    while let Some(token) = tokens.get(start + len) {
+        if len > 0 && !tokens[start + len - 1].is_open() {
+            break;
+        }
+        let digit = match token.lower().as_str() {
+            "oh" | "o" => 0,
+            word => match number_word(word) {
+                Some((NumberWord::Unit(n), false)) if n < 10 => n,
+                _ => break,
+            },
+        };
+        fraction.push_str(&digit.to_string());
+        len += 1;
+    }
+    (len > 0).then(|| (fraction, 1 + len))
+}
+
+/// Whether a scale word follows, as in "1.5 million", and how it's written
+fn scale_after(tokens: &[Token], index: usize) -> Option<(String, usize)> {
+    if !tokens[index - 1].is_open() {
+        return None;
+    }
+    let word = word_at(tokens, index)?;
+    SCALES
+        .iter()
+        .skip(2)
+        .find(|(scale, _)| *scale == word)
+        .map(|(scale, _)| (format!(" {}", scale), 1))
+}
+
+/// "five dollars and ten cents" as "$5.10", "two point five million euros"
+/// as "€2.5 million" and "ten cents" as "10¢"
+fn currency(tokens: &[Token], index: usize) -> Option<Written> {
+    let (amount, mut len) = decimal_at(tokens, index)?;
+    let (scale, scale_len) = scale_after(tokens, index + len).unwrap_or_default();
+    len += scale_len;
+    if !tokens[index + len - 1].is_open() {
+        return None;
+    }
+
+    let currency_word = word_at(tokens, index + len)?;
+    let cents_word = |word: &str| matches!(word, "cent" | "cents");
+    if cents_word(&currency_word) && scale.is_empty() {
+        return Some(Written::new(format!("{}¢", amount), len + 1));
+    }
+    let symbol = CURRENCIES
+        .iter()
+        .find(|(name, _)| currency_word == *name || currency_word == format!("{}s", name))
+        .map(|(_, symbol)| *symbol)?;
+    len += 1;
+
+    let amount = match amount.parse::<u64>() {
+        Ok(whole) if scale.is_empty() => digits(whole),
+        _ => amount,
+    };
+    // "and ten cents"
+    let cents_start = index + len + usize::from(word_at(tokens, index + len).as_deref() == Some("and"));
+    let cents = (scale.is_empty() && !amount.contains('.') && tokens[index + len - 1].is_open())
+        .then(|| number_at(tokens, cents_start))
+        .flatten()
+        .filter(|n| n.value < 100 && !n.ordinal && tokens[cents_start..cents_start + n.len].iter().all(Token::is_open))
+        .filter(|n| word_at(tokens, cents_start + n.len).is_some_and(|w| cents_word(&w)))
+        .filter(|_| tokens[index + len..cents_start].iter().all(Token::is_open));
+    if let Some(cents) = cents {
+        return Some(Written::new(
+            format!("{}{}.{:02}", symbol, amount, cents.value),
+            cents_start - index + cents.len + 1,
+        ));
+    }
+    Some(Written::new(format!("{}{}{}", symbol, amount, scale), len))
+}
+
+/// "version two point one" as "version 2.1", with as many parts as said
+fn version(tokens: &[Token], index: usize) -> Option<Written> {
+    if word_at(tokens, index)? != "version" || !tokens[index].is_open() {
+        return None;
+    }
+    let first = number_at(tokens, index + 1).filter(|n| !n.ordinal)?;
+    let mut text = format!("{} {}", tokens[index].word, first.value);
+    let mut len = 1 + first.len;
+    while let Some((part, part_len)) = fraction_at(tokens, index + len).or_else(|| {
+        // Later parts can be spoken as "dot" too
+        let dot = index + len;
+        let open = tokens[dot - 1].is_open() && tokens.get(dot).is_some_and(Token::is_open);
+        (open && word_at(tokens, dot).as_deref() == Some("dot"))
+            .then(|| number_at(tokens, dot + 1).filter(|n| !n.ordinal))
+            .flatten()
+            .map(|n| (n.value.to_string(), 1 + n.len))
+    }) {
+        text = format!("{}.{}", text, part);
+        len += part_len;
+    }
+    Some(Written::new(text, len))
+}
+
+/// "twenty five percent" as "25%" and "five kilometers" as "5 km"
+fn measurement(tokens: &[Token], index: usize) -> Option<Written> {
+    let (amount, len) = decimal_at(tokens, index)?;
+    if !tokens[index + len - 1].is_open() {
+        return None;
+    }
+    let amount = amount.parse::<u64>().map(digits).unwrap_or(amount);
+    MEASUREMENTS.iter().find_map(|(phrase, written, spaced)| {
+        let unit_len = phrase_at(tokens, index + len, phrase).or_else(|| {
+            let plural = format!("{}s", phrase);
+            phrase_at(tokens, index + len, &plural)
+        })?;
+        let separator = if *spaced { " " } else { "" };
+        Some(Written::new(
+            format!("{}{}{}", amount, separator, written),
+            len + unit_len,
+        ))
+    })
+}
+
+/// Any other number of two or more words or of ten and up, ordinals
+/// included, and decimals
+fn plain_number(tokens: &[Token], index: usize) -> Option<Written> {
+    if tokens[index].word.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Some((decimal, len)) = decimal_at(tokens, index).filter(|(text, _)| text.contains('.')) {
+        return Some(Written::new(decimal, len));
+    }
+    let number = year_at(tokens, index).or_else(|| number_at(tokens, index))?;
+    if number.len == 1 && number.value < 10 {
+        return None;
+    }
+    let text = if number.ordinal {
+        ordinal(number.value)
+    } else {
+        digits(number.value)
+    };
+    Some(Written::new(text, number.len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::English;
+    use crate::itn::Normalizer;
+
+    #[test]
+    fn english() {
+        let cases = [
+            // Numbers
+            ("I have two cats and thirteen fish.", "I have two cats and 13 fish."),
+            ("It took one hundred and five people.", "It took 105 people."),
+            ("About three thousand four hundred people.", "About 3400 people."),
+            ("Two million users.", "2,000,000 users."),
+            ("About two hundred thousand people.", "About 200,000 people."),
+            ("Three hundred fifty thousand", "350,000"),
+            (
+                "nine hundred ninety nine billion nine hundred million",
+                "999,900,000,000",
+            ),
+            ("She came twenty first.", "She came 21st."),
+            ("One more thing.", "One more thing."),
+            ("It's twenty, maybe thirty.", "It's 20, maybe 30."),
+            ("Pi is three point one four.", "Pi is 3.14."),
+            // Percentages and units
+            ("It grew twenty five percent.", "It grew 25%."),
+            ("It grew 25 percent.", "It grew 25%."),
+            ("Run five kilometers today", "Run 5 km today"),
+            ("It's one kilometer away", "It's 1 km away"),
+            ("Set it to twenty degrees celsius.", "Set it to 20°C."),
+            ("A two point five gigabyte file", "A 2.5 GB file"),
+            // Dates
+            ("The meeting is on march third.", "The meeting is on March 3."),
+            ("It's due on the fifth of may.", "It's due on May 5."),
+            ("Born june twelfth, nineteen ninety nine.", "Born June 12, 1999."),
+            (
+                "The launch is july four twenty twenty four",
+                "The launch is July 4, 2024",
+            ),
+            ("Back in twenty oh five.", "Back in 2005."),
+            ("You may go.", "You may go."),
+            ("You may one day regret it.", "You may one day regret it."),
+            ("We march two miles.", "We march two miles."),
+            ("Give me a second of march music.", "Give me a second of march music."),
+            ("We met on May two.", "We met on May 2."),
+            ("It's may two twenty twenty five", "It's May 2, 2025"),
+            ("Due on the second of march.", "Due on March 2."),
+            // Times
+            ("Let's meet at three thirty pm.", "Let's meet at 3:30 PM."),
+            ("Call me at nine a.m. tomorrow", "Call me at 9 AM tomorrow"),
+            ("Call me at nine a.m. Thanks.", "Call me at 9 AM. Thanks."),
+            ("It starts at seven oh five p m", "It starts at 7:05 PM"),
+            ("Meet at three fifteen.", "Meet at 3:15."),
+            // Currency
+            ("That's five dollars and ten cents.", "That's $5.10."),
+            ("That's five dollars ten cents", "That's $5.10"),
+            ("It costs twenty dollars.", "It costs $20."),
+            ("It raised two point five million euros", "It raised €2.5 million"),
+            ("Only ninety nine cents", "Only 99¢"),
+            ("Twelve thousand dollars", "$12,000"),
+            ("Five dollars, and ten cents", "$5, and 10¢"),
+            // Versions
+            ("Upgrade to version two point one", "Upgrade to version 2.1"),
+            ("Version three point ten point two is out.", "Version 3.10.2 is out."),
+            ("version two dot one dot three", "version 2.1.3"),
+            ("Use version two.", "Use version 2."),
+        ];
+        for (spoken, written) in cases {
+            assert_eq!(English.normalize(spoken), written, "{:?}", spoken);
+        }
+    }
+}
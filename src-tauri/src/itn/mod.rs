@@ -0,0 +1,85 @@
+// Inverse text normalization: spoken numbers, amounts, dates and times
+// in the written form a reader expects
+
+mod english;
+
+/// Rewrites what speech-to-text spelled out in one language's written
+/// conventions, e.g. "five dollars and ten cents" as "$5.10"
+pub trait Normalizer: Send + Sync {
+    /// Primary language subtag, e.g. "en"
+    fn code(&self) -> &'static str;
+    fn normalize(&self, text: &str) -> String;
+}
+
+const NORMALIZERS: &[&dyn Normalizer] = &[&english::English];
+
+/// The primary subtag of a language tag, e.g. "en" for "en-US"
+pub fn language_code(tag: &str) -> String {
+    tag.split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+/// Normalize `text` with the rules for `language`, a tag like "en-US".
+/// Languages without rules come back unchanged.
+pub fn inverse_normalize(text: &str, language: &str) -> String {
+    let code = language_code(language);
+    match NORMALIZERS.iter().find(|normalizer| normalizer.code() == code) {
+        Some(normalizer) => normalizer.normalize(text),
+        None => text.to_string(),
+    }
+}
+
+/// A word with the punctuation speech-to-text put after it kept apart, so
+/// rules can match words and a replacement can keep the last one's
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    word: String,
+    trailing: String,
+}
+
+impl Token {
+    fn split(text: &str) -> Vec<Token> {
+        text.split_whitespace()
+            .map(|word| {
+                let stem = word.trim_end_matches(['.', ',', '!', '?', ';', ':']);
+                // A word that's all punctuation, like "...", stays whole
+                let stem = if stem.is_empty() { word } else { stem };
+                Token {
+                    word: stem.to_string(),
+                    trailing: word[stem.len()..].to_string(),
+                }
+            })
+            .collect()
+    }
+
+    fn lower(&self) -> String {
+        self.word.to_lowercase()
+    }
+
+    /// Whether a rule can carry on past this word into the next
+    fn is_open(&self) -> bool {
+        self.trailing.is_empty()
+    }
+}
+
+fn join(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| format!("{}{}", token.word, token.trailing))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inverse_normalize;
+
+    #[test]
+    fn picks_rules_by_language() {
+        assert_eq!(inverse_normalize("twenty five percent", "en-US"), "25%");
+        assert_eq!(inverse_normalize("twenty five percent", "en"), "25%");
+        assert_eq!(
+            inverse_normalize("vingt-cinq pour cent", "fr-FR"),
+            "vingt-cinq pour cent"
+        );
+    }
+}
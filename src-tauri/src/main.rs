@@ -16,11 +16,37 @@ mod types;
 mod state;
 mod shortcuts;
 mod mcp;
-
-use config::ConfigStore;
-use state::AppState;
+mod history;
+mod trash;
+mod backup;
+mod retention;
+mod vault;
+mod stats;
+mod costs;
+mod auto_profile;
+mod app_rules;
+mod app_watcher;
+mod context;
+mod itn;
+mod normalize;
+
+use app_rules::RuleResolution;
+use auto_profile::ActivationContext;
+use context::{ContextDetection, DenyRule, TitleContext, TitleParserRegistry};
+use backup::{BackupManifest, RestoreMode, RestoreReport};
+use config::{
+    Config, ConfigStore, ContextFormattingConfig, FieldError, ImportConflict, ImportReport, KeyOrigin, ProfileEffectiveConfig,
+    SaveConfigError, SecretHandling,
+};
+use costs::{CapDecision, CostEntry, CostKind, CostLedger, CostPolicy, CostSummary, ModelPrice};
+use history::HistoryStore;
+use retention::{RetentionPolicy, SweepReport};
+use stats::DictationStats;
+use trash::{Trash, TrashEntry};
+use vault::{Vault, VaultKeySource, VaultStatus};
+use state::{AppState, RecordingTarget};
 use types::*;
-use mcp::McpClient;
+use mcp::{McpClient, McpServer};
 use std::sync::mpsc::channel;
 
 // ===== CORE TAURI COMMANDS =====
@@ -173,52 +199,48 @@ async fn write_text_command(text: String) -> Result<(), String> {
     keyboard::write_text(&text).map_err(|e| e.to_string())
 }
 
-// ===== RECORDING MANAGEMENT =====
-
-#[tauri::command]
-async fn get_recording_history(app: AppHandle) -> Result<Vec<RecordingHistoryItem>, String> {
-    use std::fs;
-
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let recordings_folder = app_data_dir.join("recordings");
-    let history_file = recordings_folder.join("history.json");
+/// Ask before typing a command that matched the deny-list; it's on the
+/// clipboard either way. Waits for the answer without blocking the runtime.
+async fn confirm_command(app: &AppHandle, command: &str, rule: &DenyRule) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 
-    if history_file.exists() {
-        let content = fs::read_to_string(history_file).map_err(|e| e.to_string())?;
-        let mut history: Vec<RecordingHistoryItem> = serde_json::from_str(&content)
-            .map_err(|e| e.to_string())?;
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(format!("{}\n\n{}\n\nType this command into the terminal?", rule.description, command))
+        .title("Confirm command")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Type it".to_string(), "Cancel".to_string()))
+        .show(move |confirmed| {
+            let _ = sender.send(confirmed);
+        });
+    receiver.await.unwrap_or(false)
+}
 
-        history.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+// ===== RECORDING MANAGEMENT =====
 
-        Ok(history)
-    } else {
-        Ok(vec![])
-    }
+#[tauri::command]
+async fn get_recording_history(
+    history_store: State<'_, Arc<HistoryStore>>,
+) -> Result<Vec<RecordingHistoryItem>, String> {
+    let mut history = history_store.load().map_err(|e| e.to_string())?;
+    history.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(history)
 }
 
 #[tauri::command]
-async fn delete_recording_item(app: AppHandle, id: String) -> Result<(), String> {
-    use std::fs;
-
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let recordings_folder = app_data_dir.join("recordings");
-    let history_file = recordings_folder.join("history.json");
-
-    if history_file.exists() {
-        let content = fs::read_to_string(&history_file).map_err(|e| e.to_string())?;
-        let mut history: Vec<RecordingHistoryItem> = serde_json::from_str(&content)
-            .map_err(|e| e.to_string())?;
-
-        history.retain(|item| item.id != id);
-
-        fs::write(&history_file, serde_json::to_string(&history).map_err(|e| e.to_string())?)
-            .map_err(|e| e.to_string())?;
-
-        let audio_file = recordings_folder.join(format!("{}.webm", id));
-        if audio_file.exists() {
-            fs::remove_file(audio_file).map_err(|e| e.to_string())?;
+async fn delete_recording_item(
+    app: AppHandle,
+    history_store: State<'_, Arc<HistoryStore>>,
+    trash: State<'_, Arc<Trash>>,
+    id: String,
+) -> Result<(), String> {
+    history_store.update(|history| {
+        if let Some(index) = history.iter().position(|item| item.id == id) {
+            let item = history.remove(index);
+            trash.trash_recording(&history_store, &item, "delete")?;
         }
-    }
+        Ok(())
+    }).map_err(|e| e.to_string())?;
 
     if let Some(window) = app.get_window("main") {
         window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
@@ -228,80 +250,120 @@ async fn delete_recording_item(app: AppHandle, id: String) -> Result<(), String>
 }
 
 #[tauri::command]
-async fn delete_recording_history(app: AppHandle) -> Result<(), String> {
-    use std::fs;
-
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let recordings_folder = app_data_dir.join("recordings");
-
-    if recordings_folder.exists() {
-        fs::remove_dir_all(recordings_folder).map_err(|e| e.to_string())?;
-    }
-
+async fn delete_recording_history(
+    history_store: State<'_, Arc<HistoryStore>>,
+    trash: State<'_, Arc<Trash>>,
+) -> Result<(), String> {
+    trash.trash_recordings_folder(&history_store, "delete-all")
+        .map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
 async fn toggle_recording_transcript(
     app: AppHandle,
+    history_store: State<'_, Arc<HistoryStore>>,
     id: String,
 ) -> Result<serde_json::Value, String> {
-    use std::fs;
-
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let recordings_folder = app_data_dir.join("recordings");
-    let history_file = recordings_folder.join("history.json");
-
-    if !history_file.exists() {
+    if !history_store.history_path().exists() {
         return Err("History file not found".to_string());
     }
 
-    let content = fs::read_to_string(&history_file).map_err(|e| e.to_string())?;
-    let mut history: Vec<RecordingHistoryItem> = serde_json::from_str(&content)
-        .map_err(|e| e.to_string())?;
-
-    if let Some(item) = history.iter_mut().find(|i| i.id == id) {
+    let is_showing = history_store.update(|history| {
+        let item = history.iter_mut().find(|i| i.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Recording not found"))?;
         if item.original_transcript.is_none() {
-            return Err("No original transcript available".to_string());
+            anyhow::bail!("No original transcript available");
         }
 
         let is_showing = item.is_original_shown.unwrap_or(false);
         item.is_original_shown = Some(!is_showing);
+        Ok(is_showing)
+    }).map_err(|e| e.to_string())?;
 
-        fs::write(&history_file, serde_json::to_string(&history).map_err(|e| e.to_string())?)
-            .map_err(|e| e.to_string())?;
-
-        if let Some(window) = app.get_window("main") {
-            window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
-        }
-
-        Ok(serde_json::json!({
-            "success": true,
-            "isShowingOriginal": !is_showing
-        }))
-    } else {
-        Err("Recording not found".to_string())
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
     }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "isShowingOriginal": !is_showing
+    }))
 }
 
 #[tauri::command]
 async fn create_recording(
     app: AppHandle,
     config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+    app_state: State<'_, Arc<AppState>>,
+    cost_ledger: State<'_, Arc<CostLedger>>,
     recording: Vec<u8>,
     duration: f64,
     use_fusion: Option<bool>,
 ) -> Result<serde_json::Value, String> {
-    use std::fs;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+    // Use the app and rule from when the recording started, not whatever has focus now
+    let pinned = app_state.recording_target.lock().unwrap().take();
+    let target = pinned.unwrap_or_else(|| recording_target(&config_store, &app_state));
+    let mut config = app_rules::effective_config(config_store.config(), target.resolution.rule.as_ref());
+    let mut provider_id = config.stt_provider_id.clone();
+
+    let cost_policy = CostPolicy::from_config(&config);
+    match cost_ledger.check_caps(&cost_policy, &provider_id, current_timestamp()) {
+        CapDecision::Allow => {}
+        CapDecision::Warn { message } => {
+            emit_cost_warning(&app, &message);
+        }
+        CapDecision::Block { message } => return Err(message),
+        CapDecision::Fallback { provider_id: fallback, message } => {
+            emit_cost_warning(&app, &format!("{}, using {} instead", message, fallback));
+            config.stt_provider_id = fallback.clone();
+            provider_id = fallback;
+        }
+    }
 
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let recordings_folder = app_data_dir.join("recordings");
-    fs::create_dir_all(&recordings_folder).map_err(|e| e.to_string())?;
+    let app_name = target.app.as_ref().map(|app| app.name.clone());
 
-    let config = config_store.get();
     let blob = recording.clone();
-    let transcript = transcribe_audio(&config, blob).await?;
+    let started = Instant::now();
+    let raw_transcript = transcribe_audio(&config, blob).await?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    // Formatted for the app it's going into; history keeps what was said
+    let formatting_context = formatting_context(&config, target.app.as_ref());
+    let normalized = match formatting_context.as_deref() {
+        // Their formatters read spoken symbols and line breaks themselves
+        Some("code-editor" | "terminal") => raw_transcript.clone(),
+        _ => normalize::normalize_transcript(&raw_transcript, &config.streaming_dictation),
+    };
+    // Numbers after punctuation, so manual punctuation mode keeps the comma
+    // a date is written with
+    let normalization = &config.streaming_dictation.normalization;
+    let normalized = if normalization.enabled && normalization.normalize_numbers {
+        itn::inverse_normalize(&normalized, &config.streaming_dictation.language)
+    } else {
+        normalized
+    };
+    let transcript = match &formatting_context {
+        Some(context) => {
+            let language = target
+                .app
+                .as_ref()
+                .and_then(|app| title_parsers(&config_store).parse(app))
+                .and_then(|title| title.file)
+                .and_then(|file| file.language);
+            context::format_for_context(
+                context,
+                &normalized,
+                &config.context_formatting,
+                &config.streaming_dictation,
+                language.as_deref(),
+            )
+        }
+        None => normalized,
+    };
 
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -310,35 +372,70 @@ async fn create_recording(
 
     let id = timestamp.to_string();
 
-    let audio_file = recordings_folder.join(format!("{}.webm", id));
-    fs::write(audio_file, recording).map_err(|e| e.to_string())?;
-
-    let history_file = recordings_folder.join("history.json");
-    let mut history: Vec<RecordingHistoryItem> = if history_file.exists() {
-        let content = fs::read_to_string(&history_file).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).unwrap_or_else(|_| vec![])
-    } else {
-        vec![]
-    };
+    let model = stt_model(&config);
+    let cost_usd = cost_policy.transcription_cost(&provider_id, model, duration);
+    if let Some(cost_usd) = cost_usd {
+        cost_ledger.record(CostEntry {
+            timestamp,
+            recording_id: Some(id.clone()),
+            provider_id: provider_id.clone(),
+            model: model.to_string(),
+            kind: CostKind::Transcription,
+            cost_usd,
+        }).map_err(|e| e.to_string())?;
+    }
 
     let item = RecordingHistoryItem {
         id: id.clone(),
         created_at: timestamp,
         duration,
         transcript: transcript.clone(),
-        original_transcript: None,
+        original_transcript: (transcript != raw_transcript).then_some(raw_transcript),
         is_original_shown: None,
+        pinned: None,
+        provider_id: Some(provider_id),
+        latency_ms: Some(latency_ms),
+        app_name,
+        cost_usd,
     };
 
-    history.push(item);
-
-    fs::write(&history_file, serde_json::to_string(&history).map_err(|e| e.to_string())?)
-        .map_err(|e| e.to_string())?;
+    // The transcript is still delivered when a locked vault keeps it out of
+    // history
+    match history_store.add(item, &recording) {
+        Ok(()) => {}
+        Err(e) if history_store.is_locked() => {
+            for label in ["main", "panel"] {
+                if let Some(window) = app.get_window(label) {
+                    let _ = window.emit("recording-not-saved", e.to_string());
+                }
+            }
+        }
+        Err(e) => return Err(e.to_string()),
+    }
 
     app.clipboard().write_text(transcript.clone()).map_err(|e| e.to_string())?;
 
-    if platform::is_accessibility_granted() {
-        let _ = keyboard::write_text(&transcript);
+    if config.auto_insert && platform::is_accessibility_granted() {
+        // A destructive command waits for the user instead of being typed.
+        // The focused app is classified for this even with context formatting
+        // off, since a terminal runs what's typed into it either way.
+        let formatting = &config.context_formatting;
+        let into_terminal = formatting_context.as_deref() == Some("terminal")
+            || target.app.as_ref().is_some_and(|app| {
+                context::classify(&formatting.context_rules, app, &formatting.fallback_context).context == "terminal"
+            });
+        let deny_rule = if into_terminal {
+            context::dangerous_command(&transcript, &formatting.shell.deny_list)
+        } else {
+            None
+        };
+        let confirmed = match deny_rule {
+            Some(rule) => confirm_command(&app, &transcript, rule).await,
+            None => true,
+        };
+        if confirmed {
+            let _ = keyboard::write_text(&transcript);
+        }
     }
 
     if let Some(window) = app.get_window("panel") {
@@ -355,27 +452,265 @@ async fn create_recording(
     }))
 }
 
-async fn transcribe_audio(config: &serde_json::Value, audio_data: Vec<u8>) -> Result<String, String> {
-    let provider_id = config.get("sttProviderId")
-        .and_then(|v| v.as_str())
-        .unwrap_or("openai");
-
-    let (api_key, base_url, model) = match provider_id {
-        "groq" => (
-            config.get("groqApiKey").and_then(|v| v.as_str()).unwrap_or(""),
-            config.get("groqBaseUrl").and_then(|v| v.as_str()).unwrap_or("https://api.groq.com/openai/v1"),
-            "whisper-large-v3"
-        ),
-        _ => (
-            config.get("openaiApiKey").and_then(|v| v.as_str()).unwrap_or(""),
-            config.get("openaiBaseUrl").and_then(|v| v.as_str()).unwrap_or("https://api.openai.com/v1"),
-            "whisper-1"
-        ),
+#[tauri::command]
+async fn set_recording_pinned(
+    app: AppHandle,
+    history_store: State<'_, Arc<HistoryStore>>,
+    id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    history_store.update(|history| {
+        let item = history.iter_mut().find(|i| i.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Recording not found"))?;
+        item.pinned = Some(pinned);
+        Ok(())
+    }).map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+async fn run_retention_sweep(
+    app: AppHandle,
+    config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+) -> Result<SweepReport, String> {
+    let policy = RetentionPolicy::from_config(&config_store.config());
+    let report = retention::sweep(&history_store, &policy, current_timestamp())
+        .map_err(|e| e.to_string())?;
+
+    if !report.is_empty() {
+        if let Some(window) = app.get_window("main") {
+            window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+async fn get_dictation_stats(
+    history_store: State<'_, Arc<HistoryStore>>,
+    since: Option<i64>,
+) -> Result<DictationStats, String> {
+    let history = history_store.load().map_err(|e| e.to_string())?;
+    Ok(stats::compute(&history, since))
+}
+
+#[tauri::command]
+async fn get_cost_summary(
+    config_store: State<'_, Arc<ConfigStore>>,
+    cost_ledger: State<'_, Arc<CostLedger>>,
+) -> Result<CostSummary, String> {
+    let policy = CostPolicy::from_config(&config_store.config());
+    Ok(cost_ledger.summary(&policy, current_timestamp()))
+}
+
+#[tauri::command]
+async fn get_price_table(
+    config_store: State<'_, Arc<ConfigStore>>,
+) -> Result<std::collections::HashMap<String, ModelPrice>, String> {
+    Ok(CostPolicy::from_config(&config_store.config()).prices)
+}
+
+/// Merge a history.json from another install (e.g. the Electron build) into ours
+#[tauri::command]
+async fn import_recording_history(
+    app: AppHandle,
+    history_store: State<'_, Arc<HistoryStore>>,
+    history_path: String,
+) -> Result<usize, String> {
+    let history_path = std::path::PathBuf::from(history_path);
+    let incoming = history_store.read_history_at(&history_path).map_err(|e| e.to_string())?;
+    let added = history_store
+        .import(incoming, history_path.parent())
+        .map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(added)
+}
+
+// ===== BACKUP & TRASH =====
+
+#[tauri::command]
+async fn create_backup(app: AppHandle, destination: String) -> Result<BackupManifest, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    backup::create_backup(&app_data_dir, std::path::Path::new(&destination))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn verify_backup(archive: String) -> Result<BackupManifest, String> {
+    backup::verify_backup(std::path::Path::new(&archive)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn restore_backup(
+    app: AppHandle,
+    config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+    trash: State<'_, Arc<Trash>>,
+    vault: State<'_, Arc<Vault>>,
+    archive: String,
+    mode: RestoreMode,
+) -> Result<RestoreReport, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let report = backup::restore_backup(
+        &app_data_dir,
+        std::path::Path::new(&archive),
+        mode,
+        &vault,
+        &config_store,
+        &history_store,
+        &trash,
+    ).map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+async fn get_trash_entries(trash: State<'_, Arc<Trash>>) -> Result<Vec<TrashEntry>, String> {
+    trash.list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn restore_trash_entry(
+    app: AppHandle,
+    config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+    trash: State<'_, Arc<Trash>>,
+    id: String,
+) -> Result<usize, String> {
+    let entry_dir = trash.entry_path(&id);
+    if !entry_dir.exists() {
+        return Err("Trash entry not found".to_string());
+    }
+
+    let (config, profiles) = backup::read_config_files(&entry_dir).map_err(|e| e.to_string())?;
+    if config.is_some() || profiles.is_some() {
+        config_store.restore(config, profiles).map_err(|e| e.to_string())?;
+    }
+
+    let incoming = history_store.read_history_at(&entry_dir.join(history::HISTORY_FILE))
+        .map_err(|e| e.to_string())?;
+    let added = history_store
+        .import(incoming, Some(&entry_dir))
+        .map_err(|e| e.to_string())?;
+
+    trash.remove(&id).map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(added)
+}
+
+#[tauri::command]
+async fn purge_trash(
+    trash: State<'_, Arc<Trash>>,
+    older_than_days: Option<u64>,
+) -> Result<Vec<String>, String> {
+    let days = older_than_days.unwrap_or(trash::DEFAULT_TRASH_RETENTION_DAYS);
+    trash.purge_older_than(std::time::Duration::from_secs(days * 24 * 60 * 60))
+        .map_err(|e| e.to_string())
+}
+
+fn stt_model(config: &Config) -> &str {
+    match config.stt_provider_id.as_str() {
+        "groq" => &config.groq_stt_model,
+        _ => &config.openai_stt_model,
+    }
+}
+
+fn emit_cost_warning(app: &AppHandle, message: &str) {
+    for label in ["main", "panel"] {
+        if let Some(window) = app.get_window(label) {
+            let _ = window.emit("cost-cap-warning", message);
+        }
+    }
+}
+
+/// Apply edits made to config.json or profiles.json outside the app. Invalid
+/// files are reported and the running config is kept.
+fn reload_config_from_disk(app: &AppHandle) {
+    let config_store = app.state::<Arc<ConfigStore>>();
+
+    let changed = match config_store.external_changes() {
+        Ok(changed) if changed.is_empty() => return,
+        Ok(changed) => changed,
+        Err(e) => {
+            eprintln!("Failed to check config files: {}", e);
+            return;
+        }
+    };
+
+    match config_store.validate_files() {
+        Ok(errors) if !errors.is_empty() => {
+            for label in ["main", "panel"] {
+                if let Some(window) = app.get_window(label) {
+                    let _ = window.emit("config-invalid", &errors);
+                }
+            }
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to validate config files: {}", e);
+            return;
+        }
+    }
+
+    let previous_shortcut = config_store.config().recording_shortcut;
+    if let Err(e) = config_store.reload() {
+        eprintln!("Failed to reload config: {}", e);
+        return;
+    }
+
+    let recording_shortcut = config_store.config().recording_shortcut;
+    if recording_shortcut != previous_shortcut {
+        if let Err(e) = shortcuts::update_recording_shortcut(app, &recording_shortcut) {
+            eprintln!("Failed to register shortcut {}: {}", recording_shortcut, e);
+        }
+    }
+
+    let payload = serde_json::json!({
+        "files": changed,
+        "config": config_store.get_masked(),
+    });
+    for label in ["main", "panel"] {
+        if let Some(window) = app.get_window(label) {
+            let _ = window.emit("config-changed", &payload);
+        }
+    }
+}
+
+async fn transcribe_audio(config: &Config, audio_data: Vec<u8>) -> Result<String, String> {
+    let provider_id = config.stt_provider_id.as_str();
+
+    let model = stt_model(config);
+    let (api_key, base_url) = match provider_id {
+        "groq" => (&config.groq_api_key, &config.groq_base_url),
+        _ => (&config.openai_api_key, &config.openai_base_url),
     };
 
     if api_key.is_empty() {
         return Err("API key not configured".to_string());
     }
+    if vault::is_encrypted_string(api_key) {
+        return Err("The API key is encrypted; unlock the vault first".to_string());
+    }
 
     let client = reqwest::Client::new();
     let form = reqwest::multipart::Form::new()
@@ -411,26 +746,109 @@ async fn transcribe_audio(config: &serde_json::Value, audio_data: Vec<u8>) -> Re
 
 #[tauri::command]
 async fn get_config(config_store: State<'_, Arc<ConfigStore>>) -> Result<serde_json::Value, String> {
-    Ok(config_store.get())
+    Ok(config_store.get_masked())
 }
 
 #[tauri::command]
 async fn save_config(
     config_store: State<'_, Arc<ConfigStore>>,
     config: serde_json::Value,
-) -> Result<(), String> {
-    config_store.save(config).map_err(|e| e.to_string())
+) -> Result<(), SaveConfigError> {
+    config_store.save(config).map_err(SaveConfigError::from)
+}
+
+#[tauri::command]
+async fn get_config_load_errors(config_store: State<'_, Arc<ConfigStore>>) -> Result<Vec<FieldError>, String> {
+    Ok(config_store.load_errors())
+}
+
+/// Which config layer (defaults, system, user, environment, profile) set each key
+#[tauri::command]
+async fn explain_config(
+    config_store: State<'_, Arc<ConfigStore>>,
+    key: Option<String>,
+) -> Result<Vec<KeyOrigin>, String> {
+    Ok(config_store.explain(key.as_deref()))
+}
+
+// ===== VAULT =====
+
+#[tauri::command]
+async fn get_vault_status(vault: State<'_, Arc<Vault>>) -> Result<VaultStatus, String> {
+    Ok(vault.status())
+}
+
+#[tauri::command]
+async fn enable_vault(
+    vault: State<'_, Arc<Vault>>,
+    config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+    key_source: VaultKeySource,
+    passphrase: Option<String>,
+    key_file: Option<String>,
+) -> Result<VaultStatus, String> {
+    vault.enable(key_source, passphrase.as_deref(), key_file)
+        .map_err(|e| e.to_string())?;
+
+    config_store.rewrite_secrets(true).map_err(|e| e.to_string())?;
+    history_store.rewrite_all(true).map_err(|e| e.to_string())?;
+
+    Ok(vault.status())
+}
+
+#[tauri::command]
+async fn disable_vault(
+    vault: State<'_, Arc<Vault>>,
+    config_store: State<'_, Arc<ConfigStore>>,
+    history_store: State<'_, Arc<HistoryStore>>,
+) -> Result<VaultStatus, String> {
+    if !vault.status().unlocked {
+        return Err("Unlock the vault before disabling it".to_string());
+    }
+
+    history_store.rewrite_all(false).map_err(|e| e.to_string())?;
+    config_store.rewrite_secrets(false).map_err(|e| e.to_string())?;
+    vault.disable().map_err(|e| e.to_string())?;
+
+    Ok(vault.status())
+}
+
+#[tauri::command]
+async fn unlock_vault(
+    app: AppHandle,
+    vault: State<'_, Arc<Vault>>,
+    passphrase: String,
+) -> Result<VaultStatus, String> {
+    vault.unlock(&passphrase).map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_window("main") {
+        window.emit("refresh-recording-history", ()).map_err(|e| e.to_string())?;
+    }
+
+    Ok(vault.status())
+}
+
+#[tauri::command]
+async fn lock_vault(vault: State<'_, Arc<Vault>>) -> Result<VaultStatus, String> {
+    vault.lock();
+    Ok(vault.status())
 }
 
 // ===== STATE MANAGEMENT =====
 
 #[tauri::command]
 async fn record_event(
+    config_store: State<'_, Arc<ConfigStore>>,
     app_state: State<'_, Arc<AppState>>,
     event_type: String,
 ) -> Result<(), String> {
-    let mut is_recording = app_state.is_recording.lock().unwrap();
-    *is_recording = event_type == "start";
+    let starting = event_type == "start";
+    *app_state.is_recording.lock().unwrap() = starting;
+
+    if starting {
+        let target = recording_target(&config_store, &app_state);
+        *app_state.recording_target.lock().unwrap() = Some(target);
+    }
     Ok(())
 }
 
@@ -460,8 +878,9 @@ async fn create_profile(
     name: String,
     description: Option<String>,
     base_config: Option<serde_json::Value>,
+    parent_id: Option<String>,
 ) -> Result<SettingsProfile, String> {
-    config_store.create_profile(name, description, base_config)
+    config_store.create_profile(name, description, base_config, parent_id)
         .map_err(|e| e.to_string())
 }
 
@@ -493,6 +912,40 @@ async fn switch_profile(
         .map_err(|e| e.to_string())
 }
 
+/// Whether a profile is pinned against activation rules, and why the
+/// active profile was last switched
+#[tauri::command]
+async fn get_profile_switch_state(config_store: State<'_, Arc<ConfigStore>>) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "activeProfileId": config_store.get_active_profile_id(),
+        "pinned": config_store.is_profile_pinned(),
+        "lastSwitch": config_store.last_profile_switch(),
+    }))
+}
+
+#[tauri::command]
+async fn set_profile_pinned(config_store: State<'_, Arc<ConfigStore>>, pinned: bool) -> Result<(), String> {
+    config_store.set_profile_pinned(pinned)
+        .map_err(|e| e.to_string())
+}
+
+/// A profile's own overrides, the config it gives once active and how that
+/// differs from the config without profiles
+#[tauri::command]
+async fn get_profile_effective_config(
+    config_store: State<'_, Arc<ConfigStore>>,
+    profile_id: String,
+) -> Result<ProfileEffectiveConfig, String> {
+    config_store.profile_effective(&profile_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn deactivate_profile(config_store: State<'_, Arc<ConfigStore>>) -> Result<(), String> {
+    config_store.clear_active_profile()
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn duplicate_profile(
     config_store: State<'_, Arc<ConfigStore>>,
@@ -506,6 +959,7 @@ async fn duplicate_profile(
             new_name,
             Some(format!("Copy of {}", profile.description.as_deref().unwrap_or(&profile.name))),
             Some(profile.config.clone()),
+            profile.parent_id.clone(),
         ).map_err(|e| e.to_string())?;
         Ok(Some(new_profile))
     } else {
@@ -513,6 +967,29 @@ async fn duplicate_profile(
     }
 }
 
+/// Write profiles, with their app rules and glossary, to a `.json` or
+/// `.toml` bundle that can be imported on another machine
+#[tauri::command]
+async fn export_profiles(
+    config_store: State<'_, Arc<ConfigStore>>,
+    profile_ids: Vec<String>,
+    destination: String,
+    secrets: SecretHandling,
+) -> Result<usize, String> {
+    config_store.export_profiles(&profile_ids, std::path::Path::new(&destination), secrets)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn import_profiles(
+    config_store: State<'_, Arc<ConfigStore>>,
+    source: String,
+    on_conflict: ImportConflict,
+) -> Result<ImportReport, String> {
+    config_store.import_profiles(std::path::Path::new(&source), on_conflict)
+        .map_err(|e| e.to_string())
+}
+
 // ===== APP RULES =====
 
 #[tauri::command]
@@ -522,32 +999,55 @@ async fn get_active_application(app_state: State<'_, Arc<AppState>>) -> Result<O
 }
 
 #[tauri::command]
-async fn update_active_application(app_state: State<'_, Arc<AppState>>) -> Result<(), String> {
+async fn update_active_application(
+    config_store: State<'_, Arc<ConfigStore>>,
+    app_state: State<'_, Arc<AppState>>,
+) -> Result<(), String> {
     let app = platform::get_active_application().map_err(|e| e.to_string())?;
-    let mut active_app = app_state.active_app.lock().unwrap();
-    *active_app = Some(app);
+    *app_state.active_app.lock().unwrap() = Some(app);
+    resolve_active_rule(&config_store, &app_state);
     Ok(())
 }
 
+/// Pick the app rule for the focused application and remember it as the active rule
+fn resolve_active_rule(config_store: &ConfigStore, app_state: &AppState) -> RuleResolution {
+    let config = config_store.config();
+    let resolution = app_rules::resolve(&config, app_state.active_app.lock().unwrap().as_ref());
+    *app_state.active_rule.lock().unwrap() = resolution.rule.clone();
+    resolution
+}
+
+/// The focused application and its app rule, to pin for a recording. The
+/// platform is asked directly, since the watcher only reports an app once it
+/// has kept focus for a while; its last report is the fallback.
+fn recording_target(config_store: &ConfigStore, app_state: &AppState) -> RecordingTarget {
+    let app = platform::get_active_application()
+        .ok()
+        .filter(|app| !app_watcher::is_own_application(app))
+        .or_else(|| app_state.active_app.lock().unwrap().clone());
+    let resolution = app_rules::resolve(&config_store.config(), app.as_ref());
+    *app_state.active_rule.lock().unwrap() = resolution.rule.clone();
+    RecordingTarget { resolution, app }
+}
+
+/// Config with the active app rule's overrides applied
 #[tauri::command]
 async fn get_effective_config(
     config_store: State<'_, Arc<ConfigStore>>,
     app_state: State<'_, Arc<AppState>>,
 ) -> Result<serde_json::Value, String> {
-    let config = config_store.get();
-    let active_rule = app_state.active_rule.lock().unwrap();
-
-    if let Some(rule) = active_rule.as_ref() {
-        let mut effective = config.clone();
-        if let Some(obj) = effective.as_object_mut() {
-            if let Some(provider) = &rule.stt_provider_id {
-                obj.insert("sttProviderId".to_string(), serde_json::Value::String(provider.clone()));
-            }
-        }
-        Ok(effective)
-    } else {
-        Ok(config)
-    }
+    let resolution = resolve_active_rule(&config_store, &app_state);
+    let config = app_rules::effective_config(config_store.config(), resolution.rule.as_ref());
+    serde_json::to_value(config).map_err(|e| e.to_string())
+}
+
+/// Which app rule applies to the focused application and why
+#[tauri::command]
+async fn get_active_rule_resolution(
+    config_store: State<'_, Arc<ConfigStore>>,
+    app_state: State<'_, Arc<AppState>>,
+) -> Result<RuleResolution, String> {
+    Ok(resolve_active_rule(&config_store, &app_state))
 }
 
 #[tauri::command]
@@ -564,18 +1064,12 @@ async fn create_app_rule(
 
     let mut new_rule: AppRule = serde_json::from_value(rule).map_err(|e| e.to_string())?;
     new_rule.id = format!("rule_{}", timestamp);
+    app_rules::check_rule(&new_rule)?;
 
-    let mut config = config_store.get();
-    if let Some(obj) = config.as_object_mut() {
-        let mut app_rules: Vec<AppRule> = obj.get("appRules")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-
-        app_rules.push(new_rule.clone());
-        obj.insert("appRules".to_string(), serde_json::to_value(&app_rules).unwrap());
-    }
+    let mut config = config_store.config();
+    config.app_rules.push(new_rule.clone());
 
-    config_store.save(config).map_err(|e| e.to_string())?;
+    config_store.save_typed(&config).map_err(|e| e.to_string())?;
     Ok(new_rule)
 }
 
@@ -584,21 +1078,13 @@ async fn update_app_rule(
     config_store: State<'_, Arc<ConfigStore>>,
     rule: AppRule,
 ) -> Result<AppRule, String> {
-    let mut config = config_store.get();
-
-    if let Some(obj) = config.as_object_mut() {
-        let mut app_rules: Vec<AppRule> = obj.get("appRules")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-
-        if let Some(existing_rule) = app_rules.iter_mut().find(|r| r.id == rule.id) {
-            *existing_rule = rule.clone();
-        }
-
-        obj.insert("appRules".to_string(), serde_json::to_value(&app_rules).unwrap());
+    app_rules::check_rule(&rule)?;
+    let mut config = config_store.config();
+    if let Some(existing_rule) = config.app_rules.iter_mut().find(|r| r.id == rule.id) {
+        *existing_rule = rule.clone();
     }
 
-    config_store.save(config).map_err(|e| e.to_string())?;
+    config_store.save_typed(&config).map_err(|e| e.to_string())?;
     Ok(rule)
 }
 
@@ -607,29 +1093,16 @@ async fn delete_app_rule(
     config_store: State<'_, Arc<ConfigStore>>,
     id: String,
 ) -> Result<(), String> {
-    let mut config = config_store.get();
+    let mut config = config_store.config();
+    config.app_rules.retain(|r| r.id != id);
 
-    if let Some(obj) = config.as_object_mut() {
-        let mut app_rules: Vec<AppRule> = obj.get("appRules")
-            .and_then(|v| serde_json::from_value(v.clone()).ok())
-            .unwrap_or_default();
-
-        app_rules.retain(|r| r.id != id);
-        obj.insert("appRules".to_string(), serde_json::to_value(&app_rules).unwrap());
-    }
-
-    config_store.save(config).map_err(|e| e.to_string())?;
+    config_store.save_typed(&config).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 #[tauri::command]
 async fn get_app_rules(config_store: State<'_, Arc<ConfigStore>>) -> Result<Vec<AppRule>, String> {
-    let config = config_store.get();
-    let app_rules: Vec<AppRule> = config.get("appRules")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
-
-    Ok(app_rules)
+    Ok(config_store.config().app_rules)
 }
 
 #[tauri::command]
@@ -639,16 +1112,7 @@ async fn test_app_rule(
 ) -> Result<bool, String> {
     let active_app = app_state.active_app.lock().unwrap();
 
-    if let Some(app) = active_app.as_ref() {
-        let app_name_matches = app.name.to_lowercase().contains(&rule.app_name.to_lowercase());
-        let exe_matches = rule.executable.as_ref()
-            .map(|exe| app.executable.to_lowercase().contains(&exe.to_lowercase()))
-            .unwrap_or(true);
-
-        Ok(app_name_matches && exe_matches)
-    } else {
-        Ok(false)
-    }
+    Ok(active_app.as_ref().map(|app| app_rules::rule_matches(&rule, app).is_some()).unwrap_or(false))
 }
 
 // ===== VOICE ACTIVATION =====
@@ -757,8 +1221,7 @@ async fn test_fusion_configuration() -> Result<serde_json::Value, String> {
 
 #[tauri::command]
 async fn get_fusion_config(config_store: State<'_, Arc<ConfigStore>>) -> Result<serde_json::Value, String> {
-    let config = config_store.get();
-    Ok(config.get("fusionTranscription").cloned().unwrap_or(serde_json::json!({})))
+    serde_json::to_value(config_store.config().fusion_transcription).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -774,13 +1237,16 @@ async fn update_fusion_config(
 }
 
 #[tauri::command]
-async fn test_context_detection() -> Result<serde_json::Value, String> {
+async fn test_context_detection(config_store: State<'_, Arc<ConfigStore>>) -> Result<serde_json::Value, String> {
     let app_info = platform::get_active_application().ok();
+    let detection = app_info.as_ref().map(|app| detect_context(&config_store.config(), app));
 
     Ok(serde_json::json!({
         "appInfo": app_info,
-        "detectedContext": "generic",
-        "availableContexts": ["code-editor", "terminal", "email", "chat", "document", "browser", "notes", "generic"]
+        "detectedContext": detection.as_ref().map(|d| d.context.clone()),
+        "matchedRule": detection.as_ref().and_then(|d| d.rule.clone()),
+        "detection": detection,
+        "availableContexts": config::APPLICATION_CONTEXTS
     }))
 }
 
@@ -789,45 +1255,115 @@ async fn get_current_app_info() -> Result<Option<ActiveApplication>, String> {
     Ok(platform::get_active_application().ok())
 }
 
+/// The focused application plus which detection backend found it and why
+/// the others failed, for diagnosing detection on Wayland
 #[tauri::command]
-async fn detect_context_for_app(app_info: ActiveApplication) -> Result<String, String> {
-    let context = match app_info.executable.to_lowercase().as_str() {
-        name if name.contains("code") || name.contains("studio") => "code-editor",
-        name if name.contains("terminal") || name.contains("iterm") || name.contains("cmd") => "terminal",
-        name if name.contains("mail") || name.contains("outlook") => "email",
-        name if name.contains("slack") || name.contains("discord") || name.contains("teams") => "chat",
-        name if name.contains("word") || name.contains("docs") => "document",
-        name if name.contains("chrome") || name.contains("firefox") || name.contains("safari") => "browser",
-        name if name.contains("notes") || name.contains("notion") => "notes",
-        _ => "generic",
-    };
+async fn get_active_application_detection() -> Result<ActiveApplicationDetection, String> {
+    Ok(platform::detect_active_application())
+}
+
+#[tauri::command]
+async fn detect_context_for_app(
+    config_store: State<'_, Arc<ConfigStore>>,
+    app_info: ActiveApplication,
+) -> Result<String, String> {
+    Ok(detect_context(&config_store.config(), &app_info).context)
+}
+
+/// Classify the application against the built-in and user context rules,
+/// or use the fallback context when auto-detection is off
+fn detect_context(config: &Config, app: &ActiveApplication) -> ContextDetection {
+    let formatting = &config.context_formatting;
+    if !formatting.auto_detect_context {
+        return ContextDetection {
+            context: formatting.fallback_context.clone(),
+            rule: None,
+            reason: "context auto-detection is off".to_string(),
+            scores: vec![],
+        };
+    }
+    context::classify(&formatting.context_rules, app, &formatting.fallback_context)
+}
 
-    Ok(context.to_string())
+/// The context to format a transcript for, or `None` when context
+/// formatting is off. An app rule can force one via `contextFormatting.context`.
+fn formatting_context(config: &Config, app: Option<&ActiveApplication>) -> Option<String> {
+    let formatting = &config.context_formatting;
+    if !formatting.enabled {
+        return None;
+    }
+    if let Some(context) = &formatting.context {
+        return Some(context.clone());
+    }
+    Some(match app {
+        Some(app) => detect_context(config, app).context,
+        None => formatting.fallback_context.clone(),
+    })
 }
 
 #[tauri::command]
-async fn get_effective_formatting_config() -> Result<Option<serde_json::Value>, String> {
+async fn get_effective_formatting_config(
+    config_store: State<'_, Arc<ConfigStore>>,
+) -> Result<Option<serde_json::Value>, String> {
     if let Ok(app) = platform::get_active_application() {
-        let context = detect_context_for_app(app).await?;
+        let title_context = title_parsers(&config_store).parse(&app);
+        let detection = detect_context(&config_store.config(), &app);
         Ok(Some(serde_json::json!({
-            "context": context,
-            "enabled": true
+            "context": detection.context,
+            "enabled": true,
+            "titleContext": title_context
         })))
     } else {
         Ok(None)
     }
 }
 
+/// File, project and site context read from a window title; the focused
+/// window's when no application is given
+#[tauri::command]
+async fn parse_window_title(
+    config_store: State<'_, Arc<ConfigStore>>,
+    app_info: Option<ActiveApplication>,
+) -> Result<Option<TitleContext>, String> {
+    let app = match app_info {
+        Some(app) => app,
+        None => platform::get_active_application().map_err(|e| e.to_string())?,
+    };
+    Ok(title_parsers(&config_store).parse(&app))
+}
+
+/// The built-in title parsers plus the user's title patterns
+fn title_parsers(config_store: &ConfigStore) -> TitleParserRegistry {
+    TitleParserRegistry::with_patterns(&config_store.config().context_formatting.title_patterns)
+}
+
+/// Format `transcript` with the saved settings overlaid by `formatting_config`,
+/// which also names the `context` and, for code, the file's `language`
 #[tauri::command]
 async fn preview_context_formatting(
+    config_store: State<'_, Arc<ConfigStore>>,
     transcript: String,
     formatting_config: serde_json::Value,
 ) -> Result<serde_json::Value, String> {
+    let context = formatting_config.get("context").and_then(|v| v.as_str()).unwrap_or("generic");
+    let language = formatting_config.get("language").and_then(|v| v.as_str());
+    let mut formatting = serde_json::to_value(config_store.config().context_formatting).map_err(|e| e.to_string())?;
+    if let (Some(formatting), Some(overrides)) = (formatting.as_object_mut(), formatting_config.as_object()) {
+        formatting.extend(overrides.clone());
+    }
+    let formatting: ContextFormattingConfig = serde_json::from_value(formatting).map_err(|e| e.to_string())?;
+
+    let dictation = config_store.config().streaming_dictation;
+    let formatted = context::format_for_context(context, &transcript, &formatting, &dictation, language);
+    let deny_rule = match context {
+        "terminal" => context::dangerous_command(&formatted, &formatting.shell.deny_list),
+        _ => None,
+    };
+
     Ok(serde_json::json!({
-        "basicFormatted": transcript,
-        "aiPrompt": format!("Format this text for {} context: {}",
-            formatting_config.get("context").and_then(|v| v.as_str()).unwrap_or("generic"),
-            transcript),
+        "basicFormatted": formatted,
+        "confirmationRequired": deny_rule,
+        "aiPrompt": format!("Format this text for {} context: {}", context, transcript),
         "originalTranscript": transcript
     }))
 }
@@ -894,8 +1430,18 @@ async fn mcp_call_tool(
 #[tauri::command]
 async fn mcp_get_context(
     mcp_client: State<'_, Arc<McpClient>>,
+    config_store: State<'_, Arc<ConfigStore>>,
+    app_state: State<'_, Arc<AppState>>,
 ) -> Result<mcp::TranscriptionContext, String> {
-    mcp_client.get_transcription_context().await.map_err(|e| e.to_string())
+    let mut context = mcp_client.get_transcription_context().await.map_err(|e| e.to_string())?;
+
+    // Without an editor server, fall back to what the window title says
+    let active_app = app_state.active_app.lock().unwrap().clone();
+    if let Some(title_context) = active_app.as_ref().and_then(|app| title_parsers(&config_store).parse(app)) {
+        context.active_file = context.active_file.or(title_context.file);
+        context.project_context = context.project_context.or(title_context.project);
+    }
+    Ok(context)
 }
 
 #[tauri::command]
@@ -906,6 +1452,15 @@ async fn mcp_enhance_transcript(
     mcp_client.enhance_transcript(&transcript).await.map_err(|e| e.to_string())
 }
 
+fn current_timestamp() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
 // ===== SYSTEM TRAY =====
 
 fn create_system_tray() -> SystemTray {
@@ -961,18 +1516,56 @@ fn main() {
         .setup(|app| {
             let app_data_dir = app.path().app_data_dir().expect("Failed to get app data dir");
 
+            let vault = Arc::new(Vault::new(&app_data_dir));
+            if let Some(error) = vault.status().error {
+                eprintln!("Vault is locked: {}", error);
+            }
+            let history_store = Arc::new(HistoryStore::new(&app_data_dir, vault.clone()));
+            let trash = Arc::new(Trash::new(&app_data_dir));
+            let cost_ledger = Arc::new(CostLedger::new(&app_data_dir).expect("Failed to load cost ledger"));
+            if let Some(error) = cost_ledger.load_error() {
+                eprintln!("{}: {}", error.path, error.message);
+            }
             let config_store = Arc::new(
-                ConfigStore::new(app_data_dir).expect("Failed to initialize config store")
+                ConfigStore::new(app_data_dir, vault.clone()).expect("Failed to initialize config store")
             );
             let app_state = Arc::new(AppState::new());
 
+            let retention = std::time::Duration::from_secs(
+                trash::DEFAULT_TRASH_RETENTION_DAYS * 24 * 60 * 60,
+            );
+            if let Err(e) = trash.purge_older_than(retention) {
+                eprintln!("Failed to purge trash: {}", e);
+            }
+
             // Initialize MCP client
             let mcp_config = mcp::McpConfiguration::default();
             let mcp_client = Arc::new(McpClient::new(mcp_config));
 
+            // Whispo as an MCP server, serving its config and history to other apps
+            let mcp_server = Arc::new(
+                McpServer::new()
+                    .with_config_store(config_store.clone())
+                    .with_history_store(history_store.clone())
+            );
+            let server_settings = config_store.config().mcp.server;
+            if server_settings.enabled {
+                let server = mcp_server.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = mcp::http::serve(server, server_settings.port).await {
+                        eprintln!("Failed to start MCP server on port {}: {}", server_settings.port, e);
+                    }
+                });
+            }
+
+            app.manage(vault);
             app.manage(config_store);
+            app.manage(history_store);
+            app.manage(trash);
+            app.manage(cost_ledger);
             app.manage(app_state);
             app.manage(mcp_client);
+            app.manage(mcp_server);
 
             let app_handle = app.handle();
             let (tx, rx) = channel();
@@ -995,11 +1588,91 @@ fn main() {
                 }
             });
 
+            // Enforce retention policies in the background
+            let sweep_handle = app.handle();
+            std::thread::spawn(move || loop {
+                let config_store = sweep_handle.state::<Arc<ConfigStore>>();
+                let history_store = sweep_handle.state::<Arc<HistoryStore>>();
+                let policy = RetentionPolicy::from_config(&config_store.config());
+
+                match retention::sweep(&history_store, &policy, current_timestamp()) {
+                    Ok(report) if !report.is_empty() => {
+                        if let Some(window) = sweep_handle.get_window("main") {
+                            let _ = window.emit("retention-sweep", &report);
+                            let _ = window.emit("refresh-recording-history", ());
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Retention sweep failed: {}", e),
+                }
+
+                std::thread::sleep(std::time::Duration::from_secs(policy.sweep_interval_minutes * 60));
+            });
+
+            // Keep the focused application and its app rule current
+            let focus_handle = app.handle().clone();
+            app_watcher::watch(move |active_app| {
+                let config_store = focus_handle.state::<Arc<ConfigStore>>();
+                let app_state = focus_handle.state::<Arc<AppState>>();
+                *app_state.active_app.lock().unwrap() = Some(active_app.clone());
+                let resolution = resolve_active_rule(&config_store, &app_state);
+
+                let payload = serde_json::json!({
+                    "app": active_app,
+                    "rule": resolution,
+                });
+                for label in ["main", "panel"] {
+                    if let Some(window) = focus_handle.get_window(label) {
+                        let _ = window.emit("active-app-changed", &payload);
+                    }
+                }
+            });
+
+            // Switch profiles when their activation rules start or stop matching
+            let activation_handle = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(auto_profile::EVALUATE_INTERVAL);
+
+                let config_store = activation_handle.state::<Arc<ConfigStore>>();
+                let app_state = activation_handle.state::<Arc<AppState>>();
+                let profiles = config_store.get_profiles();
+                // Never change settings underneath a recording
+                if !auto_profile::has_rules(&profiles) || *app_state.is_recording.lock().unwrap() {
+                    continue;
+                }
+
+                let previous_shortcut = config_store.config().recording_shortcut;
+                match config_store.apply_activation_rules(&ActivationContext::current(&profiles)) {
+                    Ok(Some(switch)) => {
+                        let recording_shortcut = config_store.config().recording_shortcut;
+                        if recording_shortcut != previous_shortcut {
+                            if let Err(e) = shortcuts::update_recording_shortcut(&activation_handle, &recording_shortcut) {
+                                eprintln!("Failed to register shortcut {}: {}", recording_shortcut, e);
+                            }
+                        }
+                        for label in ["main", "panel"] {
+                            if let Some(window) = activation_handle.get_window(label) {
+                                let _ = window.emit("profile-switched", &switch);
+                            }
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Automatic profile switch failed: {}", e),
+                }
+            });
+
             // Register global shortcuts
             if let Err(e) = shortcuts::register_global_shortcuts(app.handle()) {
                 eprintln!("Failed to register shortcuts: {}", e);
             }
 
+            // Pick up edits made to config.json and profiles.json by other tools
+            let watch_handle = app.handle().clone();
+            let config_store = app.state::<Arc<ConfigStore>>();
+            if let Err(e) = config_store.watch(move || reload_config_from_disk(&watch_handle)) {
+                eprintln!("Failed to watch config files: {}", e);
+            }
+
             if platform::is_accessibility_granted() {
                 if let Some(window) = app.get_window("main") {
                     let _ = window.show();
@@ -1035,8 +1708,27 @@ fn main() {
             delete_recording_history,
             toggle_recording_transcript,
             create_recording,
+            set_recording_pinned,
+            run_retention_sweep,
+            get_dictation_stats,
+            get_cost_summary,
+            get_price_table,
+            import_recording_history,
+            create_backup,
+            verify_backup,
+            restore_backup,
+            get_trash_entries,
+            restore_trash_entry,
+            purge_trash,
             get_config,
             save_config,
+            get_config_load_errors,
+            explain_config,
+            get_vault_status,
+            enable_vault,
+            disable_vault,
+            unlock_vault,
+            lock_vault,
             record_event,
             get_recording_state,
             get_profiles,
@@ -1045,10 +1737,17 @@ fn main() {
             update_profile,
             delete_profile,
             switch_profile,
+            deactivate_profile,
+            get_profile_switch_state,
+            set_profile_pinned,
             duplicate_profile,
+            get_profile_effective_config,
+            export_profiles,
+            import_profiles,
             get_active_application,
             update_active_application,
             get_effective_config,
+            get_active_rule_resolution,
             create_app_rule,
             update_app_rule,
             delete_app_rule,
@@ -1072,9 +1771,11 @@ fn main() {
             update_fusion_config,
             test_context_detection,
             get_current_app_info,
+            get_active_application_detection,
             detect_context_for_app,
             get_effective_formatting_config,
             preview_context_formatting,
+            parse_window_title,
             register_shortcut,
             unregister_shortcuts,
             mcp_initialize,
@@ -0,0 +1,286 @@
+// Localhost HTTP transport for the MCP server: one JSON-RPC request per
+// POST to /mcp
+
+use super::server::McpServer;
+use super::types::{McpError, McpRequest, McpResponse};
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Accept connections on 127.0.0.1:`port` until the app exits
+pub async fn serve(server: Arc<McpServer>, port: u16) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("MCP server failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &server).await {
+                eprintln!("MCP server connection failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, server: &McpServer) -> Result<()> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+
+    let mut head = (&mut reader).take(MAX_HEAD_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line).await?;
+    let mut headers = Vec::new();
+    let complete = loop {
+        let mut line = String::new();
+        if head.read_line(&mut line).await? == 0 {
+            break false;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break true;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    };
+
+    let checked = if complete {
+        check_request(&request_line, &headers)
+    } else {
+        Err((400, "Bad Request"))
+    };
+    let length = match checked {
+        Ok(length) => length,
+        Err((status, reason)) => {
+            return write_response(&mut write_half, status, reason, reason.as_bytes()).await;
+        }
+    };
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    let response = match serde_json::from_slice::<McpRequest>(&body) {
+        Ok(request) => {
+            let id = request.id.clone();
+            server
+                .handle_request(request)
+                .await
+                .unwrap_or_else(|e| error_response(id, -32603, e.to_string()))
+        }
+        Err(e) => error_response(String::new(), -32700, format!("Parse error: {}", e)),
+    };
+    let body = serde_json::to_vec(&response)?;
+    write_response(&mut write_half, 200, "OK", &body).await
+}
+
+/// The body length a request may send, or the status to refuse it with.
+/// Only JSON posted to /mcp from a page served by localhost, if from a page
+/// at all, is taken: the Host check stops DNS rebinding and the content type
+/// keeps out form posts, which browsers send without asking first.
+fn check_request(
+    request_line: &str,
+    headers: &[(String, String)],
+) -> Result<usize, (u16, &'static str)> {
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+    );
+    if path != "/mcp" {
+        return Err((404, "Not Found"));
+    }
+    if method != "POST" {
+        return Err((405, "Method Not Allowed"));
+    }
+
+    if !header("host").map(is_local_host).unwrap_or(false) {
+        return Err((403, "Forbidden"));
+    }
+    if let Some(origin) = header("origin") {
+        let host = origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"));
+        if !host.map(is_local_host).unwrap_or(false) {
+            return Err((403, "Forbidden"));
+        }
+    }
+
+    let content_type = header("content-type").unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    if !media_type.eq_ignore_ascii_case("application/json") {
+        return Err((415, "Unsupported Media Type"));
+    }
+
+    let length = header("content-length")
+        .ok_or((411, "Length Required"))?
+        .parse::<usize>()
+        .map_err(|_| (400, "Bad Request"))?;
+    if length > MAX_BODY_BYTES {
+        return Err((413, "Payload Too Large"));
+    }
+    Ok(length)
+}
+
+/// "localhost" or the loopback address, with or without a port
+fn is_local_host(host: &str) -> bool {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    matches!(name, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn error_response(id: String, code: i32, message: String) -> McpResponse {
+    McpResponse {
+        jsonrpc: "2.0".to_string(),
+        id,
+        result: None,
+        error: Some(McpError {
+            code,
+            message,
+            data: None,
+        }),
+    }
+}
+
+async fn write_response(
+    stream: &mut (impl AsyncWriteExt + Unpin),
+    status: u16,
+    reason: &str,
+    body: &[u8],
+) -> Result<()> {
+    let content_type = if status == 200 {
+        "application/json"
+    } else {
+        "text/plain"
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn json_post(extra: &[(&str, &str)]) -> Vec<(String, String)> {
+        let mut pairs = vec![
+            ("host", "localhost:3000"),
+            ("content-type", "application/json"),
+            ("content-length", "42"),
+        ];
+        pairs.extend_from_slice(extra);
+        headers(&pairs)
+    }
+
+    #[test]
+    fn accepts_local_json_posts() {
+        assert_eq!(check_request("POST /mcp HTTP/1.1", &json_post(&[])), Ok(42));
+        let from_page = json_post(&[("origin", "http://127.0.0.1:5173")]);
+        assert_eq!(check_request("POST /mcp HTTP/1.1", &from_page), Ok(42));
+        let charset = headers(&[
+            ("host", "127.0.0.1"),
+            ("content-type", "application/json; charset=utf-8"),
+            ("content-length", "0"),
+        ]);
+        assert_eq!(check_request("POST /mcp HTTP/1.1", &charset), Ok(0));
+    }
+
+    #[test]
+    fn refuses_other_requests() {
+        let cases = [
+            ("GET /mcp HTTP/1.1", json_post(&[]), 405),
+            ("POST / HTTP/1.1", json_post(&[]), 404),
+            ("", json_post(&[]), 404),
+        ];
+        for (line, headers, status) in cases {
+            assert_eq!(
+                check_request(line, &headers).unwrap_err().0,
+                status,
+                "{:?}",
+                line
+            );
+        }
+
+        let line = "POST /mcp HTTP/1.1";
+        let rebound = headers(&[
+            ("host", "evil.example:3000"),
+            ("content-type", "application/json"),
+            ("content-length", "2"),
+        ]);
+        assert_eq!(check_request(line, &rebound).unwrap_err().0, 403);
+        let lookalike = headers(&[
+            ("host", "localhost.evil.example"),
+            ("content-type", "application/json"),
+            ("content-length", "2"),
+        ]);
+        assert_eq!(check_request(line, &lookalike).unwrap_err().0, 403);
+        let other_site = json_post(&[("origin", "https://evil.example")]);
+        assert_eq!(check_request(line, &other_site).unwrap_err().0, 403);
+        let form = headers(&[
+            ("host", "localhost"),
+            ("content-type", "text/plain"),
+            ("content-length", "2"),
+        ]);
+        assert_eq!(check_request(line, &form).unwrap_err().0, 415);
+        let no_length = headers(&[("host", "localhost"), ("content-type", "application/json")]);
+        assert_eq!(check_request(line, &no_length).unwrap_err().0, 411);
+        let huge = headers(&[
+            ("host", "localhost"),
+            ("content-type", "application/json"),
+            ("content-length", "999999999"),
+        ]);
+        assert_eq!(check_request(line, &huge).unwrap_err().0, 413);
+    }
+
+    #[test]
+    fn recognizes_local_hosts() {
+        for host in [
+            "localhost",
+            "localhost:3000",
+            "127.0.0.1:3000",
+            "[::1]:3000",
+            "[::1]",
+        ] {
+            assert!(is_local_host(host), "{:?}", host);
+        }
+        for host in [
+            "example.com",
+            "localhost.example.com:3000",
+            "127.0.0.2",
+            "0.0.0.0:3000",
+        ] {
+            assert!(!is_local_host(host), "{:?}", host);
+        }
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod types;
 pub mod client;
+pub mod http;
 pub mod server;
 pub mod tools;
 
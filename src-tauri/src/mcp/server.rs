@@ -1,5 +1,7 @@
 use super::tools::{get_whispo_tools, handle_tool_call};
 use super::types::*;
+use crate::config::ConfigStore;
+use crate::history::HistoryStore;
 use anyhow::Result;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
@@ -12,6 +14,8 @@ pub struct McpServer {
     resources: Vec<McpResource>,
     prompts: Vec<McpPrompt>,
     state: Arc<Mutex<ServerState>>,
+    config_store: Option<Arc<ConfigStore>>,
+    history_store: Option<Arc<HistoryStore>>,
 }
 
 struct ServerState {
@@ -47,9 +51,23 @@ impl McpServer {
                 initialized: false,
                 client_capabilities: None,
             })),
+            config_store: None,
+            history_store: None,
         }
     }
 
+    /// Serve resources from the app's config store instead of placeholders
+    pub fn with_config_store(mut self, config_store: Arc<ConfigStore>) -> Self {
+        self.config_store = Some(config_store);
+        self
+    }
+
+    /// Serve history-based resources such as `whispo://stats`
+    pub fn with_history_store(mut self, history_store: Arc<HistoryStore>) -> Self {
+        self.history_store = Some(history_store);
+        self
+    }
+
     /// Handle incoming MCP requests
     pub async fn handle_request(&self, request: McpRequest) -> Result<McpResponse> {
         match request.method.as_str() {
@@ -203,18 +221,40 @@ impl McpServer {
     async fn read_resource(&self, uri: &str) -> Result<ResourceContent> {
         // Parse URI and return appropriate resource
         match uri {
-            "whispo://config" => Ok(ResourceContent {
-                uri: uri.to_string(),
-                mime_type: "application/json".to_string(),
-                text: Some(serde_json::json!({"provider": "openai"}).to_string()),
-                blob: None,
-            }),
+            "whispo://config" => {
+                // Secrets are never exposed to MCP clients
+                let config = match &self.config_store {
+                    Some(store) => store.get_masked(),
+                    None => serde_json::json!({"provider": "openai"}),
+                };
+
+                Ok(ResourceContent {
+                    uri: uri.to_string(),
+                    mime_type: "application/json".to_string(),
+                    text: Some(config.to_string()),
+                    blob: None,
+                })
+            }
             "whispo://history" => Ok(ResourceContent {
                 uri: uri.to_string(),
                 mime_type: "application/json".to_string(),
                 text: Some(serde_json::json!({"items": []}).to_string()),
                 blob: None,
             }),
+            "whispo://stats" => {
+                let history = match &self.history_store {
+                    Some(store) => store.load()?,
+                    None => vec![],
+                };
+                let stats = crate::stats::compute(&history, None);
+
+                Ok(ResourceContent {
+                    uri: uri.to_string(),
+                    mime_type: "application/json".to_string(),
+                    text: Some(serde_json::to_string(&stats)?),
+                    blob: None,
+                })
+            }
             _ => anyhow::bail!("Unknown resource URI: {}", uri),
         }
     }
@@ -260,6 +300,12 @@ fn get_whispo_resources() -> Vec<McpResource> {
             description: Some("Recent transcription history".to_string()),
             mime_type: Some("application/json".to_string()),
         },
+        McpResource {
+            uri: "whispo://stats".to_string(),
+            name: "Dictation Statistics".to_string(),
+            description: Some("Words per day, audio minutes, latency and app usage".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
         McpResource {
             uri: "whispo://glossary".to_string(),
             name: "User Glossary".to_string(),
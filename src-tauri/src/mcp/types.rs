@@ -146,7 +146,7 @@ pub struct ProjectContext {
     pub framework: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GlossaryEntry {
     pub term: String,
     pub replacement: String,
@@ -0,0 +1,588 @@
+use crate::config::{LanguageRules, StreamingDictationConfig};
+use crate::itn::language_code;
+
+/// Built-in rules for one language. User rules from
+/// `streamingDictation.normalization.languages` are tried before these.
+struct Language {
+    code: &'static str,
+    /// Spoken phrase and the mark it's written as
+    punctuation: &'static [(&'static str, &'static str)],
+    fillers: &'static [&'static str],
+    /// Spoken before a word to capitalize it, in manual capitalization mode
+    capitalize: &'static [&'static str],
+    /// Spoken before a word to write it in capitals
+    all_caps: &'static [&'static str],
+    /// Write "i", "i'm" and so on as "I", "I'm"
+    capital_i: bool,
+}
+
+const ENGLISH: Language = Language {
+    code: "en",
+    punctuation: &[
+        ("comma", ","),
+        ("period", "."),
+        ("full stop", "."),
+        ("question mark", "?"),
+        ("exclamation mark", "!"),
+        ("exclamation point", "!"),
+        ("colon", ":"),
+        ("semicolon", ";"),
+        ("semi colon", ";"),
+        ("dot dot dot", "..."),
+        ("ellipsis", "..."),
+        ("hyphen", "-"),
+        ("dash", "—"),
+        ("open quote", "\""),
+        ("close quote", "\""),
+        ("end quote", "\""),
+        ("open paren", "("),
+        ("open parenthesis", "("),
+        ("close paren", ")"),
+        ("close parenthesis", ")"),
+        ("new line", "\n"),
+        ("newline", "\n"),
+        ("new paragraph", "\n\n"),
+    ],
+    fillers: &["um", "umm", "uh", "uhh", "uhm", "erm", "er", "hmm", "mm"],
+    capitalize: &["cap", "capital"],
+    all_caps: &["all caps"],
+    capital_i: true,
+};
+
+const GERMAN: Language = Language {
+    code: "de",
+    punctuation: &[
+        ("komma", ","),
+        ("punkt", "."),
+        ("fragezeichen", "?"),
+        ("ausrufezeichen", "!"),
+        ("doppelpunkt", ":"),
+        ("semikolon", ";"),
+        ("bindestrich", "-"),
+        ("neue zeile", "\n"),
+        ("neuer absatz", "\n\n"),
+    ],
+    fillers: &["äh", "ähm", "öh", "hm"],
+    capitalize: &["groß"],
+    all_caps: &["alles groß"],
+    capital_i: false,
+};
+
+const SPANISH: Language = Language {
+    code: "es",
+    punctuation: &[
+        ("coma", ","),
+        ("punto", "."),
+        ("punto y coma", ";"),
+        ("dos puntos", ":"),
+        ("abrir interrogación", "¿"),
+        ("cerrar interrogación", "?"),
+        ("abrir exclamación", "¡"),
+        ("cerrar exclamación", "!"),
+        ("nueva línea", "\n"),
+        ("nuevo párrafo", "\n\n"),
+    ],
+    fillers: &["eh", "em", "mmm"],
+    capitalize: &["mayúscula"],
+    all_caps: &["todo mayúsculas"],
+    capital_i: false,
+};
+
+const FRENCH: Language = Language {
+    code: "fr",
+    punctuation: &[
+        ("virgule", ","),
+        ("point", "."),
+        ("point d'interrogation", "?"),
+        ("point d'exclamation", "!"),
+        ("deux points", ":"),
+        ("point-virgule", ";"),
+        ("point virgule", ";"),
+        ("trait d'union", "-"),
+        ("à la ligne", "\n"),
+        ("nouvelle ligne", "\n"),
+        ("nouveau paragraphe", "\n\n"),
+    ],
+    fillers: &["euh", "heu", "hum"],
+    capitalize: &["majuscule"],
+    all_caps: &["tout en majuscules"],
+    capital_i: false,
+};
+
+/// Languages without built-in rules still get capitalization and
+/// whatever the user configures
+const OTHER: Language = Language {
+    code: "",
+    punctuation: &[],
+    fillers: &[],
+    capitalize: &[],
+    all_caps: &[],
+    capital_i: false,
+};
+
+const LANGUAGES: &[&Language] = &[&ENGLISH, &GERMAN, &SPANISH, &FRENCH];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Word(String),
+    Mark {
+        text: String,
+        attach_left: bool,
+        attach_right: bool,
+        /// Said out loud, as opposed to added by speech-to-text
+        spoken: bool,
+    },
+}
+
+impl Piece {
+    fn word(&self) -> Option<&str> {
+        match self {
+            Piece::Word(word) => Some(word),
+            Piece::Mark { .. } => None,
+        }
+    }
+
+    fn is_mark(&self, mark: &str) -> bool {
+        matches!(self, Piece::Mark { text, .. } if text == mark)
+    }
+
+    fn ends_sentence(&self) -> bool {
+        matches!(self, Piece::Mark { text, .. } if text.ends_with(['.', '!', '?', '\n']))
+    }
+}
+
+/// How a mark sits against the words around it
+fn mark(phrase: &str, text: &str, spoken: bool) -> Piece {
+    let (attach_left, attach_right) = if text.starts_with('\n') || matches!(text, "-" | "/") {
+        (true, true)
+    } else if phrase.starts_with("open ") || phrase.starts_with("abrir ") || matches!(text, "(" | "[" | "¿" | "¡") {
+        (false, true)
+    } else if matches!(text, "—" | "&") {
+        (false, false)
+    } else {
+        (true, false)
+    };
+    Piece::Mark {
+        text: text.to_string(),
+        attach_left,
+        attach_right,
+        spoken,
+    }
+}
+
+/// Words, with the punctuation speech-to-text put at their end split off
+fn pieces(text: &str) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    for word in text.split_whitespace() {
+        let stem = word.trim_end_matches(['.', ',', '!', '?', ';', ':']);
+        if !stem.is_empty() {
+            pieces.push(Piece::Word(stem.to_string()));
+        }
+        let trailing = &word[stem.len()..];
+        if !trailing.is_empty() {
+            pieces.push(mark("", trailing, false));
+        }
+    }
+    pieces
+}
+
+fn join(pieces: &[Piece]) -> String {
+    let mut output = String::new();
+    let mut attach_next = true;
+    for piece in pieces {
+        let (text, attach_left, attach_right) = match piece {
+            Piece::Word(word) => (word.as_str(), false, false),
+            Piece::Mark {
+                text,
+                attach_left,
+                attach_right,
+                ..
+            } => (text.as_str(), *attach_left, *attach_right),
+        };
+        if !(attach_next || attach_left) {
+            output.push(' ');
+        }
+        output.push_str(text);
+        attach_next = attach_right;
+    }
+    output
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn lower(word: &str) -> String {
+    word.to_lowercase()
+}
+
+/// Length in pieces of `phrase` if the words starting at `index` say it
+fn phrase_at(pieces: &[Piece], index: usize, phrase: &str) -> Option<usize> {
+    let parts: Vec<&str> = phrase.split(' ').collect();
+    let spoken = pieces.get(index..index + parts.len())?;
+    spoken
+        .iter()
+        .zip(&parts)
+        .all(|(piece, part)| piece.word().is_some_and(|word| lower(word) == lower(part)))
+        .then_some(parts.len())
+}
+
+/// The rules in effect for one transcript
+struct Rules<'a> {
+    language: &'a Language,
+    user: Option<&'a LanguageRules>,
+}
+
+impl Rules<'_> {
+    fn punctuation(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.user
+            .into_iter()
+            .flat_map(|user| user.spoken_punctuation.iter().map(|(p, m)| (p.as_str(), m.as_str())))
+            .chain(self.language.punctuation.iter().copied())
+    }
+
+    fn is_filler(&self, word: &str) -> bool {
+        let word = lower(word);
+        self.language.fillers.contains(&word.as_str())
+            || self
+                .user
+                .is_some_and(|user| user.fillers.iter().any(|filler| lower(filler) == word))
+    }
+}
+
+/// Drop fillers along with the comma speech-to-text puts after them
+fn remove_fillers(pieces: Vec<Piece>, rules: &Rules) -> Vec<Piece> {
+    let mut output: Vec<Piece> = Vec::with_capacity(pieces.len());
+    let mut skip_comma = false;
+    for piece in pieces {
+        if piece.word().is_some_and(|word| rules.is_filler(word)) {
+            skip_comma = true;
+            continue;
+        }
+        if skip_comma && piece.is_mark(",") {
+            skip_comma = false;
+            continue;
+        }
+        skip_comma = false;
+        output.push(piece);
+    }
+    // A filler between commas leaves one behind, and one that opened the
+    // transcript leaves nothing to attach to
+    while output.first().is_some_and(|piece| piece.is_mark(",")) {
+        output.remove(0);
+    }
+    output
+}
+
+/// Turn spoken punctuation into marks. In auto mode speech-to-text already
+/// punctuates, so only layout like "new paragraph" is taken from speech,
+/// and only when `spoken_layout` is on, since "a new line of products" is
+/// prose; in manual mode all of it is, and speech-to-text's own is dropped;
+/// off drops punctuation altogether.
+fn punctuate(pieces: Vec<Piece>, rules: &Rules, mode: &str, spoken_layout: bool) -> Vec<Piece> {
+    let keep_automatic = mode == "auto";
+    let spoken_kinds = |text: &str| match mode {
+        "auto" => spoken_layout && text.starts_with('\n'),
+        "manual" => true,
+        _ => false,
+    };
+    let phrases: Vec<(&str, &str)> = rules.punctuation().filter(|(_, text)| spoken_kinds(text)).collect();
+
+    let mut output = Vec::with_capacity(pieces.len());
+    let mut index = 0;
+    while index < pieces.len() {
+        let spoken = phrases
+            .iter()
+            .filter_map(|(phrase, text)| phrase_at(&pieces, index, phrase).map(|len| (phrase, text, len)))
+            .max_by_key(|(_, _, len)| *len);
+        if let Some((phrase, text, len)) = spoken {
+            output.push(mark(phrase, text, true));
+            index += len;
+            continue;
+        }
+        // Speech-to-text often puts its own period after a spoken "new line"
+        let after_spoken = matches!(output.last(), Some(Piece::Mark { spoken: true, .. }));
+        match &pieces[index] {
+            Piece::Mark { spoken: false, .. } if !keep_automatic || after_spoken => {}
+            piece => output.push(piece.clone()),
+        }
+        index += 1;
+    }
+    output
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// In auto mode every sentence starts with a capital; in manual mode only
+/// words the user asked for ("cap london", "all caps nasa") change
+fn capitalize_words(pieces: Vec<Piece>, rules: &Rules, mode: &str) -> Vec<Piece> {
+    match mode {
+        "auto" => {
+            let mut sentence_start = true;
+            pieces
+                .into_iter()
+                .map(|piece| match piece {
+                    Piece::Word(word) => {
+                        let is_i = rules.language.capital_i && (lower(&word) == "i" || lower(&word).starts_with("i'"));
+                        let word = if sentence_start || is_i {
+                            capitalize(&word)
+                        } else {
+                            word
+                        };
+                        sentence_start = false;
+                        Piece::Word(word)
+                    }
+                    mark => {
+                        sentence_start = sentence_start || mark.ends_sentence();
+                        mark
+                    }
+                })
+                .collect()
+        }
+        "manual" => {
+            let commands: Vec<(&str, bool)> = rules
+                .language
+                .capitalize
+                .iter()
+                .map(|phrase| (*phrase, false))
+                .chain(rules.language.all_caps.iter().map(|phrase| (*phrase, true)))
+                .collect();
+            let mut output = Vec::with_capacity(pieces.len());
+            let mut index = 0;
+            while index < pieces.len() {
+                let command = commands
+                    .iter()
+                    .filter_map(|(phrase, upper)| phrase_at(&pieces, index, phrase).map(|len| (*upper, len)))
+                    .max_by_key(|(_, len)| *len);
+                if let Some((upper, len)) = command {
+                    if let Some(Piece::Word(word)) = pieces.get(index + len) {
+                        output.push(Piece::Word(if upper { word.to_uppercase() } else { capitalize(word) }));
+                        index += len + 1;
+                        continue;
+                    }
+                }
+                output.push(pieces[index].clone());
+                index += 1;
+            }
+            output
+        }
+        _ => pieces,
+    }
+}
+
+/// Tidy a transcript the way `streamingDictation` asks: fillers dropped,
+/// spoken punctuation written and sentences capitalized, using the rules
+/// for `streamingDictation.language`
+pub fn normalize_transcript(text: &str, dictation: &StreamingDictationConfig) -> String {
+    let settings = &dictation.normalization;
+    if !settings.enabled {
+        return text.to_string();
+    }
+
+    let code = language_code(&dictation.language);
+    let rules = Rules {
+        language: LANGUAGES.iter().find(|l| l.code == code).copied().unwrap_or(&OTHER),
+        user: settings.languages.get(&code),
+    };
+
+    let mut pieces = pieces(text);
+    if settings.remove_fillers {
+        pieces = remove_fillers(pieces, &rules);
+    }
+    pieces = punctuate(pieces, &rules, &dictation.punctuation_mode, settings.spoken_layout);
+    pieces = capitalize_words(pieces, &rules, &dictation.capitalization_mode);
+    join(&pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_transcript;
+    use crate::config::{LanguageRules, StreamingDictationConfig};
+    use crate::itn::inverse_normalize;
+    use std::collections::BTreeMap;
+
+    fn dictation(language: &str, punctuation: &str, capitalization: &str) -> StreamingDictationConfig {
+        StreamingDictationConfig {
+            language: language.to_string(),
+            punctuation_mode: punctuation.to_string(),
+            capitalization_mode: capitalization.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn punctuation_and_capitalization_modes() {
+        // Language, punctuation mode, capitalization mode, transcript, expected
+        let cases = [
+            (
+                "en-US",
+                "auto",
+                "auto",
+                "Hello there. How are you?",
+                "Hello there. How are you?",
+            ),
+            (
+                "en-US",
+                "auto",
+                "auto",
+                "We need a new line of products.",
+                "We need a new line of products.",
+            ),
+            (
+                "en-US",
+                "auto",
+                "auto",
+                "the trial period ended",
+                "The trial period ended",
+            ),
+            (
+                "en-US",
+                "manual",
+                "auto",
+                "Hello comma how are you question mark. I'm fine period",
+                "Hello, how are you? I'm fine.",
+            ),
+            (
+                "en-US",
+                "manual",
+                "auto",
+                "dear sam comma new line thanks for the update exclamation point",
+                "Dear sam,\nThanks for the update!",
+            ),
+            (
+                "en-US",
+                "manual",
+                "auto",
+                "she said open quote hi close quote and left",
+                "She said \"hi\" and left",
+            ),
+            (
+                "en-US",
+                "off",
+                "auto",
+                "Well, this is it. Done!",
+                "Well this is it Done",
+            ),
+            ("en-US", "off", "off", "well comma this", "well comma this"),
+            ("en-US", "auto", "off", "hello. i think so", "hello. i think so"),
+            (
+                "en-US",
+                "auto",
+                "auto",
+                "hello. i think i'm right",
+                "Hello. I think I'm right",
+            ),
+            (
+                "en-US",
+                "manual",
+                "manual",
+                "meet cap london at all caps nasa",
+                "meet London at NASA",
+            ),
+            (
+                "de-DE",
+                "manual",
+                "auto",
+                "hallo komma wie geht's fragezeichen",
+                "Hallo, wie geht's?",
+            ),
+            (
+                "es-ES",
+                "manual",
+                "auto",
+                "abrir interrogación qué tal cerrar interrogación",
+                "¿Qué tal?",
+            ),
+            (
+                "fr-FR",
+                "manual",
+                "auto",
+                "bonjour virgule ça va point d'interrogation",
+                "Bonjour, ça va?",
+            ),
+        ];
+        for (language, punctuation, capitalization, text, expected) in cases {
+            let dictation = dictation(language, punctuation, capitalization);
+            assert_eq!(
+                normalize_transcript(text, &dictation),
+                expected,
+                "{:?} ({})",
+                text,
+                punctuation
+            );
+        }
+    }
+
+    #[test]
+    fn spoken_layout_in_auto_mode_is_opt_in() {
+        let mut dictation = dictation("en-US", "auto", "auto");
+        dictation.normalization.spoken_layout = true;
+        assert_eq!(
+            normalize_transcript("hello there new paragraph thanks", &dictation),
+            "Hello there\n\nThanks"
+        );
+        assert_eq!(
+            normalize_transcript("hello there, new line. thanks", &dictation),
+            "Hello there,\nThanks"
+        );
+    }
+
+    #[test]
+    fn numbers_are_written_after_punctuation() {
+        // In the order transcripts go through them, so manual mode doesn't
+        // drop the comma a date is written with
+        let dictation = dictation("en-US", "manual", "auto");
+        let normalized = normalize_transcript("born june twelfth, nineteen ninety nine period", &dictation);
+        assert_eq!(
+            inverse_normalize(&normalized, &dictation.language),
+            "Born June 12, 1999."
+        );
+    }
+
+    #[test]
+    fn fillers() {
+        let cases = [
+            ("en", "Um, so I think, uh, we should go.", "So I think, we should go."),
+            ("en", "uh hello", "Hello"),
+            ("en", "The umbrella is here.", "The umbrella is here."),
+            ("de", "Ähm, ich weiß nicht.", "Ich weiß nicht."),
+            ("fr", "Euh, je pense que oui.", "Je pense que oui."),
+        ];
+        for (language, text, expected) in cases {
+            assert_eq!(
+                normalize_transcript(text, &dictation(language, "auto", "auto")),
+                expected,
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn user_rules_per_language() {
+        let mut dictation = dictation("en-GB", "manual", "auto");
+        dictation.normalization.languages = BTreeMap::from([(
+            "en".to_string(),
+            LanguageRules {
+                spoken_punctuation: BTreeMap::from([("smiley".to_string(), ":)".to_string())]),
+                fillers: vec!["like".to_string()],
+                ..Default::default()
+            },
+        )]);
+        assert_eq!(
+            normalize_transcript("it was like great smiley", &dictation),
+            "It was great:)"
+        );
+
+        dictation.normalization.enabled = false;
+        assert_eq!(normalize_transcript("um hello comma", &dictation), "um hello comma");
+    }
+}
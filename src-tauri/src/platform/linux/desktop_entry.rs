@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The parts of a `.desktop` file needed to name a window's application
+#[derive(Debug, Clone)]
+struct DesktopEntry {
+    /// File name without `.desktop`, e.g. "org.gnome.Nautilus"
+    id: String,
+    name: String,
+    startup_wm_class: Option<String>,
+    /// File name of the program `Exec` runs
+    exec: Option<String>,
+}
+
+/// Installed desktop entries, read once since they rarely change while we run
+fn entries() -> &'static [DesktopEntry] {
+    static ENTRIES: OnceLock<Vec<DesktopEntry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        let mut entries = Vec::new();
+        for dir in application_dirs() {
+            let Ok(files) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let path = file.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("desktop") {
+                    entries.extend(parse(&path));
+                }
+            }
+        }
+        entries
+    })
+}
+
+/// `applications` under `$XDG_DATA_HOME` and each of `$XDG_DATA_DIRS`, in
+/// precedence order
+fn application_dirs() -> Vec<PathBuf> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    let data_home = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| format!("{}/.local/share", home));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    std::iter::once(data_home.as_str())
+        .chain(data_dirs.split(':'))
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join("applications"))
+        .collect()
+}
+
+fn parse(path: &Path) -> Option<DesktopEntry> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut in_main_section = false;
+    let mut name = None;
+    let mut startup_wm_class = None;
+    let mut exec = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "Name" => name = Some(value.trim().to_string()),
+            "StartupWMClass" => startup_wm_class = Some(value.trim().to_string()),
+            "Exec" => {
+                exec = value
+                    .split_whitespace()
+                    .find(|token| !token.contains('='))
+                    .and_then(|program| Path::new(program.trim_matches('"')).file_name())
+                    .map(|program| program.to_string_lossy().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    Some(DesktopEntry {
+        id: path.file_stem()?.to_string_lossy().to_string(),
+        name: name?,
+        startup_wm_class,
+        exec,
+    })
+}
+
+/// The user-facing name of an application, found by matching the window's
+/// WM_CLASS against `StartupWMClass` or the entry's file name, then the
+/// executable against `Exec`
+pub fn display_name(wm_class: Option<&str>, executable: Option<&str>) -> Option<String> {
+    let entries = entries();
+    let same = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+
+    if let Some(class) = wm_class.filter(|c| !c.is_empty()) {
+        let by_class = entries
+            .iter()
+            .find(|e| e.startup_wm_class.as_deref().is_some_and(|c| same(c, class)))
+            .or_else(|| {
+                entries
+                    .iter()
+                    .find(|e| same(&e.id, class) || e.id.rsplit('.').next().is_some_and(|last| same(last, class)))
+            });
+        if let Some(entry) = by_class {
+            return Some(entry.name.clone());
+        }
+    }
+
+    let executable = executable.filter(|e| !e.is_empty())?;
+    entries
+        .iter()
+        .find(|e| e.exec.as_deref().is_some_and(|exec| same(exec, executable)))
+        .map(|e| e.name.clone())
+}
@@ -0,0 +1,103 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use zbus::blocking::Connection;
+
+const WINDOW_CALLS_PATH: &str = "/org/gnome/Shell/Extensions/Windows";
+const WINDOW_CALLS_INTERFACE: &str = "org.gnome.Shell.Extensions.Windows";
+
+const EVAL_SCRIPT: &str = r#"(() => {
+    const w = global.display.focus_window;
+    return w ? JSON.stringify({ wm_class: w.get_wm_class(), title: w.get_title(), pid: w.get_pid() }) : "null";
+})()"#;
+
+/// GNOME Shell doesn't expose the focused window over D-Bus by default.
+/// Use the "Window Calls" extension if it's installed, otherwise
+/// `Shell.Eval`, which only works with unsafe mode enabled.
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let conn = Connection::session().context("Failed to connect to the session bus")?;
+
+    let from_extension = match window_calls(&conn) {
+        Ok(app) => return Ok(app),
+        Err(e) => e,
+    };
+    eval(&conn).map_err(|from_eval| {
+        anyhow::anyhow!(
+            "Window Calls extension: {:#}; Shell.Eval: {:#}",
+            from_extension,
+            from_eval
+        )
+    })
+}
+
+fn window_calls(conn: &Connection) -> Result<ActiveApplication> {
+    let list: String = conn
+        .call_method(
+            Some("org.gnome.Shell"),
+            WINDOW_CALLS_PATH,
+            Some(WINDOW_CALLS_INTERFACE),
+            "List",
+            &(),
+        )
+        .context("not installed")?
+        .body()
+        .deserialize()?;
+
+    let windows: Vec<Value> = serde_json::from_str(&list).context("Invalid window list")?;
+    let window = windows
+        .iter()
+        .find(|w| w["focus"].as_bool() == Some(true))
+        .context("no window has focus")?;
+
+    // Titles are only in the list on newer versions of the extension
+    let title = match window["title"].as_str() {
+        Some(title) => title.to_string(),
+        None => window["id"]
+            .as_u64()
+            .and_then(|id| {
+                conn.call_method(
+                    Some("org.gnome.Shell"),
+                    WINDOW_CALLS_PATH,
+                    Some(WINDOW_CALLS_INTERFACE),
+                    "GetTitle",
+                    &(id as u32,),
+                )
+                .ok()?
+                .body()
+                .deserialize::<String>()
+                .ok()
+            })
+            .unwrap_or_default(),
+    };
+
+    let pid = window["pid"].as_i64().filter(|pid| *pid > 0).map(|pid| pid as u32);
+    Ok(super::application(window["wm_class"].as_str(), title, pid))
+}
+
+fn eval(conn: &Connection) -> Result<ActiveApplication> {
+    let (success, result): (bool, String) = conn
+        .call_method(
+            Some("org.gnome.Shell"),
+            "/org/gnome/Shell",
+            Some("org.gnome.Shell"),
+            "Eval",
+            &(EVAL_SCRIPT,),
+        )
+        .context("GNOME Shell is not on the session bus")?
+        .body()
+        .deserialize()?;
+    if !success {
+        anyhow::bail!("disabled (GNOME Shell is not in unsafe mode)");
+    }
+
+    let window: Value = serde_json::from_str(&result).context("Invalid reply from GNOME Shell")?;
+    if window.is_null() {
+        anyhow::bail!("GNOME Shell reports no focused window");
+    }
+    let pid = window["pid"].as_i64().filter(|pid| *pid > 0).map(|pid| pid as u32);
+    Ok(super::application(
+        window["wm_class"].as_str(),
+        window["title"].as_str().unwrap_or_default().to_string(),
+        pid,
+    ))
+}
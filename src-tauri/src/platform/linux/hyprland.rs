@@ -0,0 +1,94 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Ask Hyprland's request socket for the active window as JSON
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .ok()
+        .context("HYPRLAND_INSTANCE_SIGNATURE is not set")?;
+
+    // Hyprland 0.40 moved the sockets from /tmp to the runtime dir
+    let mut sockets = Vec::new();
+    if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+        sockets.push(
+            PathBuf::from(runtime_dir)
+                .join("hypr")
+                .join(&signature)
+                .join(".socket.sock"),
+        );
+    }
+    sockets.push(PathBuf::from("/tmp/hypr").join(&signature).join(".socket.sock"));
+    let socket = sockets
+        .iter()
+        .find(|path| path.exists())
+        .with_context(|| format!("No Hyprland socket for instance {}", signature))?;
+
+    let mut stream =
+        UnixStream::connect(socket).with_context(|| format!("Failed to connect to {}", socket.display()))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.write_all(b"j/activewindow")?;
+    stream.shutdown(Shutdown::Write)?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+
+    let (class, title, pid) = parse_reply(&reply)?;
+    Ok(super::application(class.as_deref(), title, pid))
+}
+
+/// Class, title and PID from an `activewindow` reply
+fn parse_reply(reply: &str) -> Result<(Option<String>, String, Option<u32>)> {
+    // An empty object when nothing has focus
+    let window: Value = serde_json::from_str(reply).context("Invalid reply from Hyprland")?;
+    if window.get("address").is_none() {
+        anyhow::bail!("Hyprland reports no active window");
+    }
+
+    let class = window["class"].as_str().map(str::to_string);
+    let title = window["title"].as_str().unwrap_or_default().to_string();
+    let pid = window["pid"].as_i64().filter(|pid| *pid > 0).map(|pid| pid as u32);
+    Ok((class, title, pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_active_window() {
+        let reply = r#"{
+            "address": "0x55d1c2a0",
+            "class": "code",
+            "title": "main.rs - whispo - Visual Studio Code",
+            "pid": 4242,
+            "xwayland": false
+        }"#;
+        assert_eq!(
+            parse_reply(reply).unwrap(),
+            (
+                Some("code".to_string()),
+                "main.rs - whispo - Visual Studio Code".to_string(),
+                Some(4242)
+            )
+        );
+    }
+
+    #[test]
+    fn drops_missing_pids() {
+        let reply = r#"{ "address": "0x1", "class": "", "title": "", "pid": -1 }"#;
+        assert_eq!(parse_reply(reply).unwrap(), (Some(String::new()), String::new(), None));
+    }
+
+    #[test]
+    fn rejects_empty_and_invalid_replies() {
+        assert!(parse_reply("{}").is_err());
+        assert!(parse_reply("unknown request").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use zbus::blocking::Connection;
+
+const PLUGIN_NAME: &str = "whispo-active-window";
+const REPORT_PATH: &str = "/ActiveWindow";
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Resource class, caption and PID of the active window
+type Report = (String, String, i32);
+
+static WATCHER: Mutex<Option<Watcher>> = Mutex::new(None);
+
+/// Receives the active window from the KWin script
+struct Reporter(Sender<Report>);
+
+#[zbus::interface(name = "io.whispo.ActiveWindow")]
+impl Reporter {
+    fn report(&self, resource_class: String, caption: String, pid: i32) {
+        let _ = self.0.send((resource_class, caption, pid));
+    }
+}
+
+/// KWin only exposes the active window to scripts, so keep a script loaded
+/// that calls back over D-Bus whenever the active window or its caption
+/// changes, and answer from the latest report
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let mut watcher = WATCHER.lock().unwrap_or_else(|e| e.into_inner());
+
+    // KWin drops scripts when it restarts, so start over if ours is gone
+    if !watcher.as_ref().is_some_and(Watcher::is_loaded) {
+        *watcher = None;
+        *watcher = Some(Watcher::start()?);
+    }
+    let (class, title, pid) = watcher.as_mut().expect("watcher was just started").latest()?;
+
+    if class.is_empty() {
+        anyhow::bail!("KWin reports no active window");
+    }
+    Ok(super::application(Some(&class), title, u32::try_from(pid).ok()))
+}
+
+struct Watcher {
+    conn: Connection,
+    reports: Receiver<Report>,
+    latest: Option<Report>,
+    script_path: PathBuf,
+}
+
+impl Watcher {
+    fn start() -> Result<Self> {
+        let conn = Connection::session().context("Failed to connect to the session bus")?;
+        let (sender, reports) = mpsc::channel();
+        conn.object_server().at(REPORT_PATH, Reporter(sender))?;
+        let bus_name = conn
+            .unique_name()
+            .context("No unique name on the session bus")?
+            .to_string();
+
+        // `activeWindow` and `windowActivated` on Plasma 6, `activeClient` and
+        // `clientActivated` before
+        let script = format!(
+            r#"let current = null;
+function report() {{
+    callDBus("{bus}", "{path}", "io.whispo.ActiveWindow", "Report",
+        current ? String(current.resourceClass) : "", current ? String(current.caption) : "",
+        current ? current.pid : -1);
+}}
+function activated(w) {{
+    try {{ if (current) current.captionChanged.disconnect(report); }} catch (e) {{}}
+    current = w;
+    if (current) current.captionChanged.connect(report);
+    report();
+}}
+(workspace.windowActivated || workspace.clientActivated).connect(activated);
+activated(workspace.activeWindow || workspace.activeClient);"#,
+            bus = bus_name,
+            path = REPORT_PATH
+        );
+        let script_path = write_script(&script)?;
+
+        // Held from here so the file is removed if loading fails
+        let watcher = Watcher {
+            conn,
+            reports,
+            latest: None,
+            script_path,
+        };
+        watcher.run_script()?;
+        Ok(watcher)
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.conn
+            .call_method(
+                Some("org.kde.KWin"),
+                "/Scripting",
+                Some("org.kde.kwin.Scripting"),
+                "isScriptLoaded",
+                &(PLUGIN_NAME,),
+            )
+            .ok()
+            .and_then(|reply| reply.body().deserialize::<bool>().ok())
+            .unwrap_or(false)
+    }
+
+    /// The most recent report, waiting for the first one after loading
+    fn latest(&mut self) -> Result<Report> {
+        if let Some(report) = self.reports.try_iter().last() {
+            self.latest = Some(report);
+        }
+        if self.latest.is_none() {
+            let report = self
+                .reports
+                .recv_timeout(TIMEOUT)
+                .context("The KWin script did not report back")?;
+            self.latest = Some(report);
+        }
+        Ok(self.latest.clone().expect("a report was just stored"))
+    }
+
+    fn run_script(&self) -> Result<()> {
+        // A script left behind by an earlier run would block loading this one
+        self.unload_script();
+
+        let id: i32 = self
+            .conn
+            .call_method(
+                Some("org.kde.KWin"),
+                "/Scripting",
+                Some("org.kde.kwin.Scripting"),
+                "loadScript",
+                &(self.script_path.to_string_lossy().as_ref(), PLUGIN_NAME),
+            )
+            .context("KWin scripting is not available on the session bus")?
+            .body()
+            .deserialize()?;
+        if id < 0 {
+            anyhow::bail!("KWin refused to load the script");
+        }
+
+        // Plasma 6 moved script objects under /Scripting
+        let run = |path: String| {
+            self.conn.call_method(
+                Some("org.kde.KWin"),
+                path.as_str(),
+                Some("org.kde.kwin.Script"),
+                "run",
+                &(),
+            )
+        };
+        run(format!("/Scripting/Script{}", id))
+            .or_else(|_| run(format!("/{}", id)))
+            .context("Failed to run the KWin script")?;
+        Ok(())
+    }
+
+    fn unload_script(&self) {
+        let _ = self.conn.call_method(
+            Some("org.kde.KWin"),
+            "/Scripting",
+            Some("org.kde.kwin.Scripting"),
+            "unloadScript",
+            &(PLUGIN_NAME,),
+        );
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.unload_script();
+        let _ = std::fs::remove_file(&self.script_path);
+    }
+}
+
+/// Write the script where KWin can read it. The runtime dir is private to
+/// the user; the shared temp dir fallback is safe because `create_new`
+/// refuses to follow a link planted at the path.
+fn write_script(script: &str) -> Result<PathBuf> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let path = dir.join(format!("{}-{}.js", PLUGIN_NAME, std::process::id()));
+
+    // Left over from an earlier process with the same PID
+    let _ = std::fs::remove_file(&path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(script.as_bytes())?;
+    Ok(path)
+}
@@ -0,0 +1,167 @@
+mod desktop_entry;
+mod gnome;
+mod hyprland;
+mod kwin;
+mod sway;
+mod wlr;
+mod x11;
+
+use crate::types::{ActiveApplication, ActiveApplicationDetection, BackendFailure};
+use anyhow::Result;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source the focused window can be read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Sway,
+    Hyprland,
+    WlrForeignToplevel,
+    Kwin,
+    Gnome,
+    X11,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Sway => "sway-ipc",
+            Backend::Hyprland => "hyprland-ipc",
+            Backend::WlrForeignToplevel => "wlr-foreign-toplevel",
+            Backend::Kwin => "kwin-dbus",
+            Backend::Gnome => "gnome-shell-dbus",
+            Backend::X11 => "x11",
+        }
+    }
+
+    fn query(self) -> Result<ActiveApplication> {
+        match self {
+            Backend::Sway => sway::get_active_application(),
+            Backend::Hyprland => hyprland::get_active_application(),
+            Backend::WlrForeignToplevel => wlr::get_active_application(),
+            Backend::Kwin => kwin::get_active_application(),
+            Backend::Gnome => gnome::get_active_application(),
+            Backend::X11 => x11::get_active_application(None),
+        }
+    }
+}
+
+/// The backend that answered last, tried first on the next poll so the
+/// watcher doesn't probe every compositor each time
+static LAST_BACKEND: Mutex<Option<Backend>> = Mutex::new(None);
+
+/// "wayland", "x11" or "unknown", from `XDG_SESSION_TYPE` or, failing
+/// that, which display variables are set
+fn session_type() -> &'static str {
+    match std::env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => "wayland",
+        Ok("x11") => "x11",
+        _ if std::env::var_os("WAYLAND_DISPLAY").is_some() => "wayland",
+        _ if std::env::var_os("DISPLAY").is_some() => "x11",
+        _ => "unknown",
+    }
+}
+
+/// Backends worth trying in this session, most specific first. On Wayland
+/// X11 comes last: XWayland only knows about X11 clients.
+fn candidates(session_type: &str) -> &'static [Backend] {
+    match session_type {
+        "x11" => &[Backend::X11],
+        _ => &[
+            Backend::Sway,
+            Backend::Hyprland,
+            Backend::WlrForeignToplevel,
+            Backend::Kwin,
+            Backend::Gnome,
+            Backend::X11,
+        ],
+    }
+}
+
+/// Try each backend for this session until one finds the focused window,
+/// recording why the ones before it failed
+pub fn detect_active_application() -> ActiveApplicationDetection {
+    let session_type = session_type();
+    let mut failures = Vec::new();
+
+    for backend in candidates(session_type) {
+        match backend.query() {
+            Ok(app) => {
+                *LAST_BACKEND.lock().unwrap() = Some(*backend);
+                return ActiveApplicationDetection {
+                    app: Some(app),
+                    session_type: session_type.to_string(),
+                    backend: Some(backend.name().to_string()),
+                    failures,
+                };
+            }
+            Err(e) => failures.push(BackendFailure {
+                backend: backend.name().to_string(),
+                reason: format!("{:#}", e),
+            }),
+        }
+    }
+
+    *LAST_BACKEND.lock().unwrap() = None;
+    ActiveApplicationDetection {
+        app: None,
+        session_type: session_type.to_string(),
+        backend: None,
+        failures,
+    }
+}
+
+/// Get the active application on Linux, from whichever backend works in
+/// this session
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let last = *LAST_BACKEND.lock().unwrap();
+    if let Some(app) = last.and_then(|backend| backend.query().ok()) {
+        return Ok(app);
+    }
+
+    let detection = detect_active_application();
+    detection.app.ok_or_else(|| {
+        let reasons: Vec<String> = detection
+            .failures
+            .iter()
+            .map(|f| format!("{}: {}", f.backend, f.reason))
+            .collect();
+        anyhow::anyhow!(
+            "No backend could detect the active window in this {} session ({})",
+            detection.session_type,
+            reasons.join("; ")
+        )
+    })
+}
+
+/// Build the application from what a backend knows about the focused
+/// window: its X11 class or Wayland app id, its title and, if known, its PID
+fn application(class: Option<&str>, title: String, pid: Option<u32>) -> ActiveApplication {
+    let class = class.filter(|c| !c.is_empty());
+    let executable_path = pid.and_then(|pid| std::fs::read_link(format!("/proc/{}/exe", pid)).ok());
+    let executable = executable_path
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .or_else(|| pid.and_then(process_name))
+        .or_else(|| class.map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let name = desktop_entry::display_name(class, Some(&executable))
+        .or_else(|| class.map(str::to_string))
+        .unwrap_or_else(|| executable.clone());
+
+    ActiveApplication {
+        name,
+        executable,
+        executable_path: executable_path.map(|path| path.display().to_string()),
+        title,
+        last_updated: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64,
+    }
+}
+
+/// Fallback for processes whose executable we may not read, e.g. another user's
+fn process_name(pid: u32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim().to_string()).filter(|name| !name.is_empty())
+}
@@ -0,0 +1,137 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+const MAGIC: &[u8] = b"i3-ipc";
+const GET_TREE: u32 = 4;
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Ask sway over its IPC socket for the layout tree and take the focused
+/// window from it
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let socket = std::env::var("SWAYSOCK").ok().context("SWAYSOCK is not set")?;
+    let mut stream = UnixStream::connect(&socket).with_context(|| format!("Failed to connect to {}", socket))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+
+    // Header: magic, payload length, message type, all native-endian
+    let mut request = MAGIC.to_vec();
+    request.extend(0u32.to_ne_bytes());
+    request.extend(GET_TREE.to_ne_bytes());
+    stream.write_all(&request)?;
+
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        anyhow::bail!("Unexpected reply on the sway socket");
+    }
+    let length = u32::from_ne_bytes(header[6..10].try_into()?) as usize;
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload)?;
+
+    let tree: Value = serde_json::from_slice(&payload).context("Invalid sway layout tree")?;
+    let window = focused_window(&tree).context("sway reports no focused window")?;
+    let (class, title, pid) = window_details(window);
+
+    Ok(super::application(class, title, pid))
+}
+
+/// Class, title and PID of a window node. Native Wayland clients have an
+/// app_id, XWayland ones an X11 class.
+fn window_details(window: &Value) -> (Option<&str>, String, Option<u32>) {
+    let class = window["app_id"]
+        .as_str()
+        .or_else(|| window["window_properties"]["class"].as_str());
+    let title = window["name"].as_str().unwrap_or_default().to_string();
+    let pid = window["pid"].as_u64().map(|pid| pid as u32);
+    (class, title, pid)
+}
+
+/// The focused container holding a window. A focused empty workspace has
+/// no window, so only `con` and `floating_con` nodes count.
+fn focused_window(node: &Value) -> Option<&Value> {
+    let is_window = matches!(node["type"].as_str(), Some("con") | Some("floating_con"));
+    if is_window && node["focused"].as_bool() == Some(true) {
+        return Some(node);
+    }
+
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(focused_window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tree(workspace: Value) -> Value {
+        json!({
+            "type": "root",
+            "nodes": [{
+                "type": "output",
+                "name": "eDP-1",
+                "nodes": [workspace]
+            }]
+        })
+    }
+
+    #[test]
+    fn finds_the_focused_window() {
+        let tree = tree(json!({
+            "type": "workspace",
+            "nodes": [
+                { "type": "con", "focused": false, "app_id": "kitty", "name": "~", "pid": 10 },
+                {
+                    "type": "con",
+                    "layout": "splitv",
+                    "nodes": [
+                        { "type": "con", "focused": true, "app_id": "firefox", "name": "Docs — Mozilla Firefox", "pid": 20 }
+                    ]
+                }
+            ]
+        }));
+
+        let window = focused_window(&tree).unwrap();
+        assert_eq!(
+            window_details(window),
+            (Some("firefox"), "Docs — Mozilla Firefox".to_string(), Some(20))
+        );
+    }
+
+    #[test]
+    fn finds_floating_windows() {
+        let tree = tree(json!({
+            "type": "workspace",
+            "nodes": [{ "type": "con", "focused": false, "app_id": "kitty", "pid": 10 }],
+            "floating_nodes": [{
+                "type": "floating_con",
+                "focused": true,
+                "app_id": null,
+                "window_properties": { "class": "Gimp" },
+                "name": "GNU Image Manipulation Program",
+                "pid": 30
+            }]
+        }));
+
+        let window = focused_window(&tree).unwrap();
+        assert_eq!(
+            window_details(window),
+            (Some("Gimp"), "GNU Image Manipulation Program".to_string(), Some(30))
+        );
+    }
+
+    #[test]
+    fn empty_focused_workspace_has_no_window() {
+        let tree = tree(json!({
+            "type": "workspace",
+            "focused": true,
+            "nodes": []
+        }));
+        assert!(focused_window(&tree).is_none());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use wayland_client::backend::ObjectId;
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+
+#[derive(Debug, Default)]
+struct Toplevel {
+    title: String,
+    app_id: String,
+    activated: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    manager: Option<ZwlrForeignToplevelManagerV1>,
+    toplevels: HashMap<ObjectId, Toplevel>,
+}
+
+impl Dispatch<WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        queue: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == ZwlrForeignToplevelManagerV1::interface().name {
+                state.manager = Some(registry.bind(name, version.min(3), queue, ()));
+            }
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for State {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        _: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // New handles are tracked through their own events
+    }
+
+    event_created_child!(State, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let toplevel = state.toplevels.entry(handle.id()).or_default();
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => toplevel.title = title,
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => toplevel.app_id = app_id,
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: flags } => {
+                // An array of native-endian u32 state values
+                toplevel.activated = flags
+                    .chunks_exact(4)
+                    .map(|value| u32::from_ne_bytes([value[0], value[1], value[2], value[3]]))
+                    .any(|value| value == zwlr_foreign_toplevel_handle_v1::State::Activated as u32);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.remove(&handle.id());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Find the activated toplevel through the wlr foreign-toplevel protocol,
+/// which wlroots compositors other than sway and Hyprland offer too. The
+/// protocol has no PIDs, so the executable is guessed from the app id.
+pub fn get_active_application() -> Result<ActiveApplication> {
+    let conn = Connection::connect_to_env().context("Failed to connect to the Wayland compositor")?;
+    let mut queue = conn.new_event_queue();
+    let handle = queue.handle();
+    conn.display().get_registry(&handle, ());
+
+    let mut state = State::default();
+    queue.roundtrip(&mut state)?;
+    let manager = state
+        .manager
+        .clone()
+        .context("The compositor does not offer zwlr_foreign_toplevel_manager_v1")?;
+
+    // The first roundtrip announces the toplevels, the second their state
+    queue.roundtrip(&mut state)?;
+    queue.roundtrip(&mut state)?;
+    manager.stop();
+
+    let active = state
+        .toplevels
+        .values()
+        .find(|toplevel| toplevel.activated)
+        .context("No toplevel is activated")?;
+
+    Ok(super::application(Some(&active.app_id), active.title.clone(), None))
+}
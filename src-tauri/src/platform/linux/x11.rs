@@ -0,0 +1,127 @@
+use crate::types::ActiveApplication;
+use anyhow::{Context, Result};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, Window};
+use x11rb::rust_connection::RustConnection;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_WM_PID,
+        _NET_WM_NAME,
+        UTF8_STRING,
+    }
+}
+
+/// Read the focused window straight from the X server. `display` overrides
+/// `$DISPLAY`, e.g. to point at a headless Xvfb server.
+pub fn get_active_application(display: Option<&str>) -> Result<ActiveApplication> {
+    let (conn, screen) = x11rb::connect(display).context("Failed to connect to the X server")?;
+    let root = conn.setup().roots[screen].root;
+    let atoms = Atoms::new(&conn)?.reply()?;
+
+    let window = property_u32(&conn, root, atoms._NET_ACTIVE_WINDOW, AtomEnum::WINDOW.into())?
+        .filter(|w| *w != 0)
+        .context("The window manager reports no active window")?;
+
+    let title = match property_string(&conn, window, atoms._NET_WM_NAME, atoms.UTF8_STRING)? {
+        Some(title) => title,
+        None => property_string(&conn, window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into())?.unwrap_or_default(),
+    };
+
+    // WM_CLASS holds two NUL-terminated strings: instance, then class
+    let wm_class = property_string(&conn, window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())?;
+    let mut class_parts = wm_class.as_deref().unwrap_or("").split('\0').filter(|s| !s.is_empty());
+    let instance = class_parts.next().map(str::to_string);
+    let class = class_parts.next().map(str::to_string).or(instance);
+
+    let pid = property_u32(&conn, window, atoms._NET_WM_PID, AtomEnum::CARDINAL.into())?;
+    Ok(super::application(class.as_deref(), title, pid))
+}
+
+fn property_u32(conn: &RustConnection, window: Window, property: Atom, kind: Atom) -> Result<Option<u32>> {
+    let reply = conn.get_property(false, window, property, kind, 0, 1)?.reply()?;
+    Ok(reply.value32().and_then(|mut values| values.next()))
+}
+
+fn property_string(conn: &RustConnection, window: Window, property: Atom, kind: Atom) -> Result<Option<String>> {
+    let reply = conn
+        .get_property(false, window, property, kind, 0, u32::MAX / 4)?
+        .reply()?;
+    if reply.format != 8 || reply.value.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&reply.value).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x11rb::protocol::xproto::{CreateWindowAux, PropMode, WindowClass};
+    use x11rb::wrapper::ConnectionExt as _;
+
+    /// Xvfb has no window manager, so the test plays one: it creates a
+    /// window, sets the properties a WM would and marks it active.
+    #[test]
+    #[ignore = "needs an X server, e.g. `xvfb-run cargo test -- --ignored`"]
+    fn reads_the_active_window_from_the_x_server() {
+        let (conn, screen) = x11rb::connect(None).unwrap();
+        let root = conn.setup().roots[screen].root;
+        let atoms = Atoms::new(&conn).unwrap().reply().unwrap();
+
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            0,
+            window,
+            root,
+            0,
+            0,
+            10,
+            10,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            AtomEnum::WM_CLASS,
+            AtomEnum::STRING,
+            b"whispo-test\0WhispoTest\0",
+        )
+        .unwrap();
+        conn.change_property8(
+            PropMode::REPLACE,
+            window,
+            atoms._NET_WM_NAME,
+            atoms.UTF8_STRING,
+            "Notes – draft".as_bytes(),
+        )
+        .unwrap();
+        conn.change_property32(
+            PropMode::REPLACE,
+            window,
+            atoms._NET_WM_PID,
+            AtomEnum::CARDINAL,
+            &[std::process::id()],
+        )
+        .unwrap();
+        conn.change_property32(
+            PropMode::REPLACE,
+            root,
+            atoms._NET_ACTIVE_WINDOW,
+            AtomEnum::WINDOW,
+            &[window],
+        )
+        .unwrap();
+        conn.sync().unwrap();
+
+        let app = get_active_application(None).unwrap();
+        let own_exe = std::env::current_exe().unwrap();
+        assert_eq!(app.title, "Notes – draft");
+        assert_eq!(app.name, "WhispoTest");
+        assert_eq!(app.executable_path, Some(own_exe.display().to_string()));
+    }
+}
@@ -44,6 +44,7 @@ pub fn get_active_application() -> Result<ActiveApplication> {
     Ok(ActiveApplication {
         name: parts[0].to_string(),
         executable,
+        executable_path: Some(parts[1].to_string()),
         title: parts[2].to_string(),
         last_updated: SystemTime::now()
             .duration_since(UNIX_EPOCH)
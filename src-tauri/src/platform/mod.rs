@@ -9,7 +9,7 @@ pub mod windows;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
-use crate::types::ActiveApplication;
+use crate::types::{ActiveApplication, ActiveApplicationDetection};
 use anyhow::Result;
 
 /// Get the currently active application
@@ -24,6 +24,34 @@ pub fn get_active_application() -> Result<ActiveApplication> {
     return linux::get_active_application();
 }
 
+/// Get the currently active application along with how it was found
+pub fn detect_active_application() -> ActiveApplicationDetection {
+    #[cfg(target_os = "linux")]
+    return linux::detect_active_application();
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let backend = std::env::consts::OS;
+        match get_active_application() {
+            Ok(app) => ActiveApplicationDetection {
+                app: Some(app),
+                session_type: backend.to_string(),
+                backend: Some(backend.to_string()),
+                failures: vec![],
+            },
+            Err(e) => ActiveApplicationDetection {
+                app: None,
+                session_type: backend.to_string(),
+                backend: None,
+                failures: vec![crate::types::BackendFailure {
+                    backend: backend.to_string(),
+                    reason: e.to_string(),
+                }],
+            },
+        }
+    }
+}
+
 /// Check if accessibility permissions are granted
 pub fn is_accessibility_granted() -> bool {
     #[cfg(target_os = "macos")]
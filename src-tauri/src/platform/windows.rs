@@ -51,15 +51,11 @@ pub fn get_active_application() -> Result<ActiveApplication> {
                 512,
             );
 
-            let exe_path = if exe_len > 0 {
-                String::from_utf16_lossy(&exe_buf[..exe_len as usize])
-            } else {
-                String::from("unknown.exe")
-            };
+            let exe_path = (exe_len > 0).then(|| String::from_utf16_lossy(&exe_buf[..exe_len as usize]));
 
             let executable = exe_path
-                .split('\\')
-                .last()
+                .as_deref()
+                .and_then(|path| path.split('\\').last())
                 .unwrap_or("unknown.exe")
                 .to_string();
 
@@ -71,6 +67,7 @@ pub fn get_active_application() -> Result<ActiveApplication> {
             Ok(ActiveApplication {
                 name: app_name,
                 executable,
+                executable_path: exe_path,
                 title,
                 last_updated: SystemTime::now()
                     .duration_since(UNIX_EPOCH)
@@ -0,0 +1,348 @@
+use crate::config::Config;
+use crate::history::HistoryStore;
+use crate::types::RecordingHistoryItem;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Retention settings read from the `retention` section of the config.
+/// A `None` limit means "keep forever".
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    pub enabled: bool,
+    pub audio_max_age_days: Option<u64>,
+    pub audio_max_total_bytes: Option<u64>,
+    pub transcript_max_age_days: Option<u64>,
+    pub sweep_interval_minutes: u64,
+}
+
+impl RetentionPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        let retention = &config.retention;
+
+        Self {
+            enabled: retention.enabled,
+            audio_max_age_days: retention.audio_max_age_days,
+            audio_max_total_bytes: retention.audio_max_total_gb.map(|gb| (gb * GB) as u64),
+            transcript_max_age_days: retention.transcript_max_age_days,
+            sweep_interval_minutes: retention.sweep_interval_minutes.max(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepReport {
+    pub swept_at: i64,
+    /// Recordings whose audio was deleted but whose transcript was kept
+    pub audio_removed: Vec<String>,
+    /// Recordings removed from history entirely
+    pub transcripts_removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+impl SweepReport {
+    pub fn is_empty(&self) -> bool {
+        self.audio_removed.is_empty() && self.transcripts_removed.is_empty()
+    }
+}
+
+/// Apply the retention policy to the history store. Pinned recordings are never
+/// touched, but their audio still counts towards the size limit. Audio files
+/// are only deleted once the history without them has been saved.
+pub fn sweep(history_store: &HistoryStore, policy: &RetentionPolicy, now: i64) -> Result<SweepReport> {
+    let mut report = SweepReport {
+        swept_at: now,
+        ..Default::default()
+    };
+
+    if !policy.enabled {
+        return Ok(report);
+    }
+
+    let doomed = history_store.update(|history| Ok(apply(history_store, history, policy, now, &mut report)))?;
+
+    // Try every file even if one fails, so a single error doesn't leave the
+    // rest behind without a history entry
+    let mut first_error = None;
+    for (path, size) in doomed {
+        match fs::remove_file(&path) {
+            Ok(()) => report.bytes_freed += size,
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => {
+                first_error.get_or_insert_with(|| {
+                    anyhow::Error::new(e).context(format!("Failed to delete {}", path.display()))
+                });
+            }
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(report),
+    }
+}
+
+/// The sweep itself, on the history as loaded under the store's lock. Returns
+/// the audio files to delete along with their sizes.
+fn apply(
+    history_store: &HistoryStore,
+    history: &mut Vec<RecordingHistoryItem>,
+    policy: &RetentionPolicy,
+    now: i64,
+    report: &mut SweepReport,
+) -> Vec<(PathBuf, u64)> {
+    let mut doomed: Vec<(PathBuf, u64)> = Vec::new();
+    let mut delete_audio = |id: &str| -> u64 {
+        let path = history_store.audio_path(id);
+        if doomed.iter().any(|(doomed_path, _)| *doomed_path == path) {
+            return 0;
+        }
+        match fs::metadata(&path) {
+            Ok(metadata) => {
+                doomed.push((path, metadata.len()));
+                metadata.len()
+            }
+            Err(_) => 0,
+        }
+    };
+
+    // 1. Transcripts past their maximum age go away together with their audio
+    if let Some(days) = policy.transcript_max_age_days {
+        let cutoff = now - days as i64 * DAY_MS;
+        let mut kept = Vec::with_capacity(history.len());
+        for item in history.drain(..) {
+            if !item.pinned.unwrap_or(false) && item.created_at < cutoff {
+                delete_audio(&item.id);
+                report.transcripts_removed.push(item.id);
+            } else {
+                kept.push(item);
+            }
+        }
+        *history = kept;
+    }
+
+    // 2. Audio past its maximum age
+    if let Some(days) = policy.audio_max_age_days {
+        let cutoff = now - days as i64 * DAY_MS;
+        for item in history.iter().filter(|i| !i.pinned.unwrap_or(false) && i.created_at < cutoff) {
+            if delete_audio(&item.id) > 0 {
+                report.audio_removed.push(item.id.clone());
+            }
+        }
+    }
+
+    // 3. Oldest audio first until the total fits the size limit
+    if let Some(max_bytes) = policy.audio_max_total_bytes {
+        let mut sizes: Vec<(i64, String, u64, bool)> = history
+            .iter()
+            .filter_map(|item| {
+                fs::metadata(history_store.audio_path(&item.id))
+                    .ok()
+                    .map(|m| (item.created_at, item.id.clone(), m.len(), item.pinned.unwrap_or(false)))
+            })
+            .collect();
+        sizes.sort_by_key(|(created_at, ..)| *created_at);
+
+        let mut total: u64 = sizes.iter().map(|(_, _, size, _)| size).sum();
+        // Audio already due from the age limit no longer counts
+        for (_, id, size, _) in &sizes {
+            if report.audio_removed.contains(id) {
+                total -= size;
+            }
+        }
+        for (_, id, _, pinned) in sizes {
+            if total <= max_bytes {
+                break;
+            }
+            if pinned {
+                continue;
+            }
+            let freed = delete_audio(&id);
+            if freed > 0 {
+                total -= freed;
+                report.audio_removed.push(id);
+            }
+        }
+    }
+
+    doomed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::Vault;
+    use std::sync::Arc;
+
+    const NOW: i64 = 1_000 * DAY_MS;
+
+    fn store(name: &str) -> (PathBuf, HistoryStore) {
+        let dir = std::env::temp_dir().join(format!("whispo-retention-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let vault = Arc::new(Vault::new(&dir));
+        let store = HistoryStore::new(&dir, vault);
+        (dir, store)
+    }
+
+    fn add(store: &HistoryStore, id: &str, age_days: i64, audio_bytes: usize, pinned: bool) {
+        let item = RecordingHistoryItem {
+            id: id.to_string(),
+            created_at: NOW - age_days * DAY_MS,
+            duration: 1.0,
+            transcript: id.to_string(),
+            original_transcript: None,
+            is_original_shown: None,
+            pinned: pinned.then_some(true),
+            provider_id: None,
+            latency_ms: None,
+            app_name: None,
+            cost_usd: None,
+        };
+        store.add(item, &vec![0; audio_bytes]).unwrap();
+    }
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            enabled: true,
+            audio_max_age_days: None,
+            audio_max_total_bytes: None,
+            transcript_max_age_days: None,
+            sweep_interval_minutes: 60,
+        }
+    }
+
+    fn ids(store: &HistoryStore) -> Vec<String> {
+        store.load().unwrap().into_iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn drops_audio_past_its_age() {
+        let (dir, store) = store("audio-age");
+        add(&store, "old", 40, 10, false);
+        add(&store, "new", 5, 10, false);
+
+        let policy = RetentionPolicy {
+            audio_max_age_days: Some(30),
+            ..policy()
+        };
+        let report = sweep(&store, &policy, NOW).unwrap();
+
+        assert_eq!(report.audio_removed, vec!["old"]);
+        assert!(report.transcripts_removed.is_empty());
+        assert_eq!(report.bytes_freed, 10);
+        assert_eq!(ids(&store), vec!["old", "new"]);
+        assert!(!store.audio_path("old").exists());
+        assert!(store.audio_path("new").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn drops_transcripts_past_their_age_with_their_audio() {
+        let (dir, store) = store("transcript-age");
+        add(&store, "old", 400, 10, false);
+        add(&store, "new", 5, 10, false);
+
+        let policy = RetentionPolicy {
+            transcript_max_age_days: Some(365),
+            audio_max_age_days: Some(30),
+            ..policy()
+        };
+        let report = sweep(&store, &policy, NOW).unwrap();
+
+        assert_eq!(report.transcripts_removed, vec!["old"]);
+        assert!(report.audio_removed.is_empty());
+        assert_eq!(report.bytes_freed, 10);
+        assert_eq!(ids(&store), vec!["new"]);
+        assert!(!store.audio_path("old").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn drops_the_oldest_audio_over_the_size_limit() {
+        let (dir, store) = store("size");
+        add(&store, "a", 3, 10, false);
+        add(&store, "b", 2, 10, false);
+        add(&store, "c", 1, 10, false);
+
+        let policy = RetentionPolicy {
+            audio_max_total_bytes: Some(15),
+            ..policy()
+        };
+        let report = sweep(&store, &policy, NOW).unwrap();
+
+        assert_eq!(report.audio_removed, vec!["a", "b"]);
+        assert_eq!(report.bytes_freed, 20);
+        assert_eq!(ids(&store), vec!["a", "b", "c"]);
+        assert!(store.audio_path("c").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn counts_audio_removed_for_age_towards_the_size_limit() {
+        let (dir, store) = store("age-and-size");
+        add(&store, "a", 40, 10, false);
+        add(&store, "b", 2, 10, false);
+
+        let policy = RetentionPolicy {
+            audio_max_age_days: Some(30),
+            audio_max_total_bytes: Some(10),
+            ..policy()
+        };
+        let report = sweep(&store, &policy, NOW).unwrap();
+
+        assert_eq!(report.audio_removed, vec!["a"]);
+        assert_eq!(report.bytes_freed, 10);
+        assert!(store.audio_path("b").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn keeps_pinned_recordings() {
+        let (dir, store) = store("pinned");
+        add(&store, "pinned", 400, 10, true);
+        add(&store, "a", 2, 10, false);
+        add(&store, "b", 1, 10, false);
+
+        let policy = RetentionPolicy {
+            audio_max_age_days: Some(30),
+            audio_max_total_bytes: Some(15),
+            transcript_max_age_days: Some(365),
+            ..policy()
+        };
+        let report = sweep(&store, &policy, NOW).unwrap();
+
+        // The pinned audio still counts towards the limit, so both others go
+        assert!(report.transcripts_removed.is_empty());
+        assert_eq!(report.audio_removed, vec!["a", "b"]);
+        assert_eq!(ids(&store), vec!["pinned", "a", "b"]);
+        assert!(store.audio_path("pinned").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn does_nothing_when_disabled() {
+        let (dir, store) = store("disabled");
+        add(&store, "old", 400, 10, false);
+
+        let policy = RetentionPolicy {
+            enabled: false,
+            transcript_max_age_days: Some(1),
+            ..policy()
+        };
+        assert!(sweep(&store, &policy, NOW).unwrap().is_empty());
+        assert_eq!(ids(&store), vec!["old"]);
+        assert!(store.audio_path("old").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,23 @@
+use crate::app_rules::RuleResolution;
 use crate::types::*;
 use std::sync::Mutex;
 
+/// The application that had focus when a recording started, and the app
+/// rule that applies to it
+#[derive(Debug, Clone)]
+pub struct RecordingTarget {
+    pub app: Option<ActiveApplication>,
+    pub resolution: RuleResolution,
+}
+
 pub struct AppState {
     pub is_recording: Mutex<bool>,
     pub voice_activation: Mutex<VoiceActivationStatus>,
     pub streaming_dictation: Mutex<StreamingDictationStatus>,
     pub active_app: Mutex<Option<ActiveApplication>>,
     pub active_rule: Mutex<Option<AppRule>>,
+    /// Set when a recording starts and taken when it is transcribed
+    pub recording_target: Mutex<Option<RecordingTarget>>,
 }
 
 impl AppState {
@@ -33,6 +44,7 @@ impl AppState {
             }),
             active_app: Mutex::new(None),
             active_rule: Mutex::new(None),
+            recording_target: Mutex::new(None),
         }
     }
 }
@@ -0,0 +1,303 @@
+use crate::types::RecordingHistoryItem;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+const TOP_APPS_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictationStats {
+    pub total_recordings: usize,
+    pub total_words: usize,
+    pub total_audio_minutes: f64,
+    pub words_per_day: Vec<DailyWords>,
+    pub providers: Vec<ProviderStats>,
+    pub post_processing: PostProcessingStats,
+    pub top_apps: Vec<AppUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyWords {
+    /// Local date as YYYY-MM-DD
+    pub date: String,
+    pub words: usize,
+    pub recordings: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStats {
+    pub provider_id: String,
+    pub recordings: usize,
+    pub audio_minutes: f64,
+    /// Only recordings made after latency tracking was added count here
+    pub average_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostProcessingStats {
+    pub processed_recordings: usize,
+    /// Average word-level edit distance between original and final transcript
+    pub average_edit_distance: f64,
+    /// Average edit distance divided by the original word count
+    pub average_edit_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsage {
+    pub app_name: String,
+    pub recordings: usize,
+    pub words: usize,
+}
+
+/// Aggregate statistics over history items created at or after `since` (ms)
+pub fn compute(history: &[RecordingHistoryItem], since: Option<i64>) -> DictationStats {
+    let items: Vec<&RecordingHistoryItem> = history
+        .iter()
+        .filter(|item| since.map(|s| item.created_at >= s).unwrap_or(true))
+        .collect();
+
+    let mut days: BTreeMap<String, DailyWords> = BTreeMap::new();
+    let mut providers: HashMap<String, (usize, f64, u64, usize)> = HashMap::new();
+    let mut apps: HashMap<String, AppUsage> = HashMap::new();
+    let mut post_processing = PostProcessingStats::default();
+    let mut total_edit_ratio = 0.0;
+    let mut total_words = 0;
+    let mut total_audio_minutes = 0.0;
+
+    for item in &items {
+        let words = word_count(&item.transcript);
+        let minutes = item.duration / 60_000.0;
+        total_words += words;
+        total_audio_minutes += minutes;
+
+        let date = local_date(item.created_at);
+        let day = days.entry(date.clone()).or_insert(DailyWords {
+            date,
+            words: 0,
+            recordings: 0,
+        });
+        day.words += words;
+        day.recordings += 1;
+
+        let provider_id = item.provider_id.clone().unwrap_or_else(|| "unknown".to_string());
+        let provider = providers.entry(provider_id).or_insert((0, 0.0, 0, 0));
+        provider.0 += 1;
+        provider.1 += minutes;
+        if let Some(latency) = item.latency_ms {
+            provider.2 += latency;
+            provider.3 += 1;
+        }
+
+        if let Some(app_name) = item.app_name.as_ref().filter(|n| !n.is_empty()) {
+            let app = apps.entry(app_name.clone()).or_insert(AppUsage {
+                app_name: app_name.clone(),
+                recordings: 0,
+                words: 0,
+            });
+            app.recordings += 1;
+            app.words += words;
+        }
+
+        if let Some(original) = &item.original_transcript {
+            let distance = word_edit_distance(original, &item.transcript);
+            post_processing.processed_recordings += 1;
+            post_processing.average_edit_distance += distance as f64;
+            total_edit_ratio += distance as f64 / word_count(original).max(1) as f64;
+        }
+    }
+
+    if post_processing.processed_recordings > 0 {
+        let count = post_processing.processed_recordings as f64;
+        post_processing.average_edit_distance /= count;
+        post_processing.average_edit_ratio = total_edit_ratio / count;
+    }
+
+    let mut providers: Vec<ProviderStats> = providers
+        .into_iter()
+        .map(|(provider_id, (recordings, audio_minutes, latency_sum, latency_count))| ProviderStats {
+            provider_id,
+            recordings,
+            audio_minutes,
+            average_latency_ms: (latency_count > 0).then(|| latency_sum as f64 / latency_count as f64),
+        })
+        .collect();
+    providers.sort_by(|a, b| b.recordings.cmp(&a.recordings).then(a.provider_id.cmp(&b.provider_id)));
+
+    let mut top_apps: Vec<AppUsage> = apps.into_values().collect();
+    top_apps.sort_by(|a, b| b.recordings.cmp(&a.recordings).then(a.app_name.cmp(&b.app_name)));
+    top_apps.truncate(TOP_APPS_LIMIT);
+
+    DictationStats {
+        total_recordings: items.len(),
+        total_words,
+        total_audio_minutes,
+        words_per_day: days.into_values().collect(),
+        providers,
+        post_processing,
+        top_apps,
+    }
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn local_date(timestamp_ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance over whitespace-separated words
+fn word_edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<&str> = a.split_whitespace().collect();
+    let b: Vec<&str> = b.split_whitespace().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, word_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, word_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(word_a != word_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A history and the rows it should produce
+    type Case<'a, Row> = (&'a [RecordingHistoryItem], &'a [Row]);
+
+    /// Local noon on the given day of May 2024, in ms
+    fn at(day: u32) -> i64 {
+        Local.with_ymd_and_hms(2024, 5, day, 12, 0, 0).single().unwrap().timestamp_millis()
+    }
+
+    fn item(created_at: i64, transcript: &str) -> RecordingHistoryItem {
+        RecordingHistoryItem {
+            id: created_at.to_string(),
+            created_at,
+            duration: 30_000.0,
+            transcript: transcript.to_string(),
+            original_transcript: None,
+            is_original_shown: None,
+            pinned: None,
+            provider_id: None,
+            latency_ms: None,
+            app_name: None,
+            cost_usd: None,
+        }
+    }
+
+    fn from(provider_id: &str, latency_ms: Option<u64>) -> RecordingHistoryItem {
+        RecordingHistoryItem {
+            provider_id: Some(provider_id.to_string()),
+            latency_ms,
+            ..item(at(1), "one two")
+        }
+    }
+
+    #[test]
+    fn words_per_day() {
+        let cases: &[Case<(&str, usize, usize)>] = &[
+            (&[], &[]),
+            (&[item(at(1), "one two three")], &[("2024-05-01", 3, 1)]),
+            (
+                &[item(at(2), "a b"), item(at(1), "c"), item(at(2), "  d  e   f ")],
+                &[("2024-05-01", 1, 1), ("2024-05-02", 5, 2)],
+            ),
+            (&[item(at(3), "")], &[("2024-05-03", 0, 1)]),
+        ];
+
+        for (history, expected) in cases {
+            let stats = compute(history, None);
+            let days: Vec<(&str, usize, usize)> =
+                stats.words_per_day.iter().map(|d| (d.date.as_str(), d.words, d.recordings)).collect();
+            assert_eq!(&days, expected);
+            assert_eq!(stats.total_words, expected.iter().map(|(_, words, _)| words).sum::<usize>());
+            assert_eq!(stats.total_recordings, history.len());
+        }
+    }
+
+    #[test]
+    fn since_filter() {
+        let history = [item(at(1), "a"), item(at(2), "b b"), item(at(3), "c c c")];
+        let cases = [(None, 3, 6), (Some(at(2)), 2, 5), (Some(at(2) + 1), 1, 3), (Some(at(4)), 0, 0)];
+
+        for (since, recordings, words) in cases {
+            let stats = compute(&history, since);
+            assert_eq!((stats.total_recordings, stats.total_words), (recordings, words), "since {:?}", since);
+        }
+    }
+
+    #[test]
+    fn provider_latency() {
+        let cases: &[Case<(&str, usize, Option<f64>)>] = &[
+            (&[from("groq", Some(100)), from("groq", Some(300))], &[("groq", 2, Some(200.0))]),
+            // Recordings from before latency tracking don't drag the average down
+            (&[from("groq", Some(100)), from("groq", None)], &[("groq", 2, Some(100.0))]),
+            (&[from("openai", None)], &[("openai", 1, None)]),
+            (
+                &[from("openai", Some(50)), from("groq", Some(10)), from("groq", Some(20))],
+                &[("groq", 2, Some(15.0)), ("openai", 1, Some(50.0))],
+            ),
+            (&[item(at(1), "a")], &[("unknown", 1, None)]),
+        ];
+
+        for (history, expected) in cases {
+            let stats = compute(history, None);
+            let providers: Vec<(&str, usize, Option<f64>)> = stats
+                .providers
+                .iter()
+                .map(|p| (p.provider_id.as_str(), p.recordings, p.average_latency_ms))
+                .collect();
+            assert_eq!(&providers, expected);
+        }
+    }
+
+    #[test]
+    fn edit_distance() {
+        let cases = [
+            ("", "", 0),
+            ("hello world", "hello world", 0),
+            ("hello world", "Hello world", 1),
+            ("hello world", "hello there world", 1),
+            ("the quick brown fox", "quick brown", 2),
+            ("a b c", "x y z", 3),
+            ("", "one two", 2),
+            ("one  two", "one two", 0),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(word_edit_distance(a, b), expected, "{:?} -> {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn post_processing_averages() {
+        let edited = |original: &str, transcript: &str| RecordingHistoryItem {
+            original_transcript: Some(original.to_string()),
+            ..item(at(1), transcript)
+        };
+        let history = [edited("a b c d", "a b c x"), edited("a b", "a b"), item(at(1), "untouched")];
+
+        let stats = compute(&history, None).post_processing;
+        assert_eq!(stats.processed_recordings, 2);
+        assert_eq!(stats.average_edit_distance, 0.5);
+        assert_eq!(stats.average_edit_ratio, 0.125);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::history::{HistoryStore, HISTORY_FILE};
+use crate::types::RecordingHistoryItem;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const TRASH_DIR: &str = "trash";
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+const TRASH_INFO_FILE: &str = "trash-info.json";
+
+/// A trash entry is a small snapshot of the app data directory: it may hold
+/// config.json, profiles.json and a history.json with the matching audio files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    pub id: String,
+    pub reason: String,
+    pub deleted_at: i64,
+    #[serde(default)]
+    pub recordings: usize,
+    #[serde(default)]
+    pub has_config: bool,
+}
+
+pub struct Trash {
+    trash_dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self {
+            trash_dir: app_data_dir.join(TRASH_DIR),
+        }
+    }
+
+    pub fn entry_path(&self, id: &str) -> PathBuf {
+        self.trash_dir.join(id)
+    }
+
+    /// Move the whole recordings folder into the trash, holding the history
+    /// lock so no recording is added to the folder while it moves
+    pub fn trash_recordings_folder(&self, history_store: &HistoryStore, reason: &str) -> Result<Option<TrashEntry>> {
+        history_store.exclusive(|| {
+            let recordings_folder = history_store.recordings_folder();
+            if !recordings_folder.exists() {
+                return Ok(None);
+            }
+            let recordings = history_store.load().map(|h| h.len()).unwrap_or(0);
+
+            fs::create_dir_all(&self.trash_dir).context("Failed to create trash directory")?;
+            let (id, deleted_at) = self.new_entry_id("recordings");
+            let entry_dir = self.entry_path(&id);
+
+            fs::rename(recordings_folder, &entry_dir)
+                .context("Failed to move recordings to trash")?;
+
+            self.write_info(&entry_dir, id, reason, deleted_at, recordings).map(Some)
+        })
+    }
+
+    /// Move a single recording (its history item and audio file) into the trash
+    pub fn trash_recording(
+        &self,
+        history_store: &HistoryStore,
+        item: &RecordingHistoryItem,
+        reason: &str,
+    ) -> Result<TrashEntry> {
+        let (id, deleted_at) = self.new_entry_id(&item.id);
+        let entry_dir = self.entry_path(&id);
+        fs::create_dir_all(&entry_dir).context("Failed to create trash entry")?;
+
+        let audio_path = history_store.audio_path(&item.id);
+        if audio_path.exists() {
+            fs::rename(audio_path, entry_dir.join(format!("{}.webm", item.id)))
+                .context("Failed to move audio file to trash")?;
+        }
+
+        history_store.write_history_at(&entry_dir.join(HISTORY_FILE), std::slice::from_ref(item))?;
+
+        self.write_info(&entry_dir, id, reason, deleted_at, 1)
+    }
+
+    /// Copy config files into the trash before they get overwritten
+    pub fn trash_config_files(&self, files: &[&Path], reason: &str) -> Result<Option<TrashEntry>> {
+        let existing: Vec<&&Path> = files.iter().filter(|f| f.exists()).collect();
+        if existing.is_empty() {
+            return Ok(None);
+        }
+
+        let (id, deleted_at) = self.new_entry_id("config");
+        let entry_dir = self.entry_path(&id);
+        fs::create_dir_all(&entry_dir).context("Failed to create trash entry")?;
+
+        for file in existing {
+            if let Some(name) = file.file_name() {
+                fs::copy(file, entry_dir.join(name))?;
+            }
+        }
+
+        self.write_info(&entry_dir, id, reason, deleted_at, 0).map(Some)
+    }
+
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        if !self.trash_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.trash_dir)? {
+            let path = dir_entry?.path();
+            let info_path = path.join(TRASH_INFO_FILE);
+            if !info_path.exists() {
+                continue;
+            }
+
+            let content = fs::read_to_string(&info_path)?;
+            if let Ok(entry) = serde_json::from_str::<TrashEntry>(&content) {
+                entries.push(entry);
+            }
+        }
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+        Ok(entries)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let entry_dir = self.entry_path(id);
+        if entry_dir.exists() {
+            fs::remove_dir_all(entry_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Permanently delete entries older than `max_age`. Returns the removed ids.
+    pub fn purge_older_than(&self, max_age: Duration) -> Result<Vec<String>> {
+        let cutoff = now_millis() - max_age.as_millis() as i64;
+        let mut purged = Vec::new();
+
+        for entry in self.list()? {
+            if entry.deleted_at < cutoff {
+                self.remove(&entry.id)?;
+                purged.push(entry.id);
+            }
+        }
+
+        Ok(purged)
+    }
+
+    fn new_entry_id(&self, label: &str) -> (String, i64) {
+        let deleted_at = now_millis();
+        let mut id = format!("{}-{}", deleted_at, label);
+        let mut suffix = 1;
+        while self.entry_path(&id).exists() {
+            id = format!("{}-{}-{}", deleted_at, label, suffix);
+            suffix += 1;
+        }
+        (id, deleted_at)
+    }
+
+    fn write_info(
+        &self,
+        entry_dir: &Path,
+        id: String,
+        reason: &str,
+        deleted_at: i64,
+        recordings: usize,
+    ) -> Result<TrashEntry> {
+        let entry = TrashEntry {
+            id,
+            reason: reason.to_string(),
+            deleted_at,
+            recordings,
+            has_config: entry_dir.join("config.json").exists()
+                || entry_dir.join("profiles.json").exists(),
+        };
+
+        fs::write(
+            entry_dir.join(TRASH_INFO_FILE),
+            serde_json::to_string_pretty(&entry)?,
+        )?;
+
+        Ok(entry)
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vault::Vault;
+    use std::sync::{mpsc, Arc};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("whispo-trash-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn item(id: &str) -> RecordingHistoryItem {
+        RecordingHistoryItem {
+            id: id.to_string(),
+            created_at: 0,
+            duration: 1.0,
+            transcript: String::new(),
+            original_transcript: None,
+            is_original_shown: None,
+            pinned: None,
+            provider_id: None,
+            latency_ms: None,
+            app_name: None,
+            cost_usd: None,
+        }
+    }
+
+    fn stores(dir: &Path) -> (Arc<HistoryStore>, Trash) {
+        let vault = Arc::new(Vault::new(dir));
+        (Arc::new(HistoryStore::new(dir, vault)), Trash::new(dir))
+    }
+
+    #[test]
+    fn trashes_the_recordings_folder() {
+        let dir = temp_dir("folder");
+        let (history_store, trash) = stores(&dir);
+        assert!(trash.trash_recordings_folder(&history_store, "delete-all").unwrap().is_none());
+
+        history_store.add(item("a"), b"audio a").unwrap();
+        history_store.add(item("b"), b"audio b").unwrap();
+
+        let entry = trash.trash_recordings_folder(&history_store, "delete-all").unwrap().unwrap();
+        assert_eq!(entry.recordings, 2);
+        assert_eq!(entry.reason, "delete-all");
+        assert!(!entry.has_config);
+        assert!(!history_store.recordings_folder().exists());
+
+        let entry_dir = trash.entry_path(&entry.id);
+        assert_eq!(history_store.read_history_at(&entry_dir.join(HISTORY_FILE)).unwrap().len(), 2);
+        assert!(entry_dir.join("a.webm").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn waits_for_a_history_update_in_progress() {
+        let dir = temp_dir("lock");
+        let (history_store, trash) = stores(&dir);
+        history_store.add(item("a"), b"audio a").unwrap();
+
+        let (started, wait_started) = mpsc::channel();
+        let updater = {
+            let history_store = history_store.clone();
+            std::thread::spawn(move || {
+                history_store
+                    .update(|history| {
+                        started.send(()).unwrap();
+                        std::thread::sleep(Duration::from_millis(100));
+                        history.push(item("b"));
+                        Ok(())
+                    })
+                    .unwrap()
+            })
+        };
+        wait_started.recv().unwrap();
+
+        // Both recordings go to the trash; neither is left behind in a
+        // recreated recordings folder
+        let entry = trash.trash_recordings_folder(&history_store, "delete-all").unwrap().unwrap();
+        updater.join().unwrap();
+        assert_eq!(entry.recordings, 2);
+        assert!(history_store.load().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn trashes_a_single_recording() {
+        let dir = temp_dir("single");
+        let (history_store, trash) = stores(&dir);
+        history_store.add(item("a"), b"audio a").unwrap();
+
+        let entry = trash.trash_recording(&history_store, &item("a"), "delete").unwrap();
+        assert_eq!(entry.recordings, 1);
+        assert!(!history_store.audio_path("a").exists());
+
+        let entry_dir = trash.entry_path(&entry.id);
+        assert_eq!(fs::read(entry_dir.join("a.webm")).unwrap(), b"audio a");
+        assert_eq!(history_store.read_history_at(&entry_dir.join(HISTORY_FILE)).unwrap(), vec![item("a")]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn copies_config_files() {
+        let dir = temp_dir("config");
+        let trash = Trash::new(&dir);
+        let config_path = dir.join("config.json");
+        let missing_path = dir.join("profiles.json");
+        assert!(trash.trash_config_files(&[&config_path, &missing_path], "restore").unwrap().is_none());
+
+        fs::write(&config_path, "{}").unwrap();
+        let entry = trash.trash_config_files(&[&config_path, &missing_path], "restore").unwrap().unwrap();
+        assert!(entry.has_config);
+        assert!(config_path.exists());
+        assert!(trash.entry_path(&entry.id).join("config.json").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn lists_newest_first_and_purges_old_entries() {
+        let dir = temp_dir("purge");
+        let trash = Trash::new(&dir);
+        let config_path = dir.join("config.json");
+        fs::write(&config_path, "{}").unwrap();
+
+        let old = trash.trash_config_files(&[&config_path], "restore").unwrap().unwrap();
+        let old = TrashEntry {
+            deleted_at: now_millis() - Duration::from_secs(2 * 86_400).as_millis() as i64,
+            ..old
+        };
+        fs::write(
+            trash.entry_path(&old.id).join(TRASH_INFO_FILE),
+            serde_json::to_string(&old).unwrap(),
+        )
+        .unwrap();
+        let recent = trash.trash_config_files(&[&config_path], "restore").unwrap().unwrap();
+
+        let ids: Vec<String> = trash.list().unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![recent.id.clone(), old.id.clone()]);
+
+        assert_eq!(trash.purge_older_than(Duration::from_secs(86_400)).unwrap(), vec![old.id.clone()]);
+        assert!(!trash.entry_path(&old.id).exists());
+        assert_eq!(trash.list().unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 
 // ===== CORE TYPES =====
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecordingHistoryItem {
     pub id: String,
@@ -12,15 +12,24 @@ pub struct RecordingHistoryItem {
     pub transcript: String,
     pub original_transcript: Option<String>,
     pub is_original_shown: Option<bool>,
+    /// Pinned recordings are exempt from retention sweeps
+    pub pinned: Option<bool>,
+    pub provider_id: Option<String>,
+    /// Time spent waiting for the transcription provider
+    pub latency_ms: Option<u64>,
+    /// Application that was focused when the recording was made
+    pub app_name: Option<String>,
+    pub cost_usd: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppRule {
     pub id: String,
     pub app_name: String,
     pub executable: Option<String>,
     pub enabled: bool,
+    /// Kept for older configs but not applied, see `app_rules::check_rule`
     pub shortcut: Option<String>,
     pub hold_key: Option<String>,
     pub key_combination: Option<String>,
@@ -31,6 +40,41 @@ pub struct AppRule {
     pub auto_insert: Option<bool>,
     pub priority: i32,
     pub context_formatting: Option<serde_json::Value>,
+    /// Further conditions on top of `app_name` and `executable`; all must hold
+    #[serde(default)]
+    pub matchers: Vec<AppMatcher>,
+    /// Keys this build doesn't know about, kept so saving doesn't drop them
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMatcher {
+    pub field: MatchField,
+    pub kind: MatchKind,
+    pub pattern: String,
+    /// Match when the pattern does not
+    #[serde(default)]
+    pub negate: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchField {
+    AppName,
+    /// Full path when the platform reports one, the file name otherwise
+    Executable,
+    WindowTitle,
+}
+
+/// Every kind ignores case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchKind {
+    Contains,
+    Regex,
+    Glob,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,12 +83,61 @@ pub struct SettingsProfile {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    /// Only the keys this profile overrides on top of its parent, or on top
+    /// of the user's config if it has none
     pub config: serde_json::Value,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Conditions under which the profile switches on by itself
+    #[serde(default)]
+    pub activation: Option<ProfileActivation>,
     pub created_at: i64,
     pub updated_at: i64,
     pub is_default: Option<bool>,
 }
 
+/// Every condition must hold for the profile to be picked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileActivation {
+    pub enabled: bool,
+    pub conditions: Vec<ActivationCondition>,
+    /// Breaks ties when several profiles match; the highest wins
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ActivationCondition {
+    /// Local time from `start` to `end` ("HH:MM", wrapping past midnight if
+    /// `end` is earlier) on the given weekdays, 1 = Monday; empty means daily
+    Schedule {
+        #[serde(default)]
+        days: Vec<u32>,
+        start: String,
+        end: String,
+    },
+    /// Foreground application; each given name must be part of the app's
+    App {
+        app_name: Option<String>,
+        executable: Option<String>,
+    },
+    /// Variable is set, and equal to `value` if given
+    EnvVar { name: String, value: Option<String> },
+    FileExists { path: String },
+}
+
+/// The last profile change and what caused it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSwitch {
+    pub profile_id: Option<String>,
+    pub automatic: bool,
+    pub reason: String,
+    pub switched_at: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VoiceActivationStatus {
@@ -74,13 +167,42 @@ pub struct StreamingDictationStatus {
 pub struct ActiveApplication {
     pub name: String,
     pub executable: String,
+    #[serde(default)]
+    pub executable_path: Option<String>,
     pub title: String,
     pub last_updated: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Which backend found the focused window, and why the ones tried before
+/// it didn't
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveApplicationDetection {
+    pub app: Option<ActiveApplication>,
+    /// "wayland", "x11", "unknown", or the OS name off Linux
+    pub session_type: String,
+    pub backend: Option<String>,
+    pub failures: Vec<BackendFailure>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendFailure {
+    pub backend: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProfilesData {
     pub profiles: Vec<SettingsProfile>,
     pub active_profile_id: Option<String>,
+    /// 0 for files whose profiles hold full config snapshots
+    #[serde(default)]
+    pub version: u32,
+    /// Set by a manual switch; activation rules are ignored until unpinned
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub last_switch: Option<ProfileSwitch>,
 }
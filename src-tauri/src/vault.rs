@@ -0,0 +1,548 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Config keys that hold secrets and are encrypted/masked
+pub const SECRET_KEYS: &[&str] = &["openaiApiKey", "groqApiKey", "geminiApiKey"];
+
+/// Prefix of masked secrets returned to the UI; saving a masked value keeps the stored one
+pub const MASK_PREFIX: &str = "••••••••";
+
+pub const VAULT_FILE: &str = "vault.json";
+pub const DEFAULT_KEY_FILE: &str = "vault.key";
+const ENCRYPTED_STRING_PREFIX: &str = "enc:v1:";
+const ENCRYPTED_BYTES_MAGIC: &[u8] = b"WHVLT1";
+const NONCE_LEN: usize = 12;
+const KEY_CHECK_PLAINTEXT: &str = "whispo-vault";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VaultKeySource {
+    /// Key derived from a passphrase with Argon2; the vault starts locked
+    Passphrase,
+    /// Random key stored in a local file; the vault unlocks on startup
+    KeyFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultMeta {
+    version: u32,
+    key_source: VaultKeySource,
+    salt: Option<String>,
+    key_file: Option<String>,
+    /// A known plaintext encrypted with the key, used to verify passphrases
+    check: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+    pub key_source: Option<VaultKeySource>,
+    /// Why the vault couldn't be opened, e.g. a missing key file
+    pub error: Option<String>,
+}
+
+/// Optional encryption at rest for secrets in config.json and for recordings
+pub struct Vault {
+    app_data_dir: PathBuf,
+    meta: Mutex<Option<VaultMeta>>,
+    key: Mutex<Option<[u8; 32]>>,
+    /// Set when vault.json or the key file can't be read. The vault then
+    /// counts as enabled and locked, so nothing gets written unencrypted.
+    error: Mutex<Option<String>>,
+}
+
+impl Vault {
+    /// Open the vault in `app_data_dir`. A vault that can't be opened starts
+    /// locked and reports why in its status.
+    pub fn new(app_data_dir: &Path) -> Self {
+        let vault = Self {
+            app_data_dir: app_data_dir.to_path_buf(),
+            meta: Mutex::new(None),
+            key: Mutex::new(None),
+            error: Mutex::new(None),
+        };
+        vault.reload();
+        vault
+    }
+
+    /// Re-read vault.json and, for a key file vault, the key. Used on startup
+    /// and after a backup brought its own vault.json.
+    pub fn reload(&self) {
+        *self.key.lock().unwrap() = None;
+        *self.error.lock().unwrap() = None;
+
+        let meta = match read_meta(&self.app_data_dir.join(VAULT_FILE)) {
+            Ok(meta) => meta,
+            Err(e) => {
+                *self.meta.lock().unwrap() = None;
+                *self.error.lock().unwrap() = Some(format!("{:#}", e));
+                return;
+            }
+        };
+
+        if let Some(meta) = &meta {
+            if meta.key_source == VaultKeySource::KeyFile {
+                let key = read_key_file(&self.key_file_path(meta))
+                    .and_then(|key| verify_key(&key, &meta.check).map(|_| key));
+                match key {
+                    Ok(key) => *self.key.lock().unwrap() = Some(key),
+                    Err(e) => *self.error.lock().unwrap() = Some(format!("{:#}", e)),
+                }
+            }
+        }
+        *self.meta.lock().unwrap() = meta;
+    }
+
+    pub fn status(&self) -> VaultStatus {
+        let meta = self.meta.lock().unwrap();
+        let error = self.error.lock().unwrap().clone();
+        VaultStatus {
+            enabled: meta.is_some() || error.is_some(),
+            unlocked: self.key.lock().unwrap().is_some(),
+            key_source: meta.as_ref().map(|m| m.key_source),
+            error,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.meta.lock().unwrap().is_some() || self.error.lock().unwrap().is_some()
+    }
+
+    /// Enabled, but without a key to encrypt or decrypt with
+    pub fn is_locked(&self) -> bool {
+        self.is_enabled() && self.key.lock().unwrap().is_none()
+    }
+
+    /// Turn the vault on. Existing data must be re-saved by the caller to get encrypted.
+    pub fn enable(
+        &self,
+        key_source: VaultKeySource,
+        passphrase: Option<&str>,
+        key_file: Option<String>,
+    ) -> Result<()> {
+        // An unreadable vault.json still holds the salt for existing data
+        if self.is_enabled() || self.app_data_dir.join(VAULT_FILE).exists() {
+            anyhow::bail!("Vault is already enabled");
+        }
+
+        let (key, salt) = match key_source {
+            VaultKeySource::Passphrase => {
+                let passphrase = passphrase
+                    .filter(|p| !p.is_empty())
+                    .context("A passphrase is required")?;
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                (derive_key(passphrase, &salt)?, Some(BASE64.encode(salt)))
+            }
+            VaultKeySource::KeyFile => {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                (key, None)
+            }
+        };
+
+        let meta = VaultMeta {
+            version: 1,
+            key_source,
+            salt,
+            key_file,
+            check: encrypt_string_with(&key, KEY_CHECK_PLAINTEXT)?,
+        };
+
+        if key_source == VaultKeySource::KeyFile {
+            write_key_file(&self.key_file_path(&meta), &key)?;
+        }
+
+        fs::write(
+            self.app_data_dir.join(VAULT_FILE),
+            serde_json::to_string_pretty(&meta)?,
+        )?;
+
+        *self.meta.lock().unwrap() = Some(meta);
+        *self.key.lock().unwrap() = Some(key);
+        Ok(())
+    }
+
+    /// Turn the vault off. The caller must decrypt existing data first.
+    pub fn disable(&self) -> Result<()> {
+        let meta = self.meta.lock().unwrap().take();
+        if let Some(meta) = meta {
+            if meta.key_source == VaultKeySource::KeyFile {
+                let _ = fs::remove_file(self.key_file_path(&meta));
+            }
+        }
+
+        let vault_path = self.app_data_dir.join(VAULT_FILE);
+        if vault_path.exists() {
+            fs::remove_file(vault_path)?;
+        }
+
+        *self.key.lock().unwrap() = None;
+        *self.error.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Unlock with a passphrase. A key file vault re-reads its key file
+    /// instead, e.g. after the file was put back.
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let meta = self.meta.lock().unwrap().clone();
+        let meta = match meta {
+            Some(meta) => meta,
+            None => match self.error.lock().unwrap().clone() {
+                Some(error) => anyhow::bail!("Vault can't be opened: {}", error),
+                None => anyhow::bail!("Vault is not enabled"),
+            },
+        };
+
+        let key = match meta.key_source {
+            VaultKeySource::Passphrase => {
+                let salt = BASE64
+                    .decode(meta.salt.as_deref().unwrap_or_default())
+                    .context("Vault salt is invalid")?;
+                derive_key(passphrase, &salt)?
+            }
+            VaultKeySource::KeyFile => read_key_file(&self.key_file_path(&meta))?,
+        };
+
+        verify_key(&key, &meta.check).context("Wrong passphrase")?;
+        *self.key.lock().unwrap() = Some(key);
+        *self.error.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Replace this vault with the vault.json, and key file if any, of a
+    /// restored backup in `dir`
+    pub fn restore_from(&self, dir: &Path) -> Result<()> {
+        let content = fs::read_to_string(dir.join(VAULT_FILE))?;
+        serde_json::from_str::<VaultMeta>(&content).context("Backup vault.json is invalid")?;
+
+        let key_path = dir.join(DEFAULT_KEY_FILE);
+        if key_path.exists() {
+            let key = read_key_file(&key_path)?;
+            write_key_file(&self.app_data_dir.join(DEFAULT_KEY_FILE), &key)?;
+        }
+        fs::write(self.app_data_dir.join(VAULT_FILE), content)?;
+
+        self.reload();
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        let is_passphrase = self
+            .meta
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|m| m.key_source == VaultKeySource::Passphrase)
+            .unwrap_or(false);
+
+        // A key file vault can't meaningfully be locked, the key is on disk anyway
+        if is_passphrase {
+            *self.key.lock().unwrap() = None;
+        }
+    }
+
+    pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let key = self.unlocked_key()?;
+        let mut out = ENCRYPTED_BYTES_MAGIC.to_vec();
+        out.extend(seal(&key, plaintext)?);
+        Ok(out)
+    }
+
+    /// Decrypt data written by `encrypt_bytes`; plaintext data is returned as-is
+    pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match data.strip_prefix(ENCRYPTED_BYTES_MAGIC) {
+            Some(sealed) => open(&self.unlocked_key()?, sealed),
+            None => Ok(data.to_vec()),
+        }
+    }
+
+    pub fn encrypt_string(&self, plaintext: &str) -> Result<String> {
+        encrypt_string_with(&self.unlocked_key()?, plaintext)
+    }
+
+    /// Decrypt a string written by `encrypt_string`; plaintext is returned as-is
+    pub fn decrypt_string(&self, value: &str) -> Result<String> {
+        decrypt_string_with(&self.unlocked_key()?, value)
+    }
+
+    /// Encrypt the secret keys of a config object when the vault is enabled
+    pub fn seal_secrets(&self, config: &mut Value) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        for key in SECRET_KEYS {
+            if let Some(Value::String(secret)) = config.get_mut(*key) {
+                if !secret.is_empty() && !is_encrypted_string(secret) {
+                    *secret = self.encrypt_string(secret)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt the secret keys of a config object. While the vault is locked
+    /// encrypted secrets stay encrypted, so a save can't replace them with
+    /// an empty value.
+    pub fn open_secrets(&self, config: &mut Value) {
+        for key in SECRET_KEYS {
+            if let Some(Value::String(secret)) = config.get_mut(*key) {
+                if let Ok(plaintext) = self.decrypt_string(secret) {
+                    *secret = plaintext;
+                }
+            }
+        }
+    }
+
+    fn unlocked_key(&self) -> Result<[u8; 32]> {
+        self.key.lock().unwrap().context("Vault is locked")
+    }
+
+    fn key_file_path(&self, meta: &VaultMeta) -> PathBuf {
+        meta.key_file
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.app_data_dir.join(DEFAULT_KEY_FILE))
+    }
+}
+
+pub fn is_encrypted_string(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_STRING_PREFIX)
+}
+
+pub fn is_masked(value: &str) -> bool {
+    value.starts_with(MASK_PREFIX)
+}
+
+/// Replace secret values with a mask that keeps only the last four characters
+pub fn mask_secrets(config: &mut Value) {
+    for key in SECRET_KEYS {
+        if let Some(Value::String(secret)) = config.get_mut(*key) {
+            if secret.is_empty() {
+                continue;
+            }
+            let tail: String = if is_encrypted_string(secret) {
+                String::new()
+            } else {
+                let chars: Vec<char> = secret.chars().collect();
+                chars[chars.len().saturating_sub(4)..].iter().collect()
+            };
+            *secret = format!("{}{}", MASK_PREFIX, tail);
+        }
+    }
+}
+
+fn read_meta(path: &Path) -> Result<Option<VaultMeta>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let meta = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(meta))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+fn open(key: &[u8; 32], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        anyhow::bail!("Encrypted data is truncated");
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Decryption failed"))
+}
+
+fn encrypt_string_with(key: &[u8; 32], plaintext: &str) -> Result<String> {
+    Ok(format!(
+        "{}{}",
+        ENCRYPTED_STRING_PREFIX,
+        BASE64.encode(seal(key, plaintext.as_bytes())?)
+    ))
+}
+
+fn decrypt_string_with(key: &[u8; 32], value: &str) -> Result<String> {
+    match value.strip_prefix(ENCRYPTED_STRING_PREFIX) {
+        Some(encoded) => {
+            let sealed = BASE64.decode(encoded).context("Encrypted value is not valid base64")?;
+            Ok(String::from_utf8(open(key, &sealed)?)?)
+        }
+        None => Ok(value.to_string()),
+    }
+}
+
+fn verify_key(key: &[u8; 32], check: &str) -> Result<()> {
+    if decrypt_string_with(key, check)? != KEY_CHECK_PLAINTEXT {
+        anyhow::bail!("Vault key does not match");
+    }
+    Ok(())
+}
+
+fn read_key_file(path: &Path) -> Result<[u8; 32]> {
+    let data = fs::read(path)
+        .with_context(|| format!("Failed to read vault key file {}", path.display()))?;
+    data.try_into()
+        .map_err(|_| anyhow::anyhow!("Vault key file {} is invalid", path.display()))
+}
+
+fn write_key_file(path: &Path, key: &[u8; 32]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, key)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigStore;
+    use crate::history::HistoryStore;
+    use crate::types::RecordingHistoryItem;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("whispo-vault-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A passphrase vault holding an API key, reopened as on the next start
+    fn locked_install(name: &str) -> (PathBuf, Arc<Vault>) {
+        let dir = temp_dir(name);
+        let vault = Arc::new(Vault::new(&dir));
+        vault.enable(VaultKeySource::Passphrase, Some("secret"), None).unwrap();
+        let store = ConfigStore::new(dir.clone(), vault).unwrap();
+        let mut config = store.get();
+        config["openaiApiKey"] = json!("sk-test-1234");
+        store.save(config).unwrap();
+
+        let vault = Arc::new(Vault::new(&dir));
+        assert!(vault.is_locked());
+        (dir, vault)
+    }
+
+    #[test]
+    fn saves_while_locked_keep_sealed_secrets() {
+        let (dir, vault) = locked_install("locked-save");
+        let store = ConfigStore::new(dir.clone(), vault.clone()).unwrap();
+
+        // What the settings page and the app rule commands send back
+        store.save(store.get_masked()).unwrap();
+        store.save_typed(&store.config()).unwrap();
+        assert!(is_encrypted_string(&store.config().openai_api_key));
+
+        vault.unlock("secret").unwrap();
+        assert_eq!(store.config().openai_api_key, "sk-test-1234");
+        let on_disk = fs::read_to_string(dir.join("config.json")).unwrap();
+        let on_disk: Value = serde_json::from_str(&on_disk).unwrap();
+        assert!(is_encrypted_string(on_disk["openaiApiKey"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn recordings_are_refused_while_locked() {
+        let (dir, vault) = locked_install("locked-recording");
+        let history = HistoryStore::new(&dir, vault.clone());
+        let item = RecordingHistoryItem {
+            id: "rec-1".to_string(),
+            created_at: 1,
+            duration: 1.0,
+            transcript: "hello".to_string(),
+            original_transcript: None,
+            is_original_shown: None,
+            pinned: None,
+            provider_id: None,
+            latency_ms: None,
+            app_name: None,
+            cost_usd: None,
+        };
+        assert!(history.add(item.clone(), b"audio").is_err());
+        assert!(!history.audio_path("rec-1").exists());
+
+        vault.unlock("secret").unwrap();
+        history.add(item, b"audio").unwrap();
+        assert_eq!(history.load().unwrap()[0].transcript, "hello");
+        let audio = fs::read(history.audio_path("rec-1")).unwrap();
+        assert!(audio.starts_with(ENCRYPTED_BYTES_MAGIC));
+        assert_eq!(history.read_audio("rec-1").unwrap(), b"audio");
+    }
+
+    #[test]
+    fn a_missing_key_file_starts_locked() {
+        let dir = temp_dir("missing-key");
+        let vault = Vault::new(&dir);
+        vault.enable(VaultKeySource::KeyFile, None, None).unwrap();
+        let key = fs::read(dir.join(DEFAULT_KEY_FILE)).unwrap();
+        fs::remove_file(dir.join(DEFAULT_KEY_FILE)).unwrap();
+
+        let vault = Vault::new(&dir);
+        let status = vault.status();
+        assert!(status.enabled && !status.unlocked);
+        assert!(status.error.unwrap().contains("vault key file"));
+        assert!(vault.encrypt_bytes(b"data").is_err());
+
+        // Putting the file back and unlocking picks it up again
+        fs::write(dir.join(DEFAULT_KEY_FILE), key).unwrap();
+        vault.unlock("").unwrap();
+        assert!(vault.status().error.is_none());
+    }
+
+    #[test]
+    fn an_unreadable_vault_file_is_not_replaced() {
+        let dir = temp_dir("corrupt-meta");
+        fs::write(dir.join(VAULT_FILE), "{").unwrap();
+
+        let vault = Vault::new(&dir);
+        assert!(vault.is_locked());
+        assert!(vault.status().error.is_some());
+        assert!(vault.enable(VaultKeySource::KeyFile, None, None).is_err());
+    }
+}